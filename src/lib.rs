@@ -48,7 +48,7 @@ pub mod logger;
 mod manager;
 pub mod metrics;
 pub mod prelude;
-mod report;
+pub mod report;
 mod throttle;
 mod user;
 pub mod util;
@@ -78,6 +78,7 @@ use crate::controller::{GooseControllerProtocol, GooseControllerRequest};
 use crate::goose::{GaggleUser, GooseTask, GooseTaskSet, GooseUser, GooseUserCommand};
 use crate::logger::{GooseLoggerJoinHandle, GooseLoggerTx};
 use crate::metrics::{GooseMetric, GooseMetrics};
+use crate::report::GooseReportOptions;
 #[cfg(feature = "gaggle")]
 use crate::worker::{register_shutdown_pipe_handler, GaggleMetrics};
 
@@ -384,6 +385,8 @@ pub struct GooseAttack {
     started: Option<time::Instant>,
     /// All metrics merged together.
     metrics: GooseMetrics,
+    /// Options controlling how the html report is rendered.
+    report_options: GooseReportOptions,
 }
 /// Goose's internal global state.
 impl GooseAttack {
@@ -410,6 +413,7 @@ impl GooseAttack {
             scheduler: GooseScheduler::RoundRobin,
             started: None,
             metrics: GooseMetrics::default(),
+            report_options: GooseReportOptions::default(),
         })
     }
 
@@ -444,6 +448,7 @@ impl GooseAttack {
             scheduler: GooseScheduler::RoundRobin,
             started: None,
             metrics: GooseMetrics::default(),
+            report_options: GooseReportOptions::default(),
         })
     }
 
@@ -511,6 +516,39 @@ impl GooseAttack {
         self
     }
 
+    /// Configure how the html report is rendered, if a report file is enabled with the
+    /// `--report-file` run-time option.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    /// use goose::report::GooseReportOptions;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .set_report_options(GooseReportOptions {
+    ///             script_nonce: Some("r4nd0m".to_string()),
+    ///             ..Default::default()
+    ///         })
+    ///         .register_taskset(taskset!("ExampleTasks")
+    ///             .register_task(task!(example_task))
+    ///         );
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn example_task(user: &mut GooseUser) -> GooseTaskResult {
+    ///     let _goose = user.get("/foo").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_report_options(mut self, report_options: GooseReportOptions) -> Self {
+        self.report_options = report_options;
+        self
+    }
+
     /// A load test must contain one or more [`GooseTaskSet`](./goose/struct.GooseTaskSet.html)s
     /// be registered into Goose's global state with this method for it to run.
     ///
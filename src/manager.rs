@@ -102,10 +102,10 @@ fn merge_requests_from_worker(
     // Make a mutable copy where we can merge things
     let mut merged_request = parent_request.clone();
     // Iterate over user response times, and merge into global response time
-    merged_request.raw_data.times = metrics::merge_times(
+    merged_request.raw_data.times = metrics::merge_response_histograms(&[
         merged_request.raw_data.times,
         user_request.raw_data.times.clone(),
-    );
+    ]);
     // Increment total response time counter.
     merged_request.raw_data.total_time += &user_request.raw_data.total_time;
     // Increment count of how many response counters we've seen.
@@ -120,6 +120,10 @@ fn merge_requests_from_worker(
         merged_request.raw_data.maximum_time,
         user_request.raw_data.maximum_time,
     );
+    // Merge the response times of successful requests.
+    merged_request
+        .success_data
+        .merge(&user_request.success_data);
     // Increment total success counter.
     merged_request.success_count += &user_request.success_count;
     // Increment total fail counter.
@@ -12,11 +12,12 @@ use chrono::prelude::*;
 use http::StatusCode;
 use itertools::Itertools;
 use num_format::{Locale, ToFormattedString};
-use regex::RegexSet;
+use regex::{Regex, RegexSet};
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 use std::cmp::{max, Ordering};
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem;
 use std::str::FromStr;
 use std::{f32, fmt};
 use tokio::io::AsyncWriteExt;
@@ -381,6 +382,11 @@ pub struct GooseRequestMetricAggregate {
     pub raw_data: GooseRequestMetricTimingData,
     /// Combines the raw data with statistically generated Coordinated Omission Metrics.
     pub coordinated_omission_data: Option<GooseRequestMetricTimingData>,
+    /// The raw data of successful requests only, so percentiles can exclude the latencies
+    /// of failures such as timeouts and refused connections. Only recorded when
+    /// [`GooseReportOptions::percentiles_exclude_failures`](../report/struct.GooseReportOptions.html#structfield.percentiles_exclude_failures)
+    /// is enabled.
+    pub success_data: GooseRequestMetricTimingData,
     /// Per-status-code counters, tracking how often each response code was returned for this request.
     pub status_code_counts: HashMap<u16, usize>,
     /// Total number of times this path-method request resulted in a successful (2xx) status code.
@@ -412,6 +418,7 @@ impl GooseRequestMetricAggregate {
             method,
             raw_data: GooseRequestMetricTimingData::new(None),
             coordinated_omission_data: None,
+            success_data: GooseRequestMetricTimingData::new(None),
             status_code_counts: HashMap::new(),
             success_count: 0,
             fail_count: 0,
@@ -501,6 +508,83 @@ impl GooseRequestMetricAggregate {
             second, self.average_response_time_per_second[second].average
         );
     }
+
+    /// Totals of the requests, failures and response times recorded during the given
+    /// seconds of the load test, used to exclude a warmup period from the report.
+    pub(crate) fn per_second_totals(
+        &self,
+        seconds: std::ops::Range<usize>,
+    ) -> (usize, usize, usize) {
+        let mut requests = 0;
+        let mut failures = 0;
+        let mut total_time = 0.0;
+        for second in seconds {
+            let count = self.requests_per_second.get(second).copied().unwrap_or(0) as usize;
+            requests += count;
+            failures += self.errors_per_second.get(second).copied().unwrap_or(0) as usize;
+            if let Some(average) = self.average_response_time_per_second.get(second) {
+                total_time += average.average * count as f32;
+            }
+        }
+        (requests, failures, total_time.round() as usize)
+    }
+
+    /// The first and last seconds of the load test during which this request was made,
+    /// or `None` if no requests were recorded per second.
+    pub(crate) fn active_seconds(&self) -> Option<(usize, usize)> {
+        let first = self
+            .requests_per_second
+            .iter()
+            .position(|count| *count > 0)?;
+        let last = self
+            .requests_per_second
+            .iter()
+            .rposition(|count| *count > 0)?;
+        Some((first, last))
+    }
+
+    /// Merge the metrics of another method-path pair into this one, combining counters,
+    /// response time histograms and per-second data.
+    pub(crate) fn merge(&mut self, other: &GooseRequestMetricAggregate) {
+        // Coordinated Omission data includes the raw data, so if either side has it the
+        // merged request needs it, falling back to the raw data of the other side.
+        if self.coordinated_omission_data.is_some() || other.coordinated_omission_data.is_some() {
+            let mut coordinated_omission_data = self
+                .coordinated_omission_data
+                .take()
+                .unwrap_or_else(|| self.raw_data.clone());
+            coordinated_omission_data.merge(
+                other
+                    .coordinated_omission_data
+                    .as_ref()
+                    .unwrap_or(&other.raw_data),
+            );
+            self.coordinated_omission_data = Some(coordinated_omission_data);
+        }
+        self.raw_data.merge(&other.raw_data);
+        self.success_data.merge(&other.success_data);
+        for (status_code, count) in &other.status_code_counts {
+            *self.status_code_counts.entry(*status_code).or_insert(0) += count;
+        }
+        self.success_count += other.success_count;
+        self.fail_count += other.fail_count;
+        for (second, count) in other.requests_per_second.iter().enumerate() {
+            expand_per_second_metric_array(&mut self.requests_per_second, second, 0);
+            self.requests_per_second[second] += count;
+        }
+        for (second, count) in other.errors_per_second.iter().enumerate() {
+            expand_per_second_metric_array(&mut self.errors_per_second, second, 0);
+            self.errors_per_second[second] += count;
+        }
+        for (second, average) in other.average_response_time_per_second.iter().enumerate() {
+            expand_per_second_metric_array(
+                &mut self.average_response_time_per_second,
+                second,
+                util::MovingAverage::new(),
+            );
+            self.average_response_time_per_second[second].merge(average);
+        }
+    }
 }
 
 /// Expands vectors that collect per-second data for HTML report graphs with a
@@ -591,6 +675,15 @@ impl GooseRequestMetricTimingData {
         }
     }
 
+    /// Merge another set of timing data into this one.
+    pub(crate) fn merge(&mut self, other: &GooseRequestMetricTimingData) {
+        self.times = merge_times(mem::take(&mut self.times), other.times.clone());
+        self.minimum_time = update_min_time(self.minimum_time, other.minimum_time);
+        self.maximum_time = update_max_time(self.maximum_time, other.maximum_time);
+        self.total_time += other.total_time;
+        self.counter += other.counter;
+    }
+
     /// Record a new time.
     pub(crate) fn record_time(&mut self, time_elapsed: u64) {
         // Perform this conversin only once, then re-use throughout this funciton.
@@ -614,22 +707,7 @@ impl GooseRequestMetricTimingData {
 
         // Round the time so we can combine similar times together and
         // minimize required memory to store and push upstream to the parent.
-        // No rounding for 1-100ms times.
-        let rounded_time = if time_elapsed < 100 {
-            time
-        }
-        // Round to nearest 10 for 100-500ms times.
-        else if time_elapsed < 500 {
-            ((time_elapsed as f64 / 10.0).round() * 10.0) as usize
-        }
-        // Round to nearest 100 for 500-1000ms times.
-        else if time_elapsed < 1000 {
-            ((time_elapsed as f64 / 100.0).round() * 100.0) as usize
-        }
-        // Round to nearest 1000 for all larger times.
-        else {
-            ((time_elapsed as f64 / 1000.0).round() * 1000.0) as usize
-        };
+        let rounded_time = round_response_time(time);
 
         let counter = match self.times.get(&rounded_time) {
             // We've seen this elapsed time before, increment counter.
@@ -646,6 +724,30 @@ impl GooseRequestMetricTimingData {
         debug!("incremented {} counter: {}", rounded_time, counter);
         self.times.insert(rounded_time, counter);
     }
+
+    /// The average of the recorded times, or 0 if no times were recorded.
+    pub(crate) fn average_time(&self) -> f32 {
+        if self.counter > 0 {
+            self.total_time as f32 / self.counter as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Remove a recorded time, such as of a request later marked as failed. The minimum
+    /// and maximum times aren't recalculated, so they remain bounds.
+    pub(crate) fn remove_time(&mut self, time_elapsed: u64) {
+        let time = time_elapsed as usize;
+        let rounded_time = round_response_time(time);
+        if let Some(counter) = self.times.get_mut(&rounded_time) {
+            *counter -= 1;
+            if *counter == 0 {
+                self.times.remove(&rounded_time);
+            }
+            self.total_time = self.total_time.saturating_sub(time);
+            self.counter = self.counter.saturating_sub(1);
+        }
+    }
 }
 
 /// The per-task metrics collected each time a task is invoked.
@@ -1119,8 +1221,8 @@ impl GooseMetrics {
             if fail_percent as usize == 100 || fail_percent as usize == 0 {
                 writeln!(
                     fmt,
-                    " {:<24} | {:>13} | {:>14} | {:>8.reqs_p$} | {:>7.fails_p$}",
-                    util::truncate_string(request_key, 24),
+                    " {} | {:>13} | {:>14} | {:>8.reqs_p$} | {:>7.fails_p$}",
+                    util::fit_to_width(request_key, 24),
                     total_count.to_formatted_string(&Locale::en),
                     format!(
                         "{} ({}%)",
@@ -1135,8 +1237,8 @@ impl GooseMetrics {
             } else {
                 writeln!(
                     fmt,
-                    " {:<24} | {:>13} | {:>14} | {:>8.reqs_p$} | {:>7.fails_p$}",
-                    util::truncate_string(request_key, 24),
+                    " {} | {:>13} | {:>14} | {:>8.reqs_p$} | {:>7.fails_p$}",
+                    util::fit_to_width(request_key, 24),
                     total_count.to_formatted_string(&Locale::en),
                     format!(
                         "{} ({:.1}%)",
@@ -1263,8 +1365,8 @@ impl GooseMetrics {
                 if fail_percent as usize == 100 || fail_percent as usize == 0 {
                     writeln!(
                         fmt,
-                        " {:<24} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
-                        util::truncate_string(
+                        " {} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
+                        util::fit_to_width(
                             &format!("  {}: {}", task.task_index + 1, task.task_name),
                             24
                         ),
@@ -1282,8 +1384,8 @@ impl GooseMetrics {
                 } else {
                     writeln!(
                         fmt,
-                        " {:<24} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
-                        util::truncate_string(
+                        " {} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
+                        util::fit_to_width(
                             &format!("  {}: {}", task.task_index + 1, task.task_name),
                             24
                         ),
@@ -1426,8 +1528,8 @@ impl GooseMetrics {
 
                 writeln!(
                     fmt,
-                    " {:<24} | {:>11.avg_precision$} | {:>10} | {:>11} | {:>10}",
-                    util::truncate_string(
+                    " {} | {:>11.avg_precision$} | {:>10} | {:>11} | {:>10}",
+                    util::fit_to_width(
                         &format!("  {}: {}", task.task_index + 1, task.task_name),
                         24
                     ),
@@ -1532,8 +1634,8 @@ impl GooseMetrics {
 
             writeln!(
                 fmt,
-                " {:<24} | {:>11.raw_avg_precision$} | {:>10} | {:>11} | {:>10}",
-                util::truncate_string(request_key, 24),
+                " {} | {:>11.raw_avg_precision$} | {:>10} | {:>11} | {:>10}",
+                util::fit_to_width(request_key, 24),
                 raw_average,
                 format_number(request.raw_data.minimum_time),
                 format_number(request.raw_data.maximum_time),
@@ -1646,8 +1748,8 @@ impl GooseMetrics {
             if let Some(co_data) = request.coordinated_omission_data.as_ref() {
                 writeln!(
                     fmt,
-                    " {:<24} | {:>11.co_avg_precision$} | {:>10.sd_precision$} | {:>11} | {:>10}",
-                    util::truncate_string(request_key, 24),
+                    " {} | {:>11.co_avg_precision$} | {:>10.sd_precision$} | {:>11} | {:>10}",
+                    util::fit_to_width(request_key, 24),
                     co_average,
                     standard_deviation,
                     format_number(co_maximum),
@@ -1663,8 +1765,8 @@ impl GooseMetrics {
             } else {
                 writeln!(
                     fmt,
-                    " {:<24} | {:>11} | {:>10} | {:>11} | {:>10}",
-                    util::truncate_string(request_key, 24),
+                    " {} | {:>11} | {:>10} | {:>11} | {:>10}",
+                    util::fit_to_width(request_key, 24),
                     "-",
                     "-",
                     "-",
@@ -1776,8 +1878,8 @@ impl GooseMetrics {
             // Sort response times so we can calculate a mean.
             writeln!(
                 fmt,
-                " {:<24} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
-                util::truncate_string(request_key, 24),
+                " {} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
+                util::fit_to_width(request_key, 24),
                 calculate_response_time_percentile(
                     &request.raw_data.times,
                     request.raw_data.counter,
@@ -1934,8 +2036,8 @@ impl GooseMetrics {
                 // Sort response times so we can calculate a mean.
                 writeln!(
                     fmt,
-                    " {:<24} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
-                    util::truncate_string(request_key, 24),
+                    " {} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
+                    util::fit_to_width(request_key, 24),
                     calculate_response_time_percentile(
                         &coordinated_omission_data.times,
                         coordinated_omission_data.counter,
@@ -1982,8 +2084,8 @@ impl GooseMetrics {
             } else {
                 writeln!(
                     fmt,
-                    " {:<24} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
-                    util::truncate_string(request_key, 24),
+                    " {} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6} | {:>6}",
+                    util::fit_to_width(request_key, 24),
                     "-",
                     "-",
                     "-",
@@ -2078,8 +2180,8 @@ impl GooseMetrics {
 
             writeln!(
                 fmt,
-                " {:<24} | {:>51}",
-                util::truncate_string(request_key, 24),
+                " {} | {:>51}",
+                util::fit_to_width(request_key, 24),
                 codes,
             )?;
         }
@@ -2510,14 +2612,28 @@ impl GooseAttack {
             ),
         };
 
+        // Successful response times are only tracked separately when reported on their own.
+        let record_success_data = self.report_options.percentiles_exclude_failures
+            && request_metric.coordinated_omission_elapsed == 0;
+
         // Handle a metrics update.
         if request_metric.update {
             if request_metric.success {
                 merge_request.success_count += 1;
                 merge_request.fail_count -= 1;
+                if record_success_data {
+                    merge_request
+                        .success_data
+                        .record_time(request_metric.response_time);
+                }
             } else {
                 merge_request.success_count -= 1;
                 merge_request.fail_count += 1;
+                if record_success_data {
+                    merge_request
+                        .success_data
+                        .remove_time(request_metric.response_time);
+                }
             }
         }
         // Store a new metric.
@@ -2544,6 +2660,11 @@ impl GooseAttack {
             }
             if request_metric.success {
                 merge_request.success_count += 1;
+                if record_success_data {
+                    merge_request
+                        .success_data
+                        .record_time(request_metric.response_time);
+                }
             } else {
                 merge_request.fail_count += 1;
             }
@@ -2713,55 +2834,95 @@ impl GooseAttack {
             let (stopping_seconds, stopping_minutes, stopping_hours) =
                 self.metrics.get_seconds_minutes_hours(&stopping, &stopped);
 
-            let mut report_range = format!(
-                "<p>Starting: <span>{} - {} (Duration: {:02}:{:02}:{:02})</span></p>",
-                starting.format("%Y-%m-%d %H:%M:%S").to_string(),
-                started.format("%Y-%m-%d %H:%M:%S").to_string(),
-                starting_hours,
-                starting_minutes,
-                starting_seconds,
+            let locale = &self.report_options.locale;
+            let strings = &self.report_options.strings;
+            let mut report_range = report::report_range_row(
+                &strings.starting,
+                &starting,
+                &started,
+                (starting_hours, starting_minutes, starting_seconds),
+                locale,
+                strings,
             );
 
             if self.metrics.started.is_some() {
-                report_range.push_str(&format!(
-                    "<p>Running: <span>{} - {} (Duration: {:02}:{:02}:{:02})</span></p>",
-                    started.format("%Y-%m-%d %H:%M:%S").to_string(),
-                    stopping.format("%Y-%m-%d %H:%M:%S").to_string(),
-                    running_hours,
-                    running_minutes,
-                    running_seconds,
+                report_range.push_str(&report::report_range_row(
+                    &strings.running,
+                    &started,
+                    &stopping,
+                    (running_hours, running_minutes, running_seconds),
+                    locale,
+                    strings,
                 ));
             }
 
-            report_range.push_str(&format!(
-                "<p>Stopping: <span>{} - {} (Duration: {:02}:{:02}:{:02})</span></p>",
-                stopping.format("%Y-%m-%d %H:%M:%S").to_string(),
-                stopped.format("%Y-%m-%d %H:%M:%S").to_string(),
-                stopping_hours,
-                stopping_minutes,
-                stopping_seconds,
+            report_range.push_str(&report::report_range_row(
+                &strings.stopping,
+                &stopping,
+                &stopped,
+                (stopping_hours, stopping_minutes, stopping_seconds),
+                locale,
+                strings,
             ));
 
+            if self.report_options.warmup_secs > 0 {
+                report_range.push_str(&format!(
+                    "<p>{}: <span>{}</span></p>",
+                    strings.warmup,
+                    strings
+                        .warmup_excluded
+                        .replace("{seconds}", &self.report_options.warmup_secs.to_string()),
+                ));
+            }
+
             // Build a comma separated list of hosts.
             let hosts = &self.metrics.hosts.clone().into_iter().join(", ");
 
+            // Merge requests renamed by the configured name templates and redactions, and
+            // guard against displaying the same method and name twice.
+            let requests = merge_duplicate_requests(normalize_request_names(
+                &self.metrics.requests,
+                &self.report_options.name_templates(),
+            ));
+
+            let apdex_target = self
+                .report_options
+                .apdex_target
+                .unwrap_or(report::DEFAULT_APDEX_TARGET);
+
             // Prepare requests and responses variables.
             let mut raw_request_metrics = Vec::new();
             let mut co_request_metrics = Vec::new();
             let mut raw_response_metrics = Vec::new();
+            let mut response_boxplots = Vec::new();
+            let mut endpoint_rps = Vec::new();
             let mut co_response_metrics = Vec::new();
             let mut raw_aggregate_total_count = 0;
             let mut co_aggregate_total_count = 0;
-            let mut raw_aggregate_fail_count = 0;
+            let mut table_total_count = 0;
+            let mut table_fail_count = 0;
+            let mut table_total_time = 0;
             let mut raw_aggregate_response_time_counter: usize = 0;
             let mut raw_aggregate_response_time_minimum: usize = 0;
             let mut raw_aggregate_response_time_maximum: usize = 0;
-            let mut raw_aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
+            let mut raw_response_histograms = Vec::new();
+            let mut aggregate_success_data = GooseRequestMetricTimingData::new(None);
+            let mut raw_aggregate_status_code_counts: HashMap<u16, usize> = HashMap::new();
             let mut co_aggregate_response_time_counter: usize = 0;
             let mut co_aggregate_response_time_maximum: usize = 0;
             let mut co_aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
             let mut co_data = false;
-            for (request_key, request) in self.metrics.requests.iter().sorted() {
+
+            // Optionally exclude the first seconds after the load test started from the
+            // counts, rates and averages of the request metrics table.
+            let warmup_start = (started.timestamp() - starting.timestamp()).max(0) as usize;
+            let warmup = warmup_start..warmup_start + self.report_options.warmup_secs;
+            let table_duration = self
+                .metrics
+                .duration
+                .saturating_sub(self.report_options.warmup_secs);
+
+            for (request_key, request) in requests.iter().sorted() {
                 // Determine whether or not to include Coordinated Omission data.
                 if !co_data && request.coordinated_omission_data.is_some() {
                     co_data = true;
@@ -2773,41 +2934,78 @@ impl GooseAttack {
                     .strip_prefix(&format!("{} ", request.method))
                     .unwrap()
                     .to_string();
-                let total_request_count = request.success_count + request.fail_count;
-                let (requests_per_second, failures_per_second) = per_second_calculations(
-                    self.metrics.duration,
-                    total_request_count,
-                    request.fail_count,
+                let (request_metric, total_time) = warmup_request_metric(
+                    request,
+                    &name,
+                    warmup.clone(),
+                    table_duration,
+                    starting,
+                    &self.report_options,
                 );
-                // Prepare per-request metrics.
-                raw_request_metrics.push(report::RequestMetric {
-                    method: method.to_string(),
-                    name: name.to_string(),
-                    number_of_requests: total_request_count,
-                    number_of_failures: request.fail_count,
-                    response_time_average: format!(
-                        "{:.2}",
-                        request.raw_data.total_time as f32 / request.raw_data.counter as f32
+                table_total_count += request_metric.number_of_requests;
+                table_fail_count += request_metric.number_of_failures;
+                table_total_time += total_time;
+                raw_request_metrics.push(request_metric);
+                for (status_code, count) in &request.status_code_counts {
+                    *raw_aggregate_status_code_counts
+                        .entry(*status_code)
+                        .or_insert(0) += count;
+                }
+
+                // Prepare per-response metrics, optionally of successful responses only.
+                let (response_data, response_average) =
+                    if self.report_options.percentiles_exclude_failures {
+                        (&request.success_data, request.success_data.average_time())
+                    } else {
+                        (&request.raw_data, request.raw_data.average_time())
+                    };
+                raw_response_metrics.push(
+                    report::get_response_metric(
+                        &method,
+                        &name,
+                        &response_data.times,
+                        response_data.counter,
+                        response_average,
+                        response_data.minimum_time,
+                        response_data.maximum_time,
+                    )
+                    .with_over_threshold(
+                        &response_data.times,
+                        self.report_options.over_threshold_ms,
                     ),
-                    response_time_minimum: request.raw_data.minimum_time,
-                    response_time_maximum: request.raw_data.maximum_time,
-                    requests_per_second: format!("{:.2}", requests_per_second),
-                    failures_per_second: format!("{:.2}", failures_per_second),
-                });
+                );
+                aggregate_success_data.merge(&request.success_data);
+
+                if self.report_options.endpoint_rps_graph {
+                    endpoint_rps.push((
+                        report::SeriesSummary {
+                            name: format!("{} {}", method, name),
+                            requests: request.success_count + request.fail_count,
+                            failures: request.fail_count,
+                        },
+                        self.add_timestamp_to_html_graph_data(
+                            request.requests_per_second.clone(),
+                            &starting,
+                            &started,
+                            report::GraphAggregation::Sum,
+                        ),
+                    ));
+                }
 
-                // Prepare per-response metrics.
-                raw_response_metrics.push(report::get_response_metric(
-                    &method,
-                    &name,
-                    &request.raw_data.times,
-                    request.raw_data.counter,
-                    request.raw_data.minimum_time,
-                    request.raw_data.maximum_time,
-                ));
+                if self.report_options.response_boxplot {
+                    response_boxplots.push((
+                        format!("{} {}", method, name),
+                        report::five_number_summary(
+                            &request.raw_data.times,
+                            request.raw_data.counter,
+                            request.raw_data.minimum_time,
+                            request.raw_data.maximum_time,
+                        ),
+                    ));
+                }
 
                 // Collect aggregated request and response metrics.
-                raw_aggregate_total_count += total_request_count;
-                raw_aggregate_fail_count += request.fail_count;
+                raw_aggregate_total_count += request.success_count + request.fail_count;
                 raw_aggregate_response_time_counter += request.raw_data.total_time;
                 raw_aggregate_response_time_minimum = update_min_time(
                     raw_aggregate_response_time_minimum,
@@ -2817,9 +3015,9 @@ impl GooseAttack {
                     raw_aggregate_response_time_maximum,
                     request.raw_data.maximum_time,
                 );
-                raw_aggregate_response_times =
-                    merge_times(raw_aggregate_response_times, request.raw_data.times.clone());
+                raw_response_histograms.push(request.raw_data.times.clone());
             }
+            let raw_aggregate_response_times = merge_response_histograms(&raw_response_histograms);
 
             // Generate graphs
 
@@ -2845,6 +3043,23 @@ impl GooseAttack {
                 (None, None)
             };
 
+            // Shade the warmup period excluded from the request metrics table on the graphs.
+            let graph_warmup = if self.report_options.warmup_secs > 0 {
+                Some((
+                    started,
+                    started + chrono::Duration::seconds(self.report_options.warmup_secs as i64),
+                ))
+            } else {
+                None
+            };
+            let graph_phases = report::GraphPhases {
+                starting: graph_starting,
+                started: graph_started,
+                stopping: graph_stopping,
+                stopped: graph_stopped,
+                warmup: graph_warmup,
+            };
+
             let mut total_graph_seconds = 0;
             for path_metric in self.metrics.requests.values() {
                 total_graph_seconds =
@@ -2875,13 +3090,24 @@ impl GooseAttack {
                 }
             }
 
-            let graph_rps_template = report::graph_rps_template(
-                &self.add_timestamp_to_html_graph_data(rps, &starting, &started),
-                graph_starting,
-                graph_started,
-                graph_stopping,
-                graph_stopped,
+            // Rates per minute are scaled from the average requests per second.
+            let rps_data = self.add_timestamp_to_html_graph_data(
+                rps.clone(),
+                &starting,
+                &started,
+                match self.report_options.rate_unit {
+                    report::RateUnit::PerSecond => report::GraphAggregation::Sum,
+                    report::RateUnit::PerMinute => report::GraphAggregation::Average,
+                },
             );
+            let mut graph_rps_template = report::empty_graph_template(
+                report::ReportGraph::RequestsPerSecond,
+                &rps_data,
+                &self.report_options,
+            )
+            .unwrap_or_else(|| {
+                report::graph_rps_template(&rps_data, graph_phases, &self.report_options)
+            });
 
             // Generate average response times per second graph.
             let mut response_times = vec![util::MovingAverage::new(); total_graph_seconds];
@@ -2900,75 +3126,331 @@ impl GooseAttack {
                 .map(|moving_average| moving_average.average as u32)
                 .collect::<Vec<_>>();
 
-            let graph_average_response_time_template = report::graph_average_response_time_template(
-                &self.add_timestamp_to_html_graph_data(response_times, &starting, &started),
-                graph_starting,
-                graph_started,
-                graph_stopping,
-                graph_stopped,
+            let response_times_data = self.add_timestamp_to_html_graph_data(
+                response_times.clone(),
+                &starting,
+                &started,
+                report::GraphAggregation::Average,
             );
+            let mut graph_average_response_time_template = report::empty_graph_template(
+                report::ReportGraph::AverageResponseTime,
+                &response_times_data,
+                &self.report_options,
+            )
+            .unwrap_or_else(|| {
+                report::graph_average_response_time_template(
+                    &response_times_data,
+                    graph_phases,
+                    // Goose doesn't track the per-second standard deviation, minimum or
+                    // maximum of response times, so there's no confidence band or envelope.
+                    None,
+                    None,
+                    &self.report_options,
+                )
+            });
+
+            // Optionally add a box plot of each endpoint's response times.
+            if self.report_options.response_boxplot {
+                graph_average_response_time_template.push_str("\n\n            ");
+                graph_average_response_time_template.push_str(
+                    &report::graph_response_boxplot_template(
+                        &response_boxplots,
+                        self.report_options.palette,
+                        strings,
+                    ),
+                );
+            }
+
+            // Optionally add the cumulative distribution of all response times.
+            if self.report_options.response_time_cdf {
+                graph_average_response_time_template.push_str("\n\n            ");
+                graph_average_response_time_template.push_str(
+                    &report::graph_response_time_cdf_template(
+                        &raw_aggregate_response_times,
+                        self.report_options.style.average_response_time,
+                        self.report_options.palette,
+                        strings,
+                    ),
+                );
+            }
 
             // Generate active users graph.
-            let graph_users_per_second = report::graph_users_per_second_template(
-                &self.add_timestamp_to_html_graph_data(
-                    self.metrics.users_per_second.clone(),
-                    &starting,
-                    &started,
-                ),
-                graph_starting,
-                graph_started,
-                graph_stopping,
-                graph_stopped,
+            let users_data = self.add_timestamp_to_html_graph_data(
+                self.metrics.users_per_second.clone(),
+                &starting,
+                &started,
+                report::GraphAggregation::Average,
             );
+            let graph_users_per_second = report::empty_graph_template(
+                report::ReportGraph::ActiveUsers,
+                &users_data,
+                &self.report_options,
+            )
+            .unwrap_or_else(|| {
+                report::graph_users_per_second_template(
+                    &users_data,
+                    graph_phases,
+                    &self.report_options,
+                )
+            });
+
+            // Generate the overview graph, if enabled.
+            let graph_overview_template = if self.report_options.overview_graph {
+                let mut eps = vec![0; total_graph_seconds];
+                for path_metric in self.metrics.requests.values() {
+                    for (second, count) in path_metric.errors_per_second.iter().enumerate() {
+                        eps[second] += count;
+                    }
+                }
+                report::graph_overview_template(
+                    &self.add_timestamp_to_html_graph_data(
+                        rps.clone(),
+                        &starting,
+                        &started,
+                        report::GraphAggregation::Sum,
+                    ),
+                    &self.add_timestamp_to_html_graph_data(
+                        eps,
+                        &starting,
+                        &started,
+                        report::GraphAggregation::Sum,
+                    ),
+                    &self.add_timestamp_to_html_graph_data(
+                        response_times,
+                        &starting,
+                        &started,
+                        report::GraphAggregation::Average,
+                    ),
+                    &self.add_timestamp_to_html_graph_data(
+                        self.metrics.users_per_second.clone(),
+                        &starting,
+                        &started,
+                        report::GraphAggregation::Average,
+                    ),
+                    graph_phases,
+                    &self.report_options,
+                )
+            } else {
+                "".to_string()
+            };
+
+            report::set_traffic_shares(&mut raw_request_metrics);
+
+            // Optionally add a treemap of the requests to each endpoint, including any
+            // endpoints truncated from the request metrics table.
+            if self.report_options.requests_treemap {
+                let treemap = raw_request_metrics
+                    .iter()
+                    .map(|metric| {
+                        (
+                            format!("{} {}", metric.method, metric.name),
+                            metric.number_of_requests,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                graph_rps_template.push_str("\n\n            ");
+                graph_rps_template.push_str(&report::graph_requests_treemap_template(
+                    &treemap,
+                    self.report_options.treemap_other_percentage,
+                    self.report_options.palette,
+                    strings,
+                ));
+            }
+
+            // Limit the size of the request, response and status code tables, keeping the
+            // endpoints with the most requests.
+            let total_request_rows = raw_request_metrics.len();
+            raw_request_metrics = report::truncate_rows(
+                raw_request_metrics,
+                self.report_options.max_rows_per_table,
+                |metric| metric.number_of_requests,
+            );
+            let displayed_requests = raw_request_metrics
+                .iter()
+                .map(|metric| format!("{} {}", metric.method, metric.name))
+                .collect::<HashSet<_>>();
+            raw_response_metrics.retain(|metric| {
+                displayed_requests.contains(&format!("{} {}", metric.method, metric.name))
+            });
+            response_boxplots.retain(|(request_key, _)| displayed_requests.contains(request_key));
+            endpoint_rps.retain(|(summary, _)| displayed_requests.contains(&summary.name));
+            if let Some((sort_by, direction)) = self.report_options.table_sort {
+                report::sort_metrics(
+                    &mut raw_request_metrics,
+                    &mut raw_response_metrics,
+                    sort_by,
+                    direction,
+                );
+            }
+            if self.report_options.endpoint_rps_graph {
+                graph_rps_template.push_str("\n\n            ");
+                graph_rps_template
+                    .push_str(&report::graph_endpoint_rps_template(&endpoint_rps, strings));
+            }
+            let truncated_requests_notice = |columns| {
+                report::truncated_rows_notice(
+                    raw_request_metrics.len(),
+                    total_request_rows,
+                    columns,
+                    strings,
+                )
+            };
+            let truncated_requests_row =
+                truncated_requests_notice(report::request_metrics_columns(&self.report_options));
+            let truncated_responses_row =
+                truncated_requests_notice(report::response_metrics_columns(&self.report_options));
 
             // Prepare aggregate per-request metrics.
             let (raw_aggregate_requests_per_second, raw_aggregate_failures_per_second) =
-                per_second_calculations(
-                    self.metrics.duration,
-                    raw_aggregate_total_count,
-                    raw_aggregate_fail_count,
-                );
+                per_second_calculations(table_duration, table_total_count, table_fail_count);
+            let raw_aggregate_requests_per_second = self
+                .report_options
+                .rate_unit
+                .scale(raw_aggregate_requests_per_second);
+            let raw_aggregate_failures_per_second = self
+                .report_options
+                .rate_unit
+                .scale(raw_aggregate_failures_per_second);
+            let first_seen = raw_request_metrics
+                .iter()
+                .filter_map(|metric| metric.first_seen)
+                .min();
+            let last_seen = raw_request_metrics
+                .iter()
+                .filter_map(|metric| metric.last_seen)
+                .max();
             raw_request_metrics.push(report::RequestMetric {
                 method: "".to_string(),
-                name: "Aggregated".to_string(),
-                number_of_requests: raw_aggregate_total_count,
-                number_of_failures: raw_aggregate_fail_count,
+                name: strings.aggregated.clone(),
+                number_of_requests: table_total_count,
+                number_of_failures: table_fail_count,
                 response_time_average: format!(
                     "{:.2}",
-                    raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32
+                    if table_total_count > 0 {
+                        table_total_time as f32 / table_total_count as f32
+                    } else {
+                        0.0
+                    }
                 ),
                 response_time_minimum: raw_aggregate_response_time_minimum,
                 response_time_maximum: raw_aggregate_response_time_maximum,
-                requests_per_second: format!("{:.2}", raw_aggregate_requests_per_second),
-                failures_per_second: format!("{:.2}", raw_aggregate_failures_per_second),
+                requests_per_second: report::format_rate(raw_aggregate_requests_per_second),
+                failures_per_second: report::format_rate(raw_aggregate_failures_per_second),
+                requests_per_second_value: raw_aggregate_requests_per_second,
+                failures_per_second_value: raw_aggregate_failures_per_second,
+                apdex: report::format_apdex(report::apdex(
+                    &raw_aggregate_response_times,
+                    apdex_target,
+                )),
+                requests_per_second_series: if self.report_options.rps_sparklines {
+                    Some(rps.clone())
+                } else {
+                    None
+                },
+                status_code_counts: raw_aggregate_status_code_counts,
+                traffic_share: if table_total_count > 0 { 100.0 } else { 0.0 },
+                first_seen,
+                last_seen,
+                connection_reuse: None,
             });
 
-            // Prepare aggregate per-response metrics.
-            raw_response_metrics.push(report::get_response_metric(
-                "",
-                "Aggregated",
-                &raw_aggregate_response_times,
-                raw_aggregate_total_count,
-                raw_aggregate_response_time_minimum,
-                raw_aggregate_response_time_maximum,
+            // Compare the configured users to the users achieved.
+            if self.report_options.show_achieved_users {
+                if let Some(target) = self.configuration.users {
+                    report_range.push_str(&report::achieved_users_row(
+                        target,
+                        &self.metrics.users_per_second,
+                        &self.report_options.locale,
+                        &self.report_options.strings,
+                    ));
+                }
+            }
+
+            // Show whether the load test is still running.
+            report_range.push_str(&report::freshness_badge(
+                self.metrics.stopped,
+                Local::now(),
+                strings,
+            ));
+
+            // Summarize the overall apdex in the header.
+            report_range.push_str(&format!(
+                "<p>{} (T = {} ms): <span>{}</span></p>",
+                self.report_options.strings.apdex,
+                apdex_target,
+                report::format_apdex(report::apdex(&raw_aggregate_response_times, apdex_target)),
             ));
 
+            // Find the slowest endpoint for each percentile, before aggregating.
+            let raw_responses_footer =
+                report::response_metrics_footer(&raw_response_metrics, &self.report_options);
+
+            // Prepare aggregate per-response metrics.
+            raw_response_metrics.push(if self.report_options.percentiles_exclude_failures {
+                report::get_response_metric(
+                    "",
+                    &strings.aggregated,
+                    &aggregate_success_data.times,
+                    aggregate_success_data.counter,
+                    aggregate_success_data.average_time(),
+                    aggregate_success_data.minimum_time,
+                    aggregate_success_data.maximum_time,
+                )
+                .with_over_threshold(
+                    &aggregate_success_data.times,
+                    self.report_options.over_threshold_ms,
+                )
+            } else {
+                report::get_response_metric(
+                    "",
+                    &strings.aggregated,
+                    &raw_aggregate_response_times,
+                    raw_aggregate_total_count,
+                    raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32,
+                    raw_aggregate_response_time_minimum,
+                    raw_aggregate_response_time_maximum,
+                )
+                .with_over_threshold(
+                    &raw_aggregate_response_times,
+                    self.report_options.over_threshold_ms,
+                )
+            });
+
+            // Fingerprint the metrics before they're rendered.
+            let fingerprint_template = if self.report_options.show_fingerprint {
+                report::fingerprint_template(
+                    &report::metrics_fingerprint(
+                        &raw_request_metrics,
+                        &raw_response_metrics,
+                        &report::report_errors(&self.metrics.errors),
+                    ),
+                    &self.report_options.strings,
+                )
+            } else {
+                "".to_string()
+            };
+
             // Compile the request metrics template.
             let mut raw_requests_rows = Vec::new();
-            for metric in raw_request_metrics {
-                raw_requests_rows.push(report::raw_request_metrics_row(metric));
+            for metric in raw_request_metrics.iter().cloned() {
+                raw_requests_rows.push(report::raw_request_metrics_row(
+                    metric,
+                    &self.report_options,
+                ));
             }
+            raw_requests_rows.push(truncated_requests_row);
 
             // Compile the response metrics template.
             let mut raw_responses_rows = Vec::new();
             for metric in raw_response_metrics {
-                raw_responses_rows.push(report::response_metrics_row(metric));
+                raw_responses_rows.push(report::response_metrics_row(metric, &self.report_options));
             }
+            raw_responses_rows.push(truncated_responses_row);
 
             let co_requests_template: String;
             let co_responses_template: String;
             if co_data {
-                for (request_key, request) in self.metrics.requests.iter().sorted() {
+                for (request_key, request) in requests.iter().sorted() {
                     if let Some(coordinated_omission_data) =
                         request.coordinated_omission_data.as_ref()
                     {
@@ -3001,6 +3483,7 @@ impl GooseAttack {
                             &name,
                             &coordinated_omission_data.times,
                             coordinated_omission_data.counter,
+                            co_average,
                             coordinated_omission_data.minimum_time,
                             coordinated_omission_data.maximum_time,
                         ));
@@ -3025,7 +3508,7 @@ impl GooseAttack {
                     raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32;
                 co_request_metrics.push(report::CORequestMetric {
                     method: "".to_string(),
-                    name: "Aggregated".to_string(),
+                    name: strings.aggregated.clone(),
                     response_time_average: format!(
                         "{:.2}",
                         co_aggregate_response_time_counter as f32 / co_aggregate_total_count as f32
@@ -3040,9 +3523,10 @@ impl GooseAttack {
                 // Prepare aggregate per-response metrics.
                 co_response_metrics.push(report::get_response_metric(
                     "",
-                    "Aggregated",
+                    &strings.aggregated,
                     &co_aggregate_response_times,
                     co_aggregate_total_count,
+                    co_average,
                     raw_aggregate_response_time_minimum,
                     co_aggregate_response_time_maximum,
                 ));
@@ -3056,6 +3540,7 @@ impl GooseAttack {
                 // Compile the status_code metrics template.
                 co_requests_template = report::coordinated_omission_request_metrics_template(
                     &co_request_rows.join("\n"),
+                    &self.report_options.strings,
                 );
 
                 // Compile the co_request metrics rows.
@@ -3068,6 +3553,7 @@ impl GooseAttack {
                 // Compile the status_code metrics template.
                 co_responses_template = report::coordinated_omission_response_metrics_template(
                     &co_response_rows.join("\n"),
+                    &self.report_options.strings,
                 );
             } else {
                 // If --status-codes is not enabled, return an empty template.
@@ -3076,9 +3562,9 @@ impl GooseAttack {
             }
 
             // Only build the tasks template if --no-task-metrics isn't enabled.
-            let tasks_template: String;
+            let mut task_metrics = Vec::new();
+            let mut tasks_template: String;
             if !self.configuration.no_task_metrics {
-                let mut task_metrics = Vec::new();
                 let mut aggregate_total_count = 0;
                 let mut aggregate_fail_count = 0;
                 let mut aggregate_task_time_counter: usize = 0;
@@ -3121,8 +3607,8 @@ impl GooseAttack {
                             response_time_average: format!("{:.2}", average),
                             response_time_minimum: task.min_time,
                             response_time_maximum: task.max_time,
-                            requests_per_second: format!("{:.2}", requests_per_second),
-                            failures_per_second: format!("{:.2}", failures_per_second),
+                            requests_per_second: report::format_rate(requests_per_second),
+                            failures_per_second: report::format_rate(failures_per_second),
                         });
 
                         aggregate_total_count += total_run_count;
@@ -3146,7 +3632,7 @@ impl GooseAttack {
                 task_metrics.push(report::TaskMetric {
                     is_task_set: false,
                     task: "".to_string(),
-                    name: "Aggregated".to_string(),
+                    name: strings.aggregated.clone(),
                     number_of_requests: aggregate_total_count,
                     number_of_failures: aggregate_fail_count,
                     response_time_average: format!(
@@ -3155,12 +3641,70 @@ impl GooseAttack {
                     ),
                     response_time_minimum: aggregate_task_time_minimum,
                     response_time_maximum: aggregate_task_time_maximum,
-                    requests_per_second: format!("{:.2}", aggregate_requests_per_second),
-                    failures_per_second: format!("{:.2}", aggregate_failures_per_second),
+                    requests_per_second: report::format_rate(aggregate_requests_per_second),
+                    failures_per_second: report::format_rate(aggregate_failures_per_second),
                 });
+                // Optionally build the task run time percentiles from each task's run time
+                // histogram.
+                let mut task_response_rows = Vec::new();
+                if self.report_options.task_percentiles {
+                    for (task_set_counter, task_set) in self.metrics.tasks.iter().enumerate() {
+                        for (task_counter, task) in task_set.iter().enumerate() {
+                            task_response_rows.push(report::task_response_metrics_row(
+                                &format!("{}.{}", task_set_counter, task_counter),
+                                report::get_response_metric(
+                                    "",
+                                    &task.task_name,
+                                    &task.times,
+                                    task.counter,
+                                    match task.counter {
+                                        0 => 0.0,
+                                        _ => task.total_time as f32 / task.counter as f32,
+                                    },
+                                    task.min_time,
+                                    task.max_time,
+                                )
+                                .with_over_threshold(
+                                    &task.times,
+                                    self.report_options.over_threshold_ms,
+                                ),
+                                &self.report_options,
+                            ));
+                        }
+                    }
+                    task_response_rows.push(report::task_response_metrics_row(
+                        "",
+                        report::get_response_metric(
+                            "",
+                            &strings.aggregated,
+                            &aggregate_task_times,
+                            aggregate_task_time_counter,
+                            match aggregate_task_time_counter {
+                                0 => 0.0,
+                                _ => {
+                                    self.metrics
+                                        .tasks
+                                        .iter()
+                                        .flatten()
+                                        .map(|task| task.total_time)
+                                        .sum::<usize>() as f32
+                                        / aggregate_task_time_counter as f32
+                                }
+                            },
+                            aggregate_task_time_minimum,
+                            aggregate_task_time_maximum,
+                        )
+                        .with_over_threshold(
+                            &aggregate_task_times,
+                            self.report_options.over_threshold_ms,
+                        ),
+                        &self.report_options,
+                    ));
+                }
+
                 let mut tasks_rows = Vec::new();
                 // Compile the task metrics template.
-                for metric in task_metrics {
+                for metric in task_metrics.iter().cloned() {
                     tasks_rows.push(report::task_metrics_row(metric));
                 }
 
@@ -3174,16 +3718,41 @@ impl GooseAttack {
                     }
                 }
 
-                let graph_tasks_per_second = report::graph_tasks_per_second_template(
-                    &self.add_timestamp_to_html_graph_data(tps, &starting, &started),
-                    graph_starting,
-                    graph_started,
-                    graph_stopping,
-                    graph_stopped,
+                let tps = self.add_timestamp_to_html_graph_data(
+                    tps,
+                    &starting,
+                    &started,
+                    report::GraphAggregation::Sum,
                 );
+                let graph_tasks_per_second = report::empty_graph_template(
+                    report::ReportGraph::TasksPerSecond,
+                    &tps,
+                    &self.report_options,
+                )
+                .unwrap_or_else(|| {
+                    report::graph_tasks_per_second_template(
+                        &tps,
+                        graph_phases,
+                        &self.report_options,
+                    )
+                });
 
-                tasks_template =
-                    report::task_metrics_template(&tasks_rows.join("\n"), &graph_tasks_per_second);
+                tasks_template = report::task_metrics_template(
+                    &tasks_rows.join("\n"),
+                    if self.report_options.sections.show_graphs {
+                        &graph_tasks_per_second
+                    } else {
+                        ""
+                    },
+                    &self.report_options.strings,
+                );
+                if self.report_options.task_percentiles {
+                    tasks_template.push_str("\n\n    ");
+                    tasks_template.push_str(&report::task_response_metrics_template(
+                        &task_response_rows.join("\n"),
+                        &self.report_options,
+                    ));
+                }
             } else {
                 tasks_template = "".to_string();
             }
@@ -3192,9 +3761,28 @@ impl GooseAttack {
             let errors_template: String;
             if !self.metrics.errors.is_empty() {
                 let mut error_rows = Vec::new();
-                for error in self.metrics.errors.values() {
-                    error_rows.push(report::error_row(error));
+                let errors = report::truncate_rows(
+                    self.metrics.errors.values().collect::<Vec<_>>(),
+                    self.report_options.max_rows_per_table,
+                    |error| error.occurrences,
+                );
+                for error in &errors {
+                    let redactions = &self.report_options.redactions;
+                    error_rows.push(report::error_row(
+                        &GooseErrorMetricAggregate {
+                            name: report::redact(&error.name, redactions),
+                            error: report::redact(&error.error, redactions),
+                            ..(*error).clone()
+                        },
+                        &self.report_options,
+                    ));
                 }
+                error_rows.push(report::truncated_rows_notice(
+                    errors.len(),
+                    self.metrics.errors.len(),
+                    5,
+                    strings,
+                ));
 
                 // Generate errors per second graph.
                 let mut eps = vec![0; total_graph_seconds];
@@ -3204,16 +3792,35 @@ impl GooseAttack {
                     }
                 }
 
-                let graph_eps_template = report::graph_eps_template(
-                    &self.add_timestamp_to_html_graph_data(eps, &starting, &started),
-                    graph_starting,
-                    graph_started,
-                    graph_stopping,
-                    graph_stopped,
+                // Correlate the first error with the load at the time.
+                let first_error =
+                    report::first_error(&eps, &self.metrics.users_per_second, &rps, &starting);
+
+                let eps = self.add_timestamp_to_html_graph_data(
+                    eps,
+                    &starting,
+                    &started,
+                    report::GraphAggregation::Sum,
                 );
+                let graph_eps_template = report::empty_graph_template(
+                    report::ReportGraph::ErrorsPerSecond,
+                    &eps,
+                    &self.report_options,
+                )
+                .unwrap_or_else(|| {
+                    report::graph_eps_template(&eps, graph_phases, &self.report_options)
+                });
 
-                errors_template =
-                    report::errors_template(&error_rows.join("\n"), &graph_eps_template);
+                errors_template = report::errors_template(
+                    &error_rows.join("\n"),
+                    if self.report_options.sections.show_graphs {
+                        &graph_eps_template
+                    } else {
+                        ""
+                    },
+                    first_error.as_ref(),
+                    &self.report_options.strings,
+                );
             } else {
                 errors_template = "".to_string();
             }
@@ -3221,74 +3828,117 @@ impl GooseAttack {
             // Only build the status_code template if --status-codes is enabled.
             let status_code_template: String;
             if self.configuration.status_codes {
-                let mut status_code_metrics = Vec::new();
-                let mut aggregated_status_code_counts: HashMap<u16, usize> = HashMap::new();
-                for (request_key, request) in self.metrics.requests.iter().sorted() {
-                    let method = format!("{}", request.method);
-                    // The request_key is "{method} {name}", so by stripping the "{method} "
-                    // prefix we get the name.
-                    let name = request_key
-                        .strip_prefix(&format!("{} ", request.method))
-                        .unwrap()
-                        .to_string();
-
-                    // Build a list of status codes, and update the aggregate record.
-                    let codes = prepare_status_codes(
-                        &request.status_code_counts,
-                        &mut Some(&mut aggregated_status_code_counts),
+                let (status_code_metrics, aggregated_status_code_counts, hidden) =
+                    status_code_metrics(
+                        &requests,
+                        &displayed_requests,
+                        self.report_options.status_code_grouping,
+                        self.report_options.hide_successful_status_codes,
+                        strings,
                     );
 
-                    // Add a row of data for the status code table.
-                    status_code_metrics.push(report::StatusCodeMetric {
-                        method,
-                        name,
-                        status_codes: codes,
-                    });
-                }
-
-                // Build a list of aggregate status codes.
-                let aggregated_codes =
-                    prepare_status_codes(&aggregated_status_code_counts, &mut None);
-
-                // Add a final row of aggregate data for the status code table.
-                status_code_metrics.push(report::StatusCodeMetric {
-                    method: "".to_string(),
-                    name: "Aggregated".to_string(),
-                    status_codes: aggregated_codes,
-                });
-
                 // Compile the status_code metrics rows.
                 let mut status_code_rows = Vec::new();
                 for metric in status_code_metrics {
                     status_code_rows.push(report::status_code_metrics_row(metric));
                 }
+                status_code_rows.push(report::hidden_successful_endpoints_notice(hidden, strings));
 
                 // Compile the status_code metrics template.
-                status_code_template =
-                    report::status_code_metrics_template(&status_code_rows.join("\n"));
+                let status_class_graph = if self.report_options.sections.show_graphs {
+                    report::graph_status_class_donut_template(
+                        &report::status_classes(&aggregated_status_code_counts),
+                        self.report_options.palette,
+                        strings,
+                    )
+                } else {
+                    "".to_string()
+                };
+                status_code_template = report::status_code_metrics_template(
+                    &status_code_rows.join("\n"),
+                    &status_class_graph,
+                    &self.report_options.strings,
+                );
             } else {
                 // If --status-codes is not enabled, return an empty template.
                 status_code_template = "".to_string();
             }
 
-            // Compile the report template.
-            let report = report::build_report(
-                &users,
-                &report_range,
-                hosts,
-                report::GooseReportTemplates {
-                    raw_requests_template: &raw_requests_rows.join("\n"),
-                    raw_responses_template: &raw_responses_rows.join("\n"),
-                    co_requests_template: &co_requests_template,
-                    co_responses_template: &co_responses_template,
-                    tasks_template: &tasks_template,
-                    status_codes_template: &status_code_template,
-                    errors_template: &errors_template,
-                    graph_rps_template: &graph_rps_template,
-                    graph_average_response_time_template: &graph_average_response_time_template,
-                    graph_users_per_second: &graph_users_per_second,
-                },
-            );
+            // Evaluate any configured assertions into a PASS/FAIL verdict.
+            let verdict_template = if self.report_options.assertions.is_empty() {
+                "".to_string()
+            } else {
+                report::verdict_template(
+                    &report::evaluate_assertions(&self.metrics, &self.report_options.assertions),
+                    &self.report_options.strings,
+                )
+            };
+
+            // Explain whether the throttle capped the requests per second. The time spent
+            // waiting on the throttle isn't tracked.
+            let throttle_template = if self.configuration.throttle_requests > 0 {
+                let (requests_per_second, _) =
+                    per_second_calculations(self.metrics.duration, raw_aggregate_total_count, 0);
+                report::throttle_template(
+                    Some(&report::ThrottleSummary {
+                        throttle_requests: self.configuration.throttle_requests,
+                        requests_per_second,
+                        throttled_time: None,
+                    }),
+                    &self.report_options.strings,
+                )
+            } else {
+                "".to_string()
+            };
+
+            // Compile the report template, or explain why there's nothing to report.
+            let report = if report::is_empty_report(
+                &raw_request_metrics,
+                &task_metrics,
+                &self.metrics.errors,
+            ) {
+                report::build_empty_report(&users, &report_range, hosts, &self.report_options)
+            } else {
+                // Omit the empty request sections if only tasks were recorded.
+                let build_report =
+                    if report::is_tasks_only_report(&raw_request_metrics, &task_metrics) {
+                        report::build_tasks_only_report
+                    } else {
+                        report::build_report
+                    };
+                build_report(
+                    &users,
+                    &report_range,
+                    hosts,
+                    report::GooseReportTemplates {
+                        raw_requests_template: &raw_requests_rows.join("\n"),
+                        raw_responses_template: &raw_responses_rows.join("\n"),
+                        raw_responses_footer_template: &raw_responses_footer,
+                        co_requests_template: &co_requests_template,
+                        co_responses_template: &co_responses_template,
+                        tasks_template: &tasks_template,
+                        status_codes_template: &status_code_template,
+                        errors_template: &errors_template,
+                        graph_rps_template: &graph_rps_template,
+                        graph_average_response_time_template: &graph_average_response_time_template,
+                        graph_users_per_second: &graph_users_per_second,
+                        verdict_template: &verdict_template,
+                        graph_overview_template: &graph_overview_template,
+                        throttle_template: &throttle_template,
+                        fingerprint_template: &fingerprint_template,
+                        // Goose doesn't time its own metrics collection.
+                        diagnostics_template: "",
+                    },
+                    &self.report_options,
+                )
+            };
+
+            // Minify last, once the report is complete.
+            let report = if self.report_options.minify {
+                report::minify_html(&report)
+            } else {
+                report
+            };
 
             // Write the report to file.
             if let Err(e) = report_file.write_all(report.as_ref()).await {
@@ -3301,6 +3951,31 @@ impl GooseAttack {
             // Be sure the file flushes to disk.
             report_file.flush().await?;
 
+            // Write the external style sheet alongside the report, if configured.
+            if let Some(href) = self.report_options.stylesheet_href.clone() {
+                let report_file_path = self.get_report_file_path().unwrap();
+                let stylesheet_path = std::path::Path::new(&report_file_path)
+                    .parent()
+                    .unwrap_or_else(|| std::path::Path::new(""))
+                    .join(&href);
+                if let Err(e) = tokio::fs::write(
+                    &stylesheet_path,
+                    report::report_stylesheet(&self.report_options),
+                )
+                .await
+                {
+                    return Err(GooseError::InvalidOption {
+                        option: "--report-file".to_string(),
+                        value: report_file_path,
+                        detail: format!(
+                            "Failed to create report style sheet {}: {}",
+                            stylesheet_path.display(),
+                            e
+                        ),
+                    });
+                }
+            }
+
             info!(
                 "wrote html report file to: {}",
                 self.get_report_file_path().unwrap()
@@ -3314,14 +3989,20 @@ impl GooseAttack {
     ///
     /// Will take a vector of (generally numerical) values and convert them into tuples where
     /// the second element will be the data point and the first element will be formatted time
-    /// it belongs to.
-    fn add_timestamp_to_html_graph_data<T: Copy>(
+    /// it belongs to. If a graph interval is configured, values are first aggregated into
+    /// one point per interval, timestamped with the first second of the interval.
+    fn add_timestamp_to_html_graph_data<T>(
         &self,
         data: Vec<T>,
         starting: &DateTime<Local>,
         started: &DateTime<Local>,
-    ) -> Vec<(String, T)> {
-        data.iter()
+        aggregation: report::GraphAggregation,
+    ) -> Vec<(String, T)>
+    where
+        T: Copy + std::iter::Sum<T> + std::ops::Div<Output = T> + std::convert::TryFrom<usize>,
+    {
+        let (seconds, values): (Vec<usize>, Vec<T>) = data
+            .iter()
             .enumerate()
             .filter(|(second, _)| {
                 // If --no-reset-metrics is used or if the load test was stopped during the
@@ -3332,7 +4013,21 @@ impl GooseAttack {
                     *second as i64 + starting.timestamp() >= started.timestamp()
                 }
             })
-            .map(|(second, &count)| {
+            .map(|(second, &count)| (second, count))
+            .unzip();
+
+        let mut interval = self.report_options.graph_interval.max(1);
+        // Aggregate into longer intervals if there would be too many points.
+        if let Some(max_graph_points) = self.report_options.max_graph_points {
+            if max_graph_points > 0 && values.len() > max_graph_points * interval {
+                interval = values.len().div_ceil(max_graph_points);
+            }
+        }
+        seconds
+            .iter()
+            .step_by(interval)
+            .zip(report::aggregate_graph_data(&values, interval, aggregation))
+            .map(|(&second, count)| {
                 (
                     Local
                         .timestamp(second as i64 + starting.timestamp(), 0)
@@ -3345,6 +4040,73 @@ impl GooseAttack {
     }
 }
 
+/// The row of a request in the request metrics table of the html report. The requests
+/// made during the `warmup` seconds of the load test are excluded from its request and
+/// failure counts, rates and average response time, with the rates relative to the
+/// `duration` after the warmup. Its minimum and maximum response times and Apdex score
+/// still include the warmup, as they aren't tracked per second. Also returns the total
+/// response time of the remaining requests, for the aggregated row.
+pub(crate) fn warmup_request_metric(
+    request: &GooseRequestMetricAggregate,
+    name: &str,
+    warmup: std::ops::Range<usize>,
+    duration: usize,
+    starting: DateTime<Local>,
+    options: &report::GooseReportOptions,
+) -> (report::RequestMetric, usize) {
+    let (warmup_requests, warmup_failures, warmup_time) = request.per_second_totals(warmup);
+    let total_request_count =
+        (request.success_count + request.fail_count).saturating_sub(warmup_requests);
+    let fail_count = request.fail_count.saturating_sub(warmup_failures);
+    let total_time = request.raw_data.total_time.saturating_sub(warmup_time);
+    // Every request may have been made during the warmup.
+    let timed_request_count = request.raw_data.counter.saturating_sub(warmup_requests);
+    let response_time_average = if timed_request_count > 0 {
+        total_time as f32 / timed_request_count as f32
+    } else {
+        0.0
+    };
+    let (requests_per_second, failures_per_second) =
+        per_second_calculations(duration, total_request_count, fail_count);
+    let requests_per_second = options.rate_unit.scale(requests_per_second);
+    let failures_per_second = options.rate_unit.scale(failures_per_second);
+    // Per-second data is relative to when the load test started starting.
+    let seen = request.active_seconds().map(|(first, last)| {
+        (
+            starting + chrono::Duration::seconds(first as i64),
+            starting + chrono::Duration::seconds(last as i64),
+        )
+    });
+    let request_metric = report::RequestMetric {
+        method: request.method.to_string(),
+        name: name.to_string(),
+        number_of_requests: total_request_count,
+        number_of_failures: fail_count,
+        response_time_average: format!("{:.2}", response_time_average),
+        response_time_minimum: request.raw_data.minimum_time,
+        response_time_maximum: request.raw_data.maximum_time,
+        requests_per_second: report::format_rate(requests_per_second),
+        failures_per_second: report::format_rate(failures_per_second),
+        requests_per_second_value: requests_per_second,
+        failures_per_second_value: failures_per_second,
+        apdex: report::format_apdex(report::apdex(
+            &request.raw_data.times,
+            options.apdex_target.unwrap_or(report::DEFAULT_APDEX_TARGET),
+        )),
+        requests_per_second_series: if options.rps_sparklines {
+            Some(request.requests_per_second.clone())
+        } else {
+            None
+        },
+        status_code_counts: request.status_code_counts.clone(),
+        traffic_share: 0.0,
+        first_seen: seen.map(|(first_seen, _)| first_seen),
+        last_seen: seen.map(|(_, last_seen)| last_seen),
+        connection_reuse: None,
+    };
+    (request_metric, total_time)
+}
+
 /// Helper to calculate requests and fails per seconds.
 pub(crate) fn per_second_calculations(duration: usize, total: usize, fail: usize) -> (f32, f32) {
     let requests_per_second;
@@ -3372,6 +4134,46 @@ pub(crate) fn format_number(number: usize) -> String {
     (number).to_formatted_string(&Locale::en)
 }
 
+/// Round a response time to the bucket it's counted in, combining similar times together
+/// to minimize the memory required to store them and push them upstream to the parent.
+pub(crate) fn round_response_time(time: usize) -> usize {
+    // No rounding for 1-100ms times.
+    if time < 100 {
+        time
+    }
+    // Round to nearest 10 for 100-500ms times.
+    else if time < 500 {
+        ((time as f64 / 10.0).round() * 10.0) as usize
+    }
+    // Round to nearest 100 for 500-1000ms times.
+    else if time < 1000 {
+        ((time as f64 / 100.0).round() * 100.0) as usize
+    }
+    // Round to nearest 1000 for all larger times.
+    else {
+        ((time as f64 / 1000.0).round() * 1000.0) as usize
+    }
+}
+
+/// Merge response time histograms, such as those of each Worker of a Gaggle, into a
+/// single histogram to compute aggregate percentiles from. Percentiles can't be averaged
+/// across histograms, the counts of each response time have to be merged first.
+///
+/// Histograms with differing bucket ranges, such as a Worker that stored unrounded times,
+/// are re-bucketed with the rounding Goose records response times with, so a response
+/// time is always counted in the same bucket.
+pub fn merge_response_histograms(histograms: &[BTreeMap<usize, usize>]) -> BTreeMap<usize, usize> {
+    let mut merged = BTreeMap::new();
+    for histogram in histograms {
+        for (response_time, count) in histogram {
+            *merged
+                .entry(round_response_time(*response_time))
+                .or_insert(0) += count;
+        }
+    }
+    merged
+}
+
 /// A helper function that merges together times.
 ///
 /// Used in `lib.rs` to merge together per-thread times, and in `metrics.rs` to
@@ -3393,6 +4195,63 @@ pub(crate) fn merge_times(
     global_response_times
 }
 
+/// Rename requests using the regular expression templates configured in
+/// [`GooseReportOptions::request_name_templates`](../report/struct.GooseReportOptions.html#structfield.request_name_templates),
+/// merging the metrics of requests that end up with the same method and name.
+pub(crate) fn normalize_request_names(
+    requests: &GooseRequestMetrics,
+    templates: &[(Regex, String)],
+) -> GooseRequestMetrics {
+    if templates.is_empty() {
+        return requests.clone();
+    }
+
+    let mut normalized: GooseRequestMetrics = HashMap::new();
+    for (request_key, request) in requests.iter().sorted() {
+        // The request_key is "{method} {name}", so by stripping the "{method} "
+        // prefix we get the name.
+        let name = request_key
+            .strip_prefix(&format!("{} ", request.method))
+            .unwrap_or(request_key);
+        let name = templates
+            .iter()
+            .fold(name.to_string(), |name, (regex, replacement)| {
+                regex.replace_all(&name, replacement.as_str()).to_string()
+            });
+        let key = format!("{} {}", request.method, name);
+        if let Some(merged_request) = normalized.get_mut(&key) {
+            merged_request.merge(request);
+        } else {
+            let mut request = request.clone();
+            request.path = name;
+            normalized.insert(key, request);
+        }
+    }
+    normalized
+}
+
+/// Merge requests that share the same method and name, for example a request keyed without
+/// its method, so each is displayed once and all of the report tables are built from the
+/// merged counters and histograms. A warning is logged for each duplicate.
+pub(crate) fn merge_duplicate_requests(requests: GooseRequestMetrics) -> GooseRequestMetrics {
+    let mut merged: GooseRequestMetrics = HashMap::new();
+    for (request_key, request) in requests.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+        // The request_key is normally "{method} {name}", so by stripping the "{method} "
+        // prefix we get the name.
+        let name = request_key
+            .strip_prefix(&format!("{} ", request.method))
+            .unwrap_or(&request_key);
+        let key = format!("{} {}", request.method, name);
+        if let Some(existing) = merged.get_mut(&key) {
+            warn!("merging duplicate request metrics for {}", key);
+            existing.merge(&request);
+        } else {
+            merged.insert(key, request);
+        }
+    }
+    merged
+}
+
 /// A helper function to update the global minimum time based on local time.
 pub(crate) fn update_min_time(mut global_min: usize, min: usize) -> usize {
     if global_min == 0 || (min > 0 && min < global_min) {
@@ -3409,7 +4268,8 @@ pub(crate) fn update_max_time(mut global_max: usize, max: usize) -> usize {
     global_max
 }
 
-/// Get the response time that a certain number of percent of the requests finished within.
+/// Get the response time that a certain number of percent of the requests finished within,
+/// formatted for display.
 pub(crate) fn calculate_response_time_percentile(
     response_times: &BTreeMap<usize, usize>,
     total_requests: usize,
@@ -3417,6 +4277,23 @@ pub(crate) fn calculate_response_time_percentile(
     max: usize,
     percent: f32,
 ) -> String {
+    format_number(response_time_percentile(
+        response_times,
+        total_requests,
+        min,
+        max,
+        percent,
+    ))
+}
+
+/// Get the response time that a certain number of percent of the requests finished within.
+pub(crate) fn response_time_percentile(
+    response_times: &BTreeMap<usize, usize>,
+    total_requests: usize,
+    min: usize,
+    max: usize,
+    percent: f32,
+) -> usize {
     let percentile_request = (total_requests as f32 * percent).round() as usize;
     debug!(
         "percentile: {}, request {} of total {}",
@@ -3429,18 +4306,86 @@ pub(crate) fn calculate_response_time_percentile(
         total_count += counter;
         if total_count >= percentile_request {
             if *value < min {
-                return format_number(min);
+                return min;
             } else if *value > max {
-                return format_number(max);
+                return max;
             } else {
-                return format_number(*value);
+                return *value;
             }
         }
     }
-    format_number(0)
+    0
 }
 
 /// Helper to count and aggregate seen status codes.
+/// Build the rows of the status code metrics table, grouped per endpoint or globally,
+/// along with the aggregated status code counts of all endpoints. Per endpoint, only the
+/// displayed requests have a row, followed by an aggregated row. If `hide_successful` is
+/// set, endpoints with only 2xx responses don't have a row either, and are counted.
+pub(crate) fn status_code_metrics(
+    requests: &GooseRequestMetrics,
+    displayed_requests: &HashSet<String>,
+    grouping: report::StatusCodeGrouping,
+    hide_successful: bool,
+    strings: &report::ReportStrings,
+) -> (Vec<report::StatusCodeMetric>, HashMap<u16, usize>, usize) {
+    let mut status_code_metrics = Vec::new();
+    let mut hidden = 0;
+    let mut aggregated_status_code_counts: HashMap<u16, usize> = HashMap::new();
+    for (request_key, request) in requests.iter().sorted() {
+        let method = format!("{}", request.method);
+        // The request_key is "{method} {name}", so by stripping the "{method} "
+        // prefix we get the name.
+        let name = request_key
+            .strip_prefix(&format!("{} ", request.method))
+            .unwrap()
+            .to_string();
+
+        // Build a list of status codes, and update the aggregate record.
+        let codes = prepare_status_codes(
+            &request.status_code_counts,
+            &mut Some(&mut aggregated_status_code_counts),
+        );
+
+        // Add a row of data for the status code table, unless grouping globally or the
+        // endpoint was truncated from the request metrics table.
+        if grouping == report::StatusCodeGrouping::PerEndpoint
+            && displayed_requests.contains(request_key)
+        {
+            if hide_successful
+                && !request.status_code_counts.is_empty()
+                && request
+                    .status_code_counts
+                    .keys()
+                    .all(|status_code| (200..300).contains(status_code))
+            {
+                hidden += 1;
+                continue;
+            }
+            status_code_metrics.push(report::StatusCodeMetric {
+                method,
+                name,
+                status_codes: codes,
+            });
+        }
+    }
+
+    // Build a list of aggregate status codes.
+    let aggregated_codes = prepare_status_codes(&aggregated_status_code_counts, &mut None);
+
+    // Add a final row of aggregate data for the status code table.
+    status_code_metrics.push(report::StatusCodeMetric {
+        method: "".to_string(),
+        name: match grouping {
+            report::StatusCodeGrouping::PerEndpoint => strings.aggregated.clone(),
+            report::StatusCodeGrouping::Global => strings.all_endpoints.clone(),
+        },
+        status_codes: aggregated_codes,
+    });
+
+    (status_code_metrics, aggregated_status_code_counts, hidden)
+}
+
 pub(crate) fn prepare_status_codes(
     status_code_counts: &HashMap<u16, usize>,
     aggregate_counts: &mut Option<&mut HashMap<u16, usize>>,
@@ -3513,6 +4458,128 @@ mod test {
         assert_eq!(&global_response_times, &local_response_times);
     }
 
+    #[tokio::test]
+    async fn percentiles_exclude_failures() {
+        use gumdrop::Options;
+
+        const EMPTY_ARGS: Vec<&str> = vec![];
+        let request_metric = |response_time: u128, success: bool| {
+            let raw_request = GooseRawRequest::new(GooseMethod::Get, "/", vec![], "");
+            let mut request_metric = GooseRequestMetric::new(raw_request, "/", 0, 0);
+            request_metric.set_response_time(response_time);
+            request_metric.success = success;
+            request_metric
+        };
+        let configuration = GooseConfiguration::parse_args_default(&EMPTY_ARGS).unwrap();
+        let mut goose_attack = GooseAttack::initialize_with_config(configuration)
+            .unwrap()
+            .set_report_options(report::GooseReportOptions {
+                percentiles_exclude_failures: true,
+                ..Default::default()
+            });
+        for _ in 0..49 {
+            goose_attack
+                .record_request_metric(&request_metric(10, true))
+                .await;
+        }
+        // A failed request that timed out after 30 seconds.
+        goose_attack
+            .record_request_metric(&request_metric(30_000, false))
+            .await;
+
+        let p99 = |data: &GooseRequestMetricTimingData| {
+            response_time_percentile(
+                &data.times,
+                data.counter,
+                data.minimum_time,
+                data.maximum_time,
+                0.99,
+            )
+        };
+        let request = &goose_attack.metrics.requests["GET /"];
+        assert_eq!(p99(&request.raw_data), 30_000);
+        assert_eq!(p99(&request.success_data), 10);
+        assert_eq!(request.success_data.average_time(), 10.0);
+        let metric = report::get_response_metric(
+            "GET",
+            "/",
+            &request.success_data.times,
+            request.success_data.counter,
+            request.success_data.average_time(),
+            request.success_data.minimum_time,
+            request.success_data.maximum_time,
+        );
+        assert_eq!(metric.percentile_99, "10");
+
+        // A request later marked as failed is removed from the successful data.
+        goose_attack
+            .record_request_metric(&request_metric(5_000, true))
+            .await;
+        let mut update = request_metric(5_000, false);
+        update.update = true;
+        goose_attack.record_request_metric(&update).await;
+        let request = &goose_attack.metrics.requests["GET /"];
+        assert_eq!(request.success_data.counter, 49);
+        assert_eq!(request.success_data.total_time, 490);
+        assert!(!request.success_data.times.contains_key(&5_000));
+
+        // Without successful responses the average doesn't divide by zero.
+        assert_eq!(GooseRequestMetricTimingData::new(None).average_time(), 0.0);
+
+        // Successful response times aren't tracked unless the option is enabled.
+        goose_attack.report_options = report::GooseReportOptions::default();
+        goose_attack.metrics.requests.clear();
+        goose_attack
+            .record_request_metric(&request_metric(10, true))
+            .await;
+        let request = &goose_attack.metrics.requests["GET /"];
+        assert_eq!(request.raw_data.counter, 1);
+        assert_eq!(request.success_data.counter, 0);
+    }
+
+    #[test]
+    fn merge_worker_response_histograms() {
+        // Three Workers with differing bucket ranges: the first only saw fast responses,
+        // and the third stored an unrounded time.
+        let workers = vec![
+            [(10, 50), (50, 30)].iter().copied().collect(),
+            [(120, 10), (300, 5)].iter().copied().collect(),
+            [(123, 2), (1_000, 2), (2_000, 1)].iter().copied().collect(),
+        ];
+        let merged = merge_response_histograms(&workers);
+        let expected: BTreeMap<usize, usize> = [
+            (10, 50),
+            (50, 30),
+            (120, 12),
+            (300, 5),
+            (1_000, 2),
+            (2_000, 1),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        assert_eq!(merged, expected);
+
+        // The 95th of 100 responses took 300ms, while averaging the p95 of each Worker
+        // would wrongly give 783ms.
+        let total = merged.values().sum();
+        assert_eq!(
+            response_time_percentile(&merged, total, 10, 2_000, 0.95),
+            300
+        );
+        let worker_p95 = [
+            response_time_percentile(&workers[0], 80, 10, 50, 0.95),
+            response_time_percentile(&workers[1], 15, 120, 300, 0.95),
+            response_time_percentile(&workers[2], 5, 123, 2_000, 0.95),
+        ];
+        assert_eq!(worker_p95, [50, 300, 2_000]);
+
+        // Merging is unaffected by the order of the Workers.
+        let reversed = workers.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(merge_response_histograms(&reversed), merged);
+        assert!(merge_response_histograms(&[]).is_empty());
+    }
+
     #[test]
     fn max_response_time_percentile() {
         let mut response_times: BTreeMap<usize, usize> = BTreeMap::new();
@@ -3984,6 +5051,7 @@ mod test {
                 data.clone(),
                 &Local.ymd(2021, 12, 14).and_hms(15, 12, 23),
                 &Local.ymd(2021, 12, 14).and_hms(15, 12, 25),
+                report::GraphAggregation::Sum,
             ),
             vec![
                 ("2021-12-14 15:12:25".to_string(), 345),
@@ -3998,6 +5066,7 @@ mod test {
                 data.clone(),
                 &Local.ymd(2021, 12, 14).and_hms(15, 12, 23),
                 &Local.ymd(2021, 12, 14).and_hms(15, 12, 25),
+                report::GraphAggregation::Sum,
             ),
             vec![
                 ("2021-12-14 15:12:23".to_string(), 123),
@@ -4015,6 +5084,7 @@ mod test {
                 data,
                 &Local.ymd(2021, 12, 14).and_hms(15, 12, 23),
                 &Local.ymd(2021, 12, 14).and_hms(15, 12, 25),
+                report::GraphAggregation::Sum,
             ),
             vec![
                 ("2021-12-14 15:12:23".to_string(), 123),
@@ -4024,5 +5094,337 @@ mod test {
                 ("2021-12-14 15:12:27".to_string(), 567)
             ]
         );
+
+        attack.report_options.graph_interval = 2;
+        assert_eq!(
+            attack.add_timestamp_to_html_graph_data(
+                vec![123, 234, 345, 456, 567],
+                &Local.with_ymd_and_hms(2021, 12, 14, 15, 12, 23).unwrap(),
+                &Local.with_ymd_and_hms(2021, 12, 14, 15, 12, 25).unwrap(),
+                report::GraphAggregation::Sum,
+            ),
+            vec![
+                ("2021-12-14 15:12:23".to_string(), 357),
+                ("2021-12-14 15:12:25".to_string(), 801),
+                ("2021-12-14 15:12:27".to_string(), 567)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_request_names() {
+        let mut requests: GooseRequestMetrics = HashMap::new();
+        for (path, times) in &[("/user/123", vec![10, 20]), ("/user/456", vec![30])] {
+            let mut request = GooseRequestMetricAggregate::new(path, GooseMethod::Get, 0);
+            for (second, time) in times.iter().enumerate() {
+                request.record_time(*time, false);
+                request.set_status_code(200);
+                request.record_requests_per_second(second);
+                request.success_count += 1;
+            }
+            requests.insert(format!("GET {}", path), request);
+        }
+        requests.insert(
+            "GET /about".to_string(),
+            GooseRequestMetricAggregate::new("/about", GooseMethod::Get, 0),
+        );
+
+        // Without templates the requests are unchanged.
+        assert_eq!(normalize_request_names(&requests, &[]), requests);
+
+        let templates = vec![(Regex::new(r"/user/\d+").unwrap(), "/user/:id".to_string())];
+        let normalized = normalize_request_names(&requests, &templates);
+        assert_eq!(normalized.len(), 2);
+        assert!(normalized.contains_key("GET /about"));
+        let user = normalized.get("GET /user/:id").unwrap();
+        assert_eq!(user.path, "/user/:id");
+        assert_eq!(user.success_count, 3);
+        assert_eq!(user.raw_data.counter, 3);
+        assert_eq!(user.raw_data.total_time, 60);
+        assert_eq!(user.raw_data.minimum_time, 10);
+        assert_eq!(user.raw_data.maximum_time, 30);
+        assert_eq!(user.raw_data.times.values().sum::<usize>(), 3);
+        assert_eq!(user.status_code_counts.get(&200), Some(&3));
+        assert_eq!(user.requests_per_second, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_merge_duplicate_requests() {
+        let mut requests: GooseRequestMetrics = HashMap::new();
+        for (key, times, status_code) in &[
+            ("GET /", vec![10, 20], 200),
+            ("/", vec![30], 500),
+            ("GET /about", vec![5], 200),
+        ] {
+            let mut request = GooseRequestMetricAggregate::new("/", GooseMethod::Get, 0);
+            for time in times {
+                request.record_time(*time, false);
+                request.set_status_code(*status_code);
+                if *status_code == 200 {
+                    request.success_count += 1;
+                } else {
+                    request.fail_count += 1;
+                }
+            }
+            requests.insert(key.to_string(), request);
+        }
+
+        let merged = merge_duplicate_requests(requests);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged.get("GET /about").unwrap().raw_data.counter, 1);
+        let request = merged.get("GET /").unwrap();
+        assert_eq!(request.success_count, 2);
+        assert_eq!(request.fail_count, 1);
+        assert_eq!(request.raw_data.counter, 3);
+        assert_eq!(request.raw_data.total_time, 60);
+        assert_eq!(request.raw_data.minimum_time, 10);
+        assert_eq!(request.raw_data.maximum_time, 30);
+        assert_eq!(request.raw_data.times.values().sum::<usize>(), 3);
+        assert_eq!(request.status_code_counts.get(&200), Some(&2));
+        assert_eq!(request.status_code_counts.get(&500), Some(&1));
+    }
+
+    #[test]
+    fn test_redactions() {
+        let mut requests: GooseRequestMetrics = HashMap::new();
+        let path = "http://api.internal.example.com/users";
+        let mut request = GooseRequestMetricAggregate::new(path, GooseMethod::Get, 0);
+        request.record_time(10, false);
+        request.success_count += 1;
+        requests.insert(format!("GET {}", path), request);
+
+        let options = report::GooseReportOptions {
+            redactions: vec![Regex::new(r"[a-z]+\.internal\.example\.com").unwrap()],
+            ..Default::default()
+        };
+        let requests = normalize_request_names(&requests, &options.name_templates());
+        let request = requests.get("GET http://***/users").unwrap();
+
+        let row = report::raw_request_metrics_row(
+            report::RequestMetric {
+                method: "GET".to_string(),
+                name: request.path.clone(),
+                number_of_requests: 1,
+                number_of_failures: 0,
+                response_time_average: "10.00".to_string(),
+                response_time_minimum: 10,
+                response_time_maximum: 10,
+                requests_per_second: "1.00".to_string(),
+                failures_per_second: "0.00".to_string(),
+                requests_per_second_value: 1.00,
+                failures_per_second_value: 0.00,
+                apdex: "1.00".to_string(),
+                requests_per_second_series: None,
+                status_code_counts: HashMap::new(),
+                traffic_share: 0.0,
+                first_seen: None,
+                last_seen: None,
+                connection_reuse: None,
+            },
+            &options,
+        );
+        let report = report::build_report(
+            "1",
+            "",
+            "http://api.internal.example.com",
+            report::GooseReportTemplates {
+                raw_requests_template: &row,
+                raw_responses_template: "",
+                raw_responses_footer_template: "",
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: "",
+                status_codes_template: "",
+                errors_template: "",
+                graph_rps_template: "",
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: "",
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: "",
+            },
+            &options,
+        );
+        assert!(!report.contains("internal"));
+        assert!(report.contains("<p>Target Host: <span>http://***</span></p>"));
+        assert!(report.contains("<td>http://***/users</td>"));
+    }
+
+    #[test]
+    fn test_unicode_request_names_align() {
+        let mut metrics = GooseMetrics {
+            duration: 1,
+            ..Default::default()
+        };
+        for path in &["/about", "/关于我们"] {
+            let mut request = GooseRequestMetricAggregate::new(path, GooseMethod::Get, 0);
+            request.record_time(10, false);
+            request.success_count += 1;
+            metrics.requests.insert(format!("GET {}", path), request);
+        }
+
+        // The display columns of each "|" separator in a row.
+        let separators = |line: &str| {
+            line.match_indices('|')
+                .map(|(index, _)| util::display_width(&line[..index]))
+                .collect::<Vec<_>>()
+        };
+        let output = format!("{}", metrics);
+        let mut aligned_tables = 0;
+        // Each table starts with a header row.
+        for table in output.split(" Name ") {
+            let ascii = table.lines().find(|line| line.contains("GET /about"));
+            let wide = table.lines().find(|line| line.contains("GET /关于我们"));
+            if let (Some(ascii), Some(wide)) = (ascii, wide) {
+                assert!(!separators(ascii).is_empty());
+                assert_eq!(separators(ascii), separators(wide));
+                aligned_tables += 1;
+            }
+        }
+        assert!(aligned_tables >= 2);
+    }
+
+    #[test]
+    fn test_status_code_grouping() {
+        let mut requests: GooseRequestMetrics = HashMap::new();
+        for path in &["/", "/about"] {
+            let mut request = GooseRequestMetricAggregate::new(path, GooseMethod::Get, 0);
+            request.status_code_counts.insert(200, 3);
+            requests.insert(format!("GET {}", path), request);
+        }
+        requests
+            .get_mut("GET /about")
+            .unwrap()
+            .status_code_counts
+            .insert(404, 1);
+        let displayed_requests = requests.keys().cloned().collect::<HashSet<_>>();
+
+        let (rows, _, _) = status_code_metrics(
+            &requests,
+            &displayed_requests,
+            report::StatusCodeGrouping::PerEndpoint,
+            false,
+            &report::ReportStrings::default(),
+        );
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].status_codes, "3 [200]");
+        assert_eq!(rows[2].name, "Aggregated");
+
+        let (rows, counts, _) = status_code_metrics(
+            &requests,
+            &displayed_requests,
+            report::StatusCodeGrouping::Global,
+            false,
+            &report::ReportStrings::default(),
+        );
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "All endpoints");
+        assert!(rows[0].status_codes.contains("6 [200]"));
+        assert!(rows[0].status_codes.contains("1 [404]"));
+        assert_eq!(counts.get(&200), Some(&6));
+    }
+
+    #[test]
+    fn test_hide_successful_status_codes() {
+        let mut requests: GooseRequestMetrics = HashMap::new();
+        for path in &["/", "/about", "/contact"] {
+            let mut request = GooseRequestMetricAggregate::new(path, GooseMethod::Get, 0);
+            request.status_code_counts.insert(200, 3);
+            requests.insert(format!("GET {}", path), request);
+        }
+        requests
+            .get_mut("GET /about")
+            .unwrap()
+            .status_code_counts
+            .insert(500, 1);
+        let displayed_requests = requests.keys().cloned().collect::<HashSet<_>>();
+
+        // All endpoints are shown by default.
+        let (rows, _, hidden) = status_code_metrics(
+            &requests,
+            &displayed_requests,
+            report::StatusCodeGrouping::PerEndpoint,
+            false,
+            &report::ReportStrings::default(),
+        );
+        assert_eq!(rows.len(), 4);
+        assert_eq!(hidden, 0);
+        assert_eq!(
+            report::hidden_successful_endpoints_notice(hidden, &report::ReportStrings::default()),
+            ""
+        );
+
+        // The 2xx-only endpoints are hidden, but still aggregated.
+        let (rows, _, hidden) = status_code_metrics(
+            &requests,
+            &displayed_requests,
+            report::StatusCodeGrouping::PerEndpoint,
+            true,
+            &report::ReportStrings::default(),
+        );
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "/about");
+        assert_eq!(rows[1].name, "Aggregated");
+        assert!(rows[1].status_codes.contains("9 [200]"));
+        assert_eq!(hidden, 2);
+        assert!(report::hidden_successful_endpoints_notice(
+            hidden,
+            &report::ReportStrings::default()
+        )
+        .contains("2 endpoints with only successful responses hidden"));
+    }
+
+    #[test]
+    fn test_warmup_request_metric() {
+        let mut request = GooseRequestMetricAggregate::new("/", GooseMethod::Get, 0);
+        // Two slow responses during the warmup, one of them failed, and two fast
+        // responses afterwards.
+        for (second, response_time) in &[(0, 1000), (1, 1000), (5, 10), (6, 20)] {
+            request.record_time(*response_time, false);
+            request.record_requests_per_second(*second);
+            request.record_average_response_time_per_second(*second, *response_time);
+            request.success_count += 1;
+        }
+        request.success_count -= 1;
+        request.fail_count += 1;
+        request.record_errors_per_second(1);
+        assert_eq!(request.per_second_totals(0..3), (2, 1, 2000));
+
+        let starting = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 30).unwrap();
+        let options = report::GooseReportOptions::default();
+        let row = |warmup, duration| {
+            let (metric, _) =
+                warmup_request_metric(&request, "/", warmup, duration, starting, &options);
+            report::raw_request_metrics_row(metric, &options)
+        };
+
+        // Excluding the warmup leaves only the fast responses in the counts, rates and
+        // average, but not in the minimum and maximum.
+        let excluded = row(0..3, 7);
+        assert!(excluded.contains(
+            &[
+                r#"<td data-count="2" data-rps="0.29">2</td>"#,
+                r#"<td data-count="0" data-rps="0.00">0</td>"#,
+                "<td>15.00</td>",
+                "<td>10</td>",
+                "<td>1000</td>",
+            ]
+            .join("\n        ")
+        ));
+
+        // Nothing is excluded without a warmup.
+        let included = row(0..0, 10);
+        assert!(included.contains(
+            &[
+                r#"<td data-count="4" data-rps="0.40">4</td>"#,
+                r#"<td data-count="1" data-rps="0.10">1</td>"#,
+                "<td>507.50</td>",
+                "<td>10</td>",
+                "<td>1000</td>",
+            ]
+            .join("\n        ")
+        ));
     }
 }
@@ -2,18 +2,22 @@
 
 use crate::metrics;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
 
 use chrono::prelude::*;
+use itertools::Itertools;
+use regex::Regex;
 use serde::Serialize;
 use serde_json::json;
+use url::Url;
 
 /// The following templates are necessary to build an html-formatted summary report.
 #[derive(Debug)]
 pub struct GooseReportTemplates<'a> {
     pub raw_requests_template: &'a str,
     pub raw_responses_template: &'a str,
+    pub raw_responses_footer_template: &'a str,
     pub co_requests_template: &'a str,
     pub co_responses_template: &'a str,
     pub tasks_template: &'a str,
@@ -22,8 +26,1342 @@ pub struct GooseReportTemplates<'a> {
     pub graph_rps_template: &'a str,
     pub graph_average_response_time_template: &'a str,
     pub graph_users_per_second: &'a str,
+    pub verdict_template: &'a str,
+    pub graph_overview_template: &'a str,
+    pub throttle_template: &'a str,
+    pub fingerprint_template: &'a str,
+    pub diagnostics_template: &'a str,
 }
 
+/// The default apdex target response time, in milliseconds.
+pub const DEFAULT_APDEX_TARGET: usize = 500;
+
+/// The default format of dates and times in the report header.
+pub const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The echarts group of the graphs linked by [`GooseReportOptions::connect_graphs`].
+pub const GRAPH_GROUP: &str = "goose-graphs";
+
+/// Graphs with fewer points than this are drawn without LTTB sampling by default.
+pub const DEFAULT_GRAPH_SAMPLING_THRESHOLD: usize = 500;
+
+/// Options controlling how the html report is rendered.
+///
+/// Configure with
+/// [`GooseAttack::set_report_options`](../struct.GooseAttack.html#method.set_report_options).
+#[derive(Clone, Debug, Default)]
+pub struct GooseReportOptions {
+    /// Optional nonce added to every `<script>` tag, required when the report is served
+    /// with a Content-Security-Policy that only allows inline scripts with a matching
+    /// nonce. Defaults to no nonce.
+    pub script_nonce: Option<String>,
+    /// Percentiles to emphasize in the response time table, for example `vec![0.95, 0.99]`.
+    /// When set, the remaining percentiles are de-emphasized and the average response time
+    /// is added as a leading column. Defaults to no emphasis.
+    pub key_percentiles: Vec<f32>,
+    /// Label suffixes of percentiles in the response time table headers, for example
+    /// `vec![(0.95, "SLA".to_string())]` labels the 95th percentile "p95 (SLA)" to mark
+    /// it as the SLA target. Defaults to no labels.
+    pub percentile_labels: Vec<(f32, String)>,
+    /// Which sections of the html report are displayed. Defaults to all sections.
+    pub sections: ReportSections,
+    /// Display a sparkline of each endpoint's requests per second next to its RPS in the
+    /// request metrics table. Defaults to false.
+    pub rps_sparklines: bool,
+    /// Number of seconds aggregated into each point of the time-series graphs, for example
+    /// `5`, `30` or `60`. Counts are summed and averages are averaged within each interval.
+    /// Defaults to one point per second (`0` and `1` are equivalent).
+    pub graph_interval: usize,
+    /// Regular expressions and replacements applied in order to request names before the
+    /// request, response time and status code tables are built, for example
+    /// `(Regex::new(r"/user/\d+").unwrap(), "/user/:id".to_string())`. Requests whose names
+    /// become identical are merged into a single row. Defaults to no renaming.
+    pub request_name_templates: Vec<(Regex, String)>,
+    /// Response time in milliseconds above which requests are considered slow, drawn as a
+    /// horizontal "SLO" line on the average response time graph. Defaults to no line.
+    pub slow_request_threshold: Option<u32>,
+    /// Round the y axis labels of the average response time graph to at most this many
+    /// decimals, instead of labels such as "12.33333". Defaults to ECharts' formatting.
+    pub graph_axis_decimals: Option<usize>,
+    /// Unit of the request and failure rates in the request metrics table and graph.
+    /// Defaults to per second.
+    pub rate_unit: RateUnit,
+    /// Assertions evaluated against the overall metrics, rendered as a PASS/FAIL verdict
+    /// at the top of the report. Defaults to no assertions and no verdict.
+    pub assertions: Vec<ReportAssertion>,
+    /// Row height and padding of the tables. Defaults to [`TableDensity::Normal`].
+    pub table_density: TableDensity,
+    /// Disable the alternating background color of table rows. Defaults to false.
+    pub no_table_striping: bool,
+    /// Graphs with fewer points than this are drawn exactly, larger graphs are downsampled
+    /// with LTTB sampling. Defaults to [`DEFAULT_GRAPH_SAMPLING_THRESHOLD`].
+    pub graph_sampling_threshold: Option<usize>,
+    /// Maximum number of rows in the request, response time, status code and error
+    /// tables. Larger tables keep the rows with the most requests or occurrences, and
+    /// display a notice. Defaults to no limit.
+    pub max_rows_per_table: Option<usize>,
+    /// Maximum number of points in each graph. Larger graphs are aggregated into longer
+    /// intervals until they fit. Defaults to no limit.
+    pub max_graph_points: Option<usize>,
+    /// Display an overview graph at the top of the report, with requests, errors,
+    /// response times and users stacked on a shared, linked time axis. Defaults to false.
+    pub overview_graph: bool,
+    /// How numbers, dates and times are written in the report tables and header. Defaults
+    /// to US English.
+    pub locale: LocaleConfig,
+    /// The apdex target response time T in milliseconds: responses within T are
+    /// satisfying, within 4T are tolerable and slower responses are frustrating. Defaults
+    /// to [`DEFAULT_APDEX_TARGET`].
+    pub apdex_target: Option<usize>,
+    /// Display a button that switches the request and failure counts of the request and
+    /// task tables between absolute counts and per-second rates. Defaults to false.
+    pub rate_toggle: bool,
+    /// Display a search box above the tables that, as the user types, hides the rows of
+    /// the request, response time and status code tables whose endpoint name doesn't
+    /// contain the search text. Defaults to false.
+    pub search_box: bool,
+    /// Number of seconds after the load test started that are excluded from the request
+    /// and failure counts, rates and average response times of the request metrics table,
+    /// including its aggregated row, and shaded on the graphs. Everything else includes the
+    /// warmup, as it isn't tracked per second: the response time minimums, maximums and
+    /// Apdex scores of the request metrics table, all other tables and graphs, and the
+    /// verdict of the [`GooseReportOptions::assertions`], which matches
+    /// [`evaluate_assertions`]. Defaults to 0.
+    pub warmup_secs: usize,
+    /// Overall p95 response times in milliseconds of previous load tests, labeled by run
+    /// and ordered from oldest to newest, for example `("v1.2.0".to_string(), 212.0)`.
+    /// Displayed as a small trend chart in the report header, to show whether response
+    /// times are drifting over time. Defaults to no history and no trend chart.
+    pub history: Vec<(String, f64)>,
+    /// Changes from a baseline, as rendered by [`baseline_delta_template`], within this
+    /// percentage are displayed neutral, and only larger regressions and improvements are
+    /// colored. Defaults to [`DEFAULT_REGRESSION_THRESHOLD_PCT`].
+    pub regression_threshold_pct: Option<f64>,
+    /// The host request names are linked to in the request and response time tables, for
+    /// example `https://example.com`, so endpoints can be opened from the report. Only
+    /// names that are paths, starting with `/`, are linked, and only to `http` and `https`
+    /// hosts. Defaults to no links.
+    pub request_name_host: Option<String>,
+    /// The color role of each graph, for example to draw errors in red. Defaults to
+    /// errors in [`ColorRole::Danger`] and all other graphs in [`ColorRole::Success`].
+    pub style: ReportStyle,
+    /// The percentage of requests allowed to fail by the SLA, for example `0.1` for 0.1%.
+    /// When set, the request metrics table displays how much of this error budget each
+    /// endpoint used, colored from green towards red as it approaches 100%. The cells are
+    /// empty if the percentage isn't positive. Defaults to no SLA budget column.
+    pub sla_failure_percentage: Option<f32>,
+    /// The columns displayed in the request metrics table, always in the table's own
+    /// order. Defaults to all columns.
+    pub request_columns: Option<HashSet<RequestColumn>>,
+    /// The columns displayed in the response time metrics table and its footer, always in
+    /// the table's own order. Defaults to all columns.
+    pub response_columns: Option<HashSet<ResponseColumn>>,
+    /// Regular expressions matched against the hosts, request names and errors displayed
+    /// in the report, for example to hide internal hostnames when sharing a report
+    /// externally. Matches are replaced with `***` before the html is escaped. Requests
+    /// whose names become identical are merged into a single row. Defaults to no
+    /// redaction.
+    pub redactions: Vec<Regex>,
+    /// Graphs drawn without the shaded starting and stopping regions, for example to
+    /// show the ramp on the active users graph but reduce clutter on the others. Defaults
+    /// to shading every graph.
+    pub hide_ramp_shading: HashSet<ReportGraph>,
+    /// Paths of the other files generated alongside the html report, such as JSON and CSV
+    /// exports, relative to the report. Linked from a "Downloads" list in the report
+    /// footer. Defaults to no downloads.
+    pub downloads: Vec<String>,
+    /// Wrap each section of the report with html comments naming the function that
+    /// produced it and the number of table rows, for example
+    /// `<!-- raw_request_metrics_row x42 -->`, to help debug report generation. Defaults
+    /// to false.
+    pub debug_annotations: bool,
+    /// Display a box plot of each endpoint's response times below the average response
+    /// time graph. Defaults to false.
+    pub response_boxplot: bool,
+    /// Display the cumulative distribution of response times below the average response
+    /// time graph, showing the percentage of requests that completed within any response
+    /// time. Defaults to false.
+    pub response_time_cdf: bool,
+    /// Display a table of the run time percentiles of each task, the end-to-end response
+    /// times of transactions composed of multiple requests, below the task metrics table.
+    /// Defaults to false.
+    pub task_percentiles: bool,
+    /// Display a treemap of the requests to each endpoint below the requests per second
+    /// graph, to show at a glance which endpoints dominated traffic. Defaults to false.
+    pub requests_treemap: bool,
+    /// Endpoints with less than this percentage of all requests are combined into a single
+    /// "Other" rectangle of the requests treemap. Defaults to
+    /// [`DEFAULT_TREEMAP_OTHER_PERCENTAGE`].
+    pub treemap_other_percentage: Option<f64>,
+    /// Colors of the HTTP method badges in the request and response time tables, keyed by
+    /// upper case method, for example `("DELETE".to_string(), "#a32a2a".to_string())`.
+    /// Overrides the [`DEFAULT_METHOD_COLORS`]; methods without a color are displayed in
+    /// [`ColorRole::Neutral`].
+    pub method_colors: HashMap<String, String>,
+    /// Link the report to an external style sheet at this location, relative to the
+    /// report, instead of inlining its styles, for sites whose Content-Security-Policy
+    /// blocks inline `<style>` blocks. The style sheet is built by [`report_stylesheet`],
+    /// and written alongside the report file. Defaults to inline styles.
+    pub stylesheet_href: Option<String>,
+    /// Whether the status code metrics table displays a row per endpoint, or a single row
+    /// merging all endpoints. Defaults to [`StatusCodeGrouping::PerEndpoint`].
+    pub status_code_grouping: StatusCodeGrouping,
+    /// Display a fingerprint of the metrics at the bottom of the report, as computed by
+    /// [`metrics_fingerprint`], so runs with identical metrics can be spotted without
+    /// diffing the reports. Defaults to false.
+    pub show_fingerprint: bool,
+    /// Add first seen and last seen columns to the request metrics table, with when each
+    /// endpoint's first and last requests were made, to correlate endpoints that come and
+    /// go with the graphs. Defaults to false.
+    pub show_first_last_seen: bool,
+    /// Add a header line comparing the configured number of users to the peak number of
+    /// users achieved, flagging a shortfall, for example when users stopped on errors.
+    /// Defaults to false.
+    pub show_achieved_users: bool,
+    /// Add a "Conn reuse %" column to the request metrics table, with the percentage of
+    /// each endpoint's requests sent over a reused connection. Goose's HTTP client doesn't
+    /// report whether connections were reused, so the counts must be set on each
+    /// [`RequestMetric::connection_reuse`] by the caller; endpoints without counts have
+    /// an empty cell. Defaults to false, omitting the column.
+    pub show_connection_reuse: bool,
+    /// A note displayed in a callout under the report header, such as "Spike test
+    /// simulating Black Friday, 3x normal load". Defaults to no description.
+    pub description: Option<String>,
+    /// How [`GooseReportOptions::description`] is written. Defaults to
+    /// [`DescriptionFormat::PlainText`].
+    pub description_format: DescriptionFormat,
+    /// Link the zoom of the time-series graphs, so zooming one graph to a window zooms
+    /// the others to the same window, by adding them to the [`GRAPH_GROUP`] echarts
+    /// group. Defaults to false, zooming each graph independently.
+    pub connect_graphs: bool,
+    /// How time-series graphs without data, such as the errors per second graph of a load
+    /// test without errors, are displayed. Defaults to [`EmptyGraphs::Render`].
+    pub empty_graphs: EmptyGraphs,
+    /// Render ANSI escape codes, such as the colors of some backends' error bodies, as is
+    /// in the errors table. Defaults to false, stripping them with
+    /// [`strip_ansi_escapes`].
+    pub preserve_ansi_escapes: bool,
+    /// Hide endpoints whose responses all had 2xx status codes from the status code
+    /// metrics table, leaving the endpoints worth investigating, with a note counting the
+    /// hidden endpoints. The aggregated row still includes them. Defaults to false.
+    pub hide_successful_status_codes: bool,
+    /// Errors longer than this many characters, such as server error bodies, are
+    /// truncated in the errors table, with the full error in an expandable block.
+    /// Defaults to [`DEFAULT_MAX_ERROR_LENGTH`].
+    pub max_error_length: Option<usize>,
+    /// A logo displayed at the top of the report, as a `data:` URI such as
+    /// `data:image/png;base64,...`, so the report doesn't load external images. Other URIs
+    /// are ignored with a warning. Defaults to no logo.
+    pub logo_data_uri: Option<String>,
+    /// The source of the [ECharts](https://echarts.apache.org) library, such as the
+    /// contents of `echarts.min.js`, inlined in the report instead of loading the library
+    /// from a CDN, so the graphs are drawn offline. Required for the graphs of
+    /// [`build_report_data_uri`]. Defaults to loading ECharts from a CDN.
+    pub echarts_script: Option<String>,
+    /// Display a graph of the requests per second of each endpoint below the requests per
+    /// second graph, with each endpoint's totals in the tooltip. Defaults to false.
+    pub endpoint_rps_graph: bool,
+    /// Sort the rows of the request and response time metrics tables by the given column
+    /// when building the report, so static copies viewed without JavaScript are sorted
+    /// too. Defaults to `None`, displaying the endpoints in the order they were first seen.
+    pub table_sort: Option<(SortBy, SortDirection)>,
+    /// Highlight the rows of the request metrics table of endpoints with any 4xx or 5xx
+    /// responses, including responses Goose didn't count as failures. Defaults to false.
+    pub highlight_error_status: bool,
+    /// The name of the tool that generated the report, such as a wrapper around Goose,
+    /// credited as "Generated by {name} (powered by goose vX.Y.Z)". Defaults to only
+    /// crediting Goose.
+    pub generator_name: Option<String>,
+    /// A link to the tool named by [`GooseReportOptions::generator_name`]. Ignored unless a
+    /// name is set. Defaults to no link.
+    pub generator_url: Option<String>,
+    /// Add a column to the response time metrics tables counting the responses slower
+    /// than this many milliseconds, and their percentage of all responses, for example
+    /// "2,341 (4.7%)". Defaults to no column.
+    pub over_threshold_ms: Option<usize>,
+    /// The colors of the html report, including the graphs, status classes and pass/fail
+    /// highlights. Defaults to [`Palette::Default`].
+    pub palette: Palette,
+    /// Display the percentiles of the response time metrics tables as multiples of each
+    /// endpoint's median, for example "4.2×", to compare tail latency across endpoints
+    /// with different baselines. Defaults to false, displaying milliseconds.
+    pub percentiles_relative_to_median: bool,
+    /// How the 100th percentile column of the response time metrics tables is labeled, or
+    /// whether it's hidden. Defaults to [`MaxPercentile::Percentile`].
+    pub max_percentile: MaxPercentile,
+    /// Compute the response time metrics table from successful responses only, noted in
+    /// its title, as the latencies of failures such as timeouts and refused connections
+    /// distort the percentiles. Defaults to false, including failed requests.
+    pub percentiles_exclude_failures: bool,
+    /// The build of the system under test, displayed in the report header for
+    /// traceability. Goose can't know it, so it must be supplied. Defaults to none.
+    pub build_info: BuildInfo,
+    /// The labels of the html report, for example to translate it. Defaults to English.
+    pub strings: ReportStrings,
+    /// Strip redundant whitespace from the html report with [`minify_html`] for the
+    /// smallest file, at the cost of readable source. Defaults to false.
+    pub minify: bool,
+}
+
+impl GooseReportOptions {
+    /// The configured request name templates, followed by the redactions.
+    pub(crate) fn name_templates(&self) -> Vec<(Regex, String)> {
+        self.request_name_templates
+            .iter()
+            .cloned()
+            .chain(
+                self.redactions
+                    .iter()
+                    .map(|redaction| (redaction.clone(), REDACTED.to_string())),
+            )
+            .collect()
+    }
+
+    /// Whether the starting and stopping regions are shaded on the given graph.
+    pub(crate) fn shows_ramp_shading(&self, graph: ReportGraph) -> bool {
+        !self.hide_ramp_shading.contains(&graph)
+    }
+
+    /// Whether the given column of the request metrics table is displayed.
+    fn shows_request_column(&self, column: RequestColumn) -> bool {
+        match self.request_columns.as_ref() {
+            Some(columns) => columns.contains(&column),
+            None => true,
+        }
+    }
+
+    /// Whether the given column of the response time metrics table is displayed.
+    fn shows_response_column(&self, column: ResponseColumn) -> bool {
+        if column == ResponseColumn::Percentile100
+            && self.max_percentile == MaxPercentile::HideIfRequestMax
+            && self.shows_request_column(RequestColumn::Maximum)
+        {
+            return false;
+        }
+        match self.response_columns.as_ref() {
+            Some(columns) => columns.contains(&column),
+            None => true,
+        }
+    }
+
+    /// Whether the average column of the response time metrics table is displayed.
+    fn shows_response_average(&self) -> bool {
+        !self.key_percentiles.is_empty() && self.shows_response_column(ResponseColumn::Average)
+    }
+}
+
+/// How densely table rows are displayed in the html report.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TableDensity {
+    /// Small rows, for large reports on large monitors.
+    Compact,
+    /// The default row size.
+    #[default]
+    Normal,
+    /// Roomy rows, for presentations.
+    Comfortable,
+}
+
+impl TableDensity {
+    /// The height of each table cell, and the padding inside it, in pixels.
+    fn cell_size(self) -> (usize, usize) {
+        match self {
+            TableDensity::Compact => (20, 1),
+            TableDensity::Normal => (30, 1),
+            TableDensity::Comfortable => (40, 6),
+        }
+    }
+}
+
+/// The build of the system under test, as displayed in the report header.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BuildInfo {
+    /// The git commit, for example `4a7d741`.
+    pub commit: Option<String>,
+    /// The git branch, for example `main`.
+    pub branch: Option<String>,
+    /// A free-form label such as a release or CI build number.
+    pub build_label: Option<String>,
+}
+
+/// Build the callout displaying the description of the report under the header, or an
+/// empty string if there's no description.
+pub fn description_template(description: Option<&str>, format: DescriptionFormat) -> String {
+    let description = match description.map(str::trim) {
+        Some(description) if !description.is_empty() => description,
+        _ => return "".to_string(),
+    };
+    let content = match format {
+        DescriptionFormat::PlainText => description
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph).replace('\n', "<br>")))
+            .join("\n"),
+        DescriptionFormat::Markdown => markdown_html(description),
+    };
+    format!(r#"<div class="description">{}</div>"#, content)
+}
+
+/// Convert the paragraphs and lists of a Markdown description to html.
+fn markdown_html(markdown: &str) -> String {
+    let unordered_item = Regex::new(r"^\s*[-*+]\s+(.*)$").unwrap();
+    let ordered_item = Regex::new(r"^\s*\d+[.)]\s+(.*)$").unwrap();
+    let mut blocks = Vec::new();
+    let mut paragraph = Vec::new();
+    let mut list: Option<(&str, Vec<String>)> = None;
+    for line in markdown.lines() {
+        let item = if let Some(captures) = unordered_item.captures(line) {
+            Some(("ul", captures[1].to_string()))
+        } else {
+            ordered_item
+                .captures(line)
+                .map(|captures| ("ol", captures[1].to_string()))
+        };
+        // Any other line ends a list, and a different kind of item starts a new one.
+        let ends_list = match (&list, &item) {
+            (Some((tag, _)), Some((item_tag, _))) => tag != item_tag,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if ends_list {
+            let (tag, items) = list.take().unwrap();
+            blocks.push(format!("<{tag}>{}</{tag}>", items.join(""), tag = tag));
+        }
+        match item {
+            Some((tag, text)) => {
+                if !paragraph.is_empty() {
+                    blocks.push(format!("<p>{}</p>", paragraph.join(" ")));
+                    paragraph.clear();
+                }
+                list.get_or_insert((tag, Vec::new()))
+                    .1
+                    .push(format!("<li>{}</li>", markdown_inline_html(&text)));
+            }
+            None if line.trim().is_empty() => {
+                if !paragraph.is_empty() {
+                    blocks.push(format!("<p>{}</p>", paragraph.join(" ")));
+                    paragraph.clear();
+                }
+            }
+            None => paragraph.push(markdown_inline_html(line.trim())),
+        }
+    }
+    if let Some((tag, items)) = list {
+        blocks.push(format!("<{tag}>{}</{tag}>", items.join(""), tag = tag));
+    }
+    if !paragraph.is_empty() {
+        blocks.push(format!("<p>{}</p>", paragraph.join(" ")));
+    }
+    blocks.join("\n")
+}
+
+/// Escape a line of Markdown, then convert its bold text and links to html.
+fn markdown_inline_html(text: &str) -> String {
+    let link = Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap();
+    let bold = Regex::new(r"\*\*(.+?)\*\*|__(.+?)__").unwrap();
+    let text = escape_html(text);
+    let text = link.replace_all(&text, |captures: &regex::Captures| {
+        let href = &captures[2];
+        let lowercase = href.to_ascii_lowercase();
+        if ["http://", "https://", "mailto:"]
+            .iter()
+            .any(|scheme| lowercase.starts_with(scheme))
+        {
+            format!(r#"<a href="{}">{}</a>"#, href, &captures[1])
+        } else {
+            captures[1].to_string()
+        }
+    });
+    bold.replace_all(&text, |captures: &regex::Captures| {
+        format!(
+            "<strong>{}</strong>",
+            captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .unwrap()
+                .as_str()
+        )
+    })
+    .to_string()
+}
+
+/// Build the lines of the report header describing the build of the system under test,
+/// omitting any that weren't supplied.
+pub fn build_info_template(build_info: &BuildInfo, strings: &ReportStrings) -> String {
+    [
+        (&strings.commit, &build_info.commit),
+        (&strings.branch, &build_info.branch),
+        (&strings.build, &build_info.build_label),
+    ]
+    .iter()
+    .filter_map(|(label, value)| {
+        value.as_ref().map(|value| {
+            format!(
+                "<p>{label}: <span>{value}</span></p>",
+                label = label,
+                value = escape_html(value),
+            )
+        })
+    })
+    .join("\n            ")
+}
+
+/// How the 100th percentile column of the response time metrics tables is displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MaxPercentile {
+    /// Labeled "100%ile".
+    #[default]
+    Percentile,
+    /// Labeled "Max".
+    Max,
+    /// Labeled "100%ile", and hidden if the request metrics table displays the maximum
+    /// response time, which is the same value.
+    HideIfRequestMax,
+}
+
+/// How the description of the report is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DescriptionFormat {
+    /// Plain text, with paragraphs separated by blank lines.
+    #[default]
+    PlainText,
+    /// A safe subset of Markdown: paragraphs, bold, links and lists. Any html is escaped,
+    /// and only http, https and mailto links are kept.
+    Markdown,
+}
+
+/// How the status code metrics table groups status codes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StatusCodeGrouping {
+    /// One row per endpoint, followed by an aggregated row.
+    #[default]
+    PerEndpoint,
+    /// A single row merging the status codes of all endpoints, for a quick health check.
+    Global,
+}
+
+/// A column the request and response time metrics tables can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortBy {
+    /// The endpoint name, then its method.
+    Name,
+    /// The number of requests.
+    Requests,
+    /// The number of failed requests.
+    Failures,
+    /// The average response time.
+    AvgResponseTime,
+    /// The 95th percentile response time.
+    P95,
+    /// The requests per second.
+    Rps,
+}
+
+/// The direction of a [`SortBy`] sort.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// An assertion about the overall metrics of a load test, used to produce a pass/fail
+/// verdict, for example to gate a CI pipeline.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReportAssertion {
+    /// The given percentile (for example `0.95`) of all response times must be below the
+    /// given number of milliseconds.
+    MaxResponseTimePercentile(f32, usize),
+    /// The percentage of failed requests (for example `1.0` for 1%) must be below the
+    /// given value.
+    MaxFailurePercentage(f32),
+    /// The overall requests per second must be above the given value.
+    MinRequestsPerSecond(f32),
+}
+
+/// The outcome of evaluating a single [`ReportAssertion`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AssertionResult {
+    /// Human readable description of the assertion, for example `p95 < 300 ms`.
+    pub description: String,
+    /// The threshold the measured value is compared with, for example `300.0`.
+    pub threshold: f64,
+    /// The measured value, unformatted.
+    pub value: f64,
+    /// The measured value, formatted for display.
+    pub actual: String,
+    /// Whether the assertion was met.
+    pub passed: bool,
+}
+
+/// The overall outcome of all [`ReportAssertion`]s.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReportVerdict {
+    /// True if every assertion was met.
+    pub passed: bool,
+    /// The outcome of each assertion, in the order they were configured. Exported to JSON
+    /// as `assertions`.
+    #[serde(rename = "assertions")]
+    pub results: Vec<AssertionResult>,
+}
+
+/// The unit in which request and failure rates are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RateUnit {
+    /// Rates per second, the default.
+    #[default]
+    PerSecond,
+    /// Rates per minute, more readable for low-traffic, long-running load tests.
+    PerMinute,
+}
+
+impl RateUnit {
+    /// Scale a per-second rate to this unit.
+    pub fn scale(self, per_second: f32) -> f32 {
+        match self {
+            RateUnit::PerSecond => per_second,
+            RateUnit::PerMinute => per_second * 60.0,
+        }
+    }
+
+    /// The column header for the request rate, with the given labels.
+    pub fn requests_label(self, strings: &ReportStrings) -> &str {
+        match self {
+            RateUnit::PerSecond => &strings.requests_per_second,
+            RateUnit::PerMinute => &strings.requests_per_minute,
+        }
+    }
+
+    /// The column header for the failure rate, with the given labels.
+    pub fn failures_label(self, strings: &ReportStrings) -> &str {
+        match self {
+            RateUnit::PerSecond => &strings.failures_per_second,
+            RateUnit::PerMinute => &strings.failures_per_minute,
+        }
+    }
+}
+
+/// The default colors of the HTTP method badges in the request and response time tables.
+pub const DEFAULT_METHOD_COLORS: [(&str, &str); 5] = [
+    ("GET", "#2f6fa3"),
+    ("POST", "#2c664f"),
+    ("PUT", "#b36b00"),
+    ("PATCH", "#d98c1f"),
+    ("DELETE", "#a32a2a"),
+];
+
+/// Build a table cell with the HTTP method displayed as a colored badge, using the color
+/// configured in [`GooseReportOptions::method_colors`] or the default of the
+/// [`GooseReportOptions::palette`]. Rows without a
+/// method, such as the aggregated row, get an empty cell.
+fn method_cell(method: &str, options: &GooseReportOptions) -> String {
+    if method.is_empty() {
+        return "<td></td>".to_string();
+    }
+    let color = match options.method_colors.get(method) {
+        Some(color) => color.as_str(),
+        None => DEFAULT_METHOD_COLORS
+            .iter()
+            .find(|(default_method, _)| *default_method == method)
+            .map(|(_, color)| options.palette.color(color))
+            .unwrap_or_else(|| ColorRole::Neutral.colors(options.palette).0),
+    };
+    format!(
+        r#"<td><span class="method" style="background-color: {color};">{method}</span></td>"#,
+        color = escape_html(color),
+        method = method,
+    )
+}
+
+/// Build the name displayed in a row of the request and response time tables, linked to
+/// the endpoint on [`GooseReportOptions::request_name_host`] if it's a path.
+fn request_name(name: &str, options: &GooseReportOptions) -> String {
+    let url = options
+        .request_name_host
+        .as_ref()
+        .filter(|_| name.starts_with('/'))
+        .and_then(|host| Url::parse(&format!("{}{}", host.trim_end_matches('/'), name)).ok())
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https");
+    match url {
+        Some(url) => format!(
+            r#"<a href="{url}" target="_blank" rel="noopener noreferrer">{name}</a>"#,
+            url = escape_html(url.as_str()),
+            name = escape_html(name),
+        ),
+        None => name.to_string(),
+    }
+}
+
+/// The text that redacted parts of the report are replaced with.
+const REDACTED: &str = "***";
+
+/// Replace every match of the `redactions` in `text` with `***`.
+pub fn redact(text: &str, redactions: &[Regex]) -> String {
+    redactions.iter().fold(text.to_string(), |text, redaction| {
+        redaction.replace_all(&text, REDACTED).to_string()
+    })
+}
+
+/// The semantic role of a graph's metric, mapped to the color it's drawn in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorRole {
+    /// Healthy activity such as requests or users, drawn in green. The default.
+    #[default]
+    Success,
+    /// Metrics that need attention such as response times, drawn in amber.
+    Warning,
+    /// Problems such as errors, drawn in red.
+    Danger,
+    /// Metrics without a connotation, drawn in gray.
+    Neutral,
+}
+
+impl ColorRole {
+    /// The line and area colors of graphs with this role, in the given palette.
+    pub fn colors(self, palette: Palette) -> (&'static str, &'static str) {
+        let (line_color, area_color) = match self {
+            ColorRole::Success => ("#2c664f", "#378063"),
+            ColorRole::Warning => ("#b36b00", "#d98c1f"),
+            ColorRole::Danger => ("#a32a2a", "#c24545"),
+            ColorRole::Neutral => ("#56646b", "#73828a"),
+        };
+        (palette.color(line_color), palette.color(area_color))
+    }
+}
+
+/// How the digits of large numbers are grouped.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NumberSystem {
+    /// Groups of three digits, for example 1,234,567.
+    #[default]
+    Western,
+    /// The last three digits, then groups of two, for example 12,34,567.
+    Indian,
+}
+
+/// The decimal and grouping style and the date and time format of the html report.
+///
+/// Numbers in the report tables are formatted in US English, then rewritten in this
+/// style, so the default leaves the report unchanged. Numbers in graphs and in
+/// machine-readable attributes are never localized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocaleConfig {
+    /// Separates the integer and fractional parts of a number. Defaults to `'.'`.
+    pub decimal_separator: char,
+    /// Separates groups of digits in large numbers. Defaults to `','`.
+    pub thousands_separator: char,
+    /// The [`chrono` format](https://docs.rs/chrono/*/chrono/format/strftime/index.html) of
+    /// the dates and times in the report, for example `"%d/%m/%Y %H:%M:%S"`. Defaults to
+    /// [`DEFAULT_DATETIME_FORMAT`].
+    pub datetime_format: String,
+    /// The timezone dates and times are displayed in. Defaults to the local timezone of the
+    /// load test.
+    pub utc_offset: Option<FixedOffset>,
+    /// How digits are grouped. Defaults to [`NumberSystem::Western`].
+    pub number_system: NumberSystem,
+}
+
+impl Default for LocaleConfig {
+    fn default() -> Self {
+        LocaleConfig {
+            decimal_separator: '.',
+            thousands_separator: ',',
+            datetime_format: DEFAULT_DATETIME_FORMAT.to_string(),
+            utc_offset: None,
+            number_system: NumberSystem::Western,
+        }
+    }
+}
+
+impl LocaleConfig {
+    /// Format a whole number with grouped digits, for example 1,234,567.
+    pub fn format_number(&self, number: usize) -> String {
+        self.group_digits(&number.to_string())
+    }
+
+    /// Format a date and time in the configured format and timezone.
+    pub fn format_datetime(&self, datetime: &DateTime<Local>) -> String {
+        match self.utc_offset {
+            Some(offset) => datetime
+                .with_timezone(&offset)
+                .format(&self.datetime_format)
+                .to_string(),
+            None => datetime.format(&self.datetime_format).to_string(),
+        }
+    }
+
+    /// Rewrite the US English numbers in a table cell, such as `1,234.50`, in this style.
+    /// Digits are only regrouped if they were grouped, and anything that isn't a plain
+    /// number, such as `1.2.3`, is left alone.
+    pub fn localize(&self, text: &str) -> String {
+        if *self == LocaleConfig::default() {
+            return text.to_string();
+        }
+
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut localized = String::with_capacity(text.len());
+        let mut index = 0;
+        while index < chars.len() {
+            if !chars[index].is_ascii_digit() {
+                localized.push(chars[index]);
+                index += 1;
+                continue;
+            }
+            let start = index;
+            while index < chars.len()
+                && (chars[index].is_ascii_digit() || chars[index] == ',' || chars[index] == '.')
+            {
+                index += 1;
+            }
+            // Trailing punctuation isn't part of the number.
+            let mut end = index;
+            while !chars[end - 1].is_ascii_digit() {
+                end -= 1;
+            }
+            let token = chars[start..end].iter().collect::<String>();
+            localized.push_str(&self.localize_number(&token).unwrap_or(token));
+            localized.extend(&chars[end..index]);
+        }
+        localized
+    }
+
+    /// Rewrite a single US English number, or returns `None` if it isn't one.
+    fn localize_number(&self, number: &str) -> Option<String> {
+        let mut parts = number.split('.');
+        let integer = parts.next()?;
+        let fraction = parts.next();
+        if parts.next().is_some() || fraction.is_some_and(|fraction| fraction.contains(',')) {
+            return None;
+        }
+
+        let grouped = integer.contains(',');
+        let digits = integer.replace(',', "");
+        if grouped && integer != metrics::format_number(digits.parse().ok()?) {
+            return None;
+        }
+        let mut localized = if grouped {
+            self.group_digits(&digits)
+        } else {
+            digits
+        };
+        if let Some(fraction) = fraction {
+            localized.push(self.decimal_separator);
+            localized.push_str(fraction);
+        }
+        Some(localized)
+    }
+
+    /// Insert the thousands separator into a string of digits.
+    fn group_digits(&self, digits: &str) -> String {
+        let mut groups = Vec::new();
+        let mut remaining = digits;
+        let mut size = 3;
+        while remaining.len() > size {
+            let (head, tail) = remaining.split_at(remaining.len() - size);
+            groups.push(tail);
+            remaining = head;
+            if self.number_system == NumberSystem::Indian {
+                size = 2;
+            }
+        }
+        groups.push(remaining);
+        groups.reverse();
+        groups.join(&self.thousands_separator.to_string())
+    }
+}
+
+/// The colors of the html report.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Palette {
+    /// Green for success and red for problems.
+    #[default]
+    Default,
+    /// Colors from the Okabe-Ito palette, distinguishable with red-green color blindness:
+    /// blue for success and vermillion for problems.
+    ColorBlindSafe,
+}
+
+/// The colors of the default palette replaced in the color-blind-safe palette. Lines and
+/// text use the Okabe-Ito colors, areas and highlights use lighter variants.
+const COLOR_BLIND_SAFE_COLORS: &[(&str, &str)] = &[
+    // Success, and POST requests.
+    ("#2c664f", "#0072b2"),
+    ("#378063", "#56b4e9"),
+    ("#00ca5a", "#56b4e9"),
+    ("#e6f2ed", "#e0eef7"),
+    // Warning, and PUT and PATCH requests.
+    ("#b36b00", "#e69f00"),
+    ("#d98c1f", "#f0c35a"),
+    // Danger, and DELETE requests.
+    ("#a32a2a", "#d55e00"),
+    ("#c24545", "#e8894d"),
+    ("#c0392b", "#d55e00"),
+    ("#fbe3e0", "#f9e0cc"),
+    // 3xx responses, and GET requests.
+    ("#2f6fa3", "#cc79a7"),
+    // 4xx responses.
+    ("#d9b310", "#f0e442"),
+];
+
+impl Palette {
+    /// The color of this palette in place of the given color of the default palette,
+    /// which is returned as is if this palette doesn't replace it.
+    pub fn color(self, default: &'static str) -> &'static str {
+        match self {
+            Palette::Default => default,
+            Palette::ColorBlindSafe => COLOR_BLIND_SAFE_COLORS
+                .iter()
+                .find(|(color, _)| *color == default)
+                .map_or(default, |(_, replacement)| replacement),
+        }
+    }
+}
+
+/// The time-series graphs of the html report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReportGraph {
+    RequestsPerSecond,
+    ErrorsPerSecond,
+    AverageResponseTime,
+    ActiveUsers,
+    TasksPerSecond,
+}
+
+impl ReportGraph {
+    /// The id of the graph's element in the html report.
+    pub fn html_id(self) -> &'static str {
+        match self {
+            ReportGraph::RequestsPerSecond => "graph-rps",
+            ReportGraph::ErrorsPerSecond => "graph-eps",
+            ReportGraph::AverageResponseTime => "graph-avg-response-time",
+            ReportGraph::ActiveUsers => "graph-active-users",
+            ReportGraph::TasksPerSecond => "graph-tps",
+        }
+    }
+
+    /// The y axis label of the graph, in the labels and rate unit of `options`.
+    fn y_axis_label(self, options: &GooseReportOptions) -> &str {
+        let strings = &options.strings;
+        match self {
+            ReportGraph::RequestsPerSecond => match options.rate_unit {
+                RateUnit::PerSecond => &strings.requests_axis,
+                RateUnit::PerMinute => &strings.requests_per_minute_axis,
+            },
+            ReportGraph::ErrorsPerSecond => &strings.errors_axis,
+            ReportGraph::AverageResponseTime => &strings.response_time_axis,
+            ReportGraph::ActiveUsers => &strings.active_users_axis,
+            ReportGraph::TasksPerSecond => &strings.tasks_axis,
+        }
+    }
+
+    /// The color role of the graph in the style of `options`.
+    fn color_role(self, options: &GooseReportOptions) -> ColorRole {
+        match self {
+            ReportGraph::RequestsPerSecond => options.style.rps,
+            ReportGraph::ErrorsPerSecond => options.style.eps,
+            ReportGraph::AverageResponseTime => options.style.average_response_time,
+            ReportGraph::ActiveUsers => options.style.users,
+            ReportGraph::TasksPerSecond => options.style.tasks,
+        }
+    }
+}
+
+/// How time-series graphs without data are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EmptyGraphs {
+    /// Rendered as an empty chart.
+    #[default]
+    Render,
+    /// Omitted, without running any script or reserving space.
+    Skip,
+    /// Replaced by a "No data" placeholder, without running any script.
+    Placeholder,
+}
+
+/// The color role of each graph in the html report.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportStyle {
+    /// Color role of the requests per second graph. Defaults to [`ColorRole::Success`].
+    pub rps: ColorRole,
+    /// Color role of the errors per second graph. Defaults to [`ColorRole::Danger`].
+    pub eps: ColorRole,
+    /// Color role of the average response time graph. Defaults to [`ColorRole::Success`].
+    pub average_response_time: ColorRole,
+    /// Color role of the active users graph. Defaults to [`ColorRole::Success`].
+    pub users: ColorRole,
+    /// Color role of the tasks per second graph. Defaults to [`ColorRole::Success`].
+    pub tasks: ColorRole,
+}
+
+impl Default for ReportStyle {
+    fn default() -> Self {
+        ReportStyle {
+            rps: ColorRole::Success,
+            eps: ColorRole::Danger,
+            average_response_time: ColorRole::Success,
+            users: ColorRole::Success,
+            tasks: ColorRole::Success,
+        }
+    }
+}
+
+/// The user-facing labels of the html report, such as section headings and column
+/// headers, for example to translate the report. Labels are inserted into the html as
+/// is, so may contain html entities. Placeholders in braces, such as `{limit}`, are
+/// replaced with the values they name. Defaults to English.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportStrings {
+    pub title: String,
+    pub users: String,
+    pub target_host: String,
+    pub request_metrics: String,
+    pub response_time_metrics: String,
+    pub co_request_metrics: String,
+    pub co_response_time_metrics: String,
+    pub status_code_metrics: String,
+    pub task_metrics: String,
+    pub task_run_time_metrics: String,
+    pub user_metrics: String,
+    pub throttle: String,
+    pub diagnostics: String,
+    pub errors: String,
+    pub scenarios: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub connection_reuse: String,
+    pub successful_responses_only: String,
+    pub downloads: String,
+    pub no_metrics: String,
+    pub no_data: String,
+    pub method: String,
+    pub name: String,
+    pub task: String,
+    pub requests: String,
+    pub times_run: String,
+    pub failures: String,
+    pub traffic_share: String,
+    pub average: String,
+    pub minimum: String,
+    pub maximum: String,
+    pub standard_deviation: String,
+    pub status_codes: String,
+    pub error: String,
+    pub apdex: String,
+    pub sla_budget_used: String,
+    pub slowest: String,
+    pub achieved_users: String,
+    pub users_unit: String,
+    pub shortfall: String,
+    pub metrics_fingerprint: String,
+    pub no_metrics_reason: String,
+    pub show_rates: String,
+    pub show_counts: String,
+    pub filter_endpoints: String,
+    pub assertion: String,
+    pub actual: String,
+    pub result: String,
+    pub pass: String,
+    pub fail: String,
+    pub met: String,
+    pub not_met: String,
+    pub throttle_summary: String,
+    pub throttle_binding: String,
+    pub throttle_not_binding: String,
+    pub throttle_waited: String,
+    pub requests_per_second: String,
+    pub requests_per_minute: String,
+    pub failures_per_second: String,
+    pub failures_per_minute: String,
+    pub max: String,
+    pub aggregated: String,
+    pub subtotal: String,
+    pub all_endpoints: String,
+    pub other: String,
+    pub overall: String,
+    pub starting: String,
+    pub running: String,
+    pub stopping: String,
+    pub warmup: String,
+    pub duration: String,
+    pub warmup_excluded: String,
+    pub commit: String,
+    pub branch: String,
+    pub build: String,
+    pub generated_by: String,
+    pub final_report: String,
+    pub live_report: String,
+    pub truncated_rows: String,
+    pub hidden_endpoint: String,
+    pub hidden_endpoints: String,
+    pub first_error: String,
+    pub metrics_overhead: String,
+    pub metrics_overhead_detail: String,
+    pub graph_error: String,
+    pub requests_axis: String,
+    pub requests_per_minute_axis: String,
+    pub errors_axis: String,
+    pub response_time_axis: String,
+    pub active_users_axis: String,
+    pub tasks_axis: String,
+    pub requests_percentage_axis: String,
+    pub response_time: String,
+    pub request_count: String,
+    pub requests_within: String,
+    pub status_class: String,
+    pub observed_minimum: String,
+    pub observed_maximum: String,
+    pub lower_bound: String,
+    pub upper_bound: String,
+    pub endpoint_totals: String,
+    pub p95_trend: String,
+}
+
+impl Default for ReportStrings {
+    fn default() -> Self {
+        ReportStrings {
+            title: "Goose Attack Report".to_string(),
+            users: "Users".to_string(),
+            target_host: "Target Host".to_string(),
+            request_metrics: "Request Metrics".to_string(),
+            response_time_metrics: "Response Time Metrics".to_string(),
+            co_request_metrics: "Request Metrics With Coordinated Omission Mitigation".to_string(),
+            co_response_time_metrics: "Response Time Metrics With Coordinated Omission Mitigation"
+                .to_string(),
+            status_code_metrics: "Status Code Metrics".to_string(),
+            task_metrics: "Task Metrics".to_string(),
+            task_run_time_metrics: "Task Run Time Metrics".to_string(),
+            user_metrics: "User Metrics".to_string(),
+            throttle: "Throttle".to_string(),
+            diagnostics: "Diagnostics".to_string(),
+            errors: "Errors".to_string(),
+            scenarios: "Scenarios".to_string(),
+            first_seen: "First seen".to_string(),
+            last_seen: "Last seen".to_string(),
+            connection_reuse: "Conn reuse %".to_string(),
+            successful_responses_only: "successful responses only".to_string(),
+            downloads: "Downloads".to_string(),
+            no_metrics: "No metrics were collected".to_string(),
+            no_data: "No data".to_string(),
+            method: "Method".to_string(),
+            name: "Name".to_string(),
+            task: "Task".to_string(),
+            requests: "# Requests".to_string(),
+            times_run: "# Times Run".to_string(),
+            failures: "# Fails".to_string(),
+            traffic_share: "% of Requests".to_string(),
+            average: "Average (ms)".to_string(),
+            minimum: "Min (ms)".to_string(),
+            maximum: "Max (ms)".to_string(),
+            standard_deviation: "Standard deviation (ms)".to_string(),
+            status_codes: "Status Codes".to_string(),
+            error: "Error".to_string(),
+            apdex: "Apdex".to_string(),
+            sla_budget_used: "SLA budget used".to_string(),
+            slowest: "Slowest".to_string(),
+            achieved_users: "Achieved users".to_string(),
+            users_unit: "users".to_string(),
+            shortfall: "shortfall".to_string(),
+            metrics_fingerprint: "Metrics fingerprint".to_string(),
+            no_metrics_reason:
+                "No metrics were collected &mdash; the test may have failed to start or was stopped immediately."
+                    .to_string(),
+            show_rates: "Show rates".to_string(),
+            show_counts: "Show counts".to_string(),
+            filter_endpoints: "Filter endpoints".to_string(),
+            assertion: "Assertion".to_string(),
+            actual: "Actual".to_string(),
+            result: "Result".to_string(),
+            pass: "PASS".to_string(),
+            fail: "FAIL".to_string(),
+            met: "met".to_string(),
+            not_met: "not met".to_string(),
+            throttle_summary: "Requests were throttled to at most {limit} per second, and {achieved} per second were achieved."
+                .to_string(),
+            throttle_binding: "The throttle was the binding constraint: the requests per second reflect the throttle, not the capacity of the server."
+                .to_string(),
+            throttle_not_binding:
+                "The throttle was not the binding constraint: fewer requests were made than it allowed."
+                    .to_string(),
+            throttle_waited: "Requests waited on the throttle for a total of {seconds} seconds."
+                .to_string(),
+            requests_per_second: "RPS".to_string(),
+            requests_per_minute: "RPM".to_string(),
+            failures_per_second: "Failures/s".to_string(),
+            failures_per_minute: "Failures/min".to_string(),
+            max: "Max".to_string(),
+            aggregated: "Aggregated".to_string(),
+            subtotal: "Subtotal".to_string(),
+            all_endpoints: "All endpoints".to_string(),
+            other: "Other".to_string(),
+            overall: "Overall".to_string(),
+            starting: "Starting".to_string(),
+            running: "Running".to_string(),
+            stopping: "Stopping".to_string(),
+            warmup: "Warmup".to_string(),
+            duration: "Duration".to_string(),
+            warmup_excluded: "the first {seconds} seconds are excluded from the request and failure counts, rates and average response times of the request metrics table".to_string(),
+            commit: "Commit".to_string(),
+            branch: "Branch".to_string(),
+            build: "Build".to_string(),
+            generated_by: "Generated by {generator} (powered by {goose})".to_string(),
+            final_report: "Final".to_string(),
+            live_report: "Live &mdash; updated {time}".to_string(),
+            truncated_rows: "Showing top {shown} of {total}.".to_string(),
+            hidden_endpoint: "{hidden} endpoint with only successful responses hidden".to_string(),
+            hidden_endpoints: "{hidden} endpoints with only successful responses hidden".to_string(),
+            first_error: "First error at: {time} (&asymp; {users} users, {rps} RPS)".to_string(),
+            metrics_overhead: "Metrics collection overhead".to_string(),
+            metrics_overhead_detail: "{seconds} seconds ({percentage}% of the load test)".to_string(),
+            graph_error: "Unable to render graph: {error}".to_string(),
+            requests_axis: "Requests #".to_string(),
+            requests_per_minute_axis: "Requests per minute".to_string(),
+            errors_axis: "Errors #".to_string(),
+            response_time_axis: "Response time [ms]".to_string(),
+            active_users_axis: "Active users #".to_string(),
+            tasks_axis: "Tasks #".to_string(),
+            requests_percentage_axis: "Requests [%]".to_string(),
+            response_time: "Response time".to_string(),
+            request_count: "Requests".to_string(),
+            requests_within: "Requests within".to_string(),
+            status_class: "Status class".to_string(),
+            observed_minimum: "Minimum".to_string(),
+            observed_maximum: "Maximum".to_string(),
+            lower_bound: "Lower bound".to_string(),
+            upper_bound: "Upper bound".to_string(),
+            endpoint_totals: "{requests} requests, {failure_rate}% failed".to_string(),
+            p95_trend: "p95 trend [ms]".to_string(),
+        }
+    }
+}
+
+/// How the per-second values of a time-series graph are combined when aggregated into a
+/// longer interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphAggregation {
+    /// Values are counts, such as requests or errors, and are summed.
+    Sum,
+    /// Values are averages or gauges, such as response times or users, and are averaged.
+    Average,
+}
+
+/// The optional columns of the request metrics table. The method and name are always
+/// displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RequestColumn {
+    Requests,
+    Failures,
+    Average,
+    Minimum,
+    Maximum,
+    RequestsPerSecond,
+    FailuresPerSecond,
+    Apdex,
+    TrafficShare,
+}
+
+impl RequestColumn {
+    /// The column header, with the given labels and rate unit.
+    fn label(self, strings: &ReportStrings, rate_unit: RateUnit) -> &str {
+        match self {
+            RequestColumn::Requests => &strings.requests,
+            RequestColumn::Failures => &strings.failures,
+            RequestColumn::Average => &strings.average,
+            RequestColumn::Minimum => &strings.minimum,
+            RequestColumn::Maximum => &strings.maximum,
+            RequestColumn::RequestsPerSecond => rate_unit.requests_label(strings),
+            RequestColumn::FailuresPerSecond => rate_unit.failures_label(strings),
+            RequestColumn::Apdex => &strings.apdex,
+            RequestColumn::TrafficShare => &strings.traffic_share,
+        }
+    }
+}
+
+/// The optional columns of the request metrics table, in the order they're displayed.
+const REQUEST_COLUMNS: [RequestColumn; 9] = [
+    RequestColumn::Requests,
+    RequestColumn::Failures,
+    RequestColumn::Average,
+    RequestColumn::Minimum,
+    RequestColumn::Maximum,
+    RequestColumn::RequestsPerSecond,
+    RequestColumn::FailuresPerSecond,
+    RequestColumn::Apdex,
+    RequestColumn::TrafficShare,
+];
+
+/// The optional columns of the response time metrics table. The method and name are
+/// always displayed, the average only when key percentiles are configured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResponseColumn {
+    Average,
+    Percentile50,
+    Percentile60,
+    Percentile70,
+    Percentile80,
+    Percentile90,
+    Percentile95,
+    Percentile99,
+    Percentile100,
+}
+
+/// The response time table column of each of the [`PERCENTILES`].
+const PERCENTILE_COLUMNS: [ResponseColumn; 8] = [
+    ResponseColumn::Percentile50,
+    ResponseColumn::Percentile60,
+    ResponseColumn::Percentile70,
+    ResponseColumn::Percentile80,
+    ResponseColumn::Percentile90,
+    ResponseColumn::Percentile95,
+    ResponseColumn::Percentile99,
+    ResponseColumn::Percentile100,
+];
+
+/// Flags to explicitly enable or disable each section of the html report, regardless
+/// of what data was collected.
+#[derive(Clone, Debug)]
+pub struct ReportSections {
+    /// Display the request metrics table.
+    pub show_requests: bool,
+    /// Display the response time metrics table.
+    pub show_responses: bool,
+    /// Display the status code metrics table, if status codes were collected.
+    pub show_status_codes: bool,
+    /// Display the task metrics table, if task metrics were collected.
+    pub show_tasks: bool,
+    /// Display the user metrics section.
+    pub show_users: bool,
+    /// Display the errors table, if any errors occurred.
+    pub show_errors: bool,
+    /// Display graphs.
+    pub show_graphs: bool,
+}
+
+impl Default for ReportSections {
+    fn default() -> Self {
+        ReportSections {
+            show_requests: true,
+            show_responses: true,
+            show_status_codes: true,
+            show_tasks: true,
+            show_users: true,
+            show_errors: true,
+            show_graphs: true,
+        }
+    }
+}
+
+/// The percentiles displayed in the response time tables, and their column labels.
+pub(crate) const PERCENTILES: [(f32, &str); 8] = [
+    (0.5, "50%ile"),
+    (0.6, "60%ile"),
+    (0.7, "70%ile"),
+    (0.8, "80%ile"),
+    (0.9, "90%ile"),
+    (0.95, "95%ile"),
+    (0.99, "99%ile"),
+    (1.0, "100%ile"),
+];
+
 /// Defines the metrics reported about requests.
 #[derive(Debug, Clone, Serialize)]
 pub struct RequestMetric {
@@ -36,6 +1374,58 @@ pub struct RequestMetric {
     pub response_time_maximum: usize,
     pub requests_per_second: String,
     pub failures_per_second: String,
+    /// The unformatted requests per second, in the report's rate unit, for exports.
+    pub requests_per_second_value: f32,
+    /// The unformatted failures per second, in the report's rate unit, for exports.
+    pub failures_per_second_value: f32,
+    /// The apdex score, from 0.00 (all users frustrated) to 1.00 (all users satisfied).
+    pub apdex: String,
+    /// Optional per-second request counts, displayed as a sparkline next to the RPS.
+    pub requests_per_second_series: Option<Vec<u32>>,
+    /// The number of responses with each status code, to highlight unhealthy endpoints.
+    pub status_code_counts: HashMap<u16, usize>,
+    /// The endpoint's percentage of all requests, as set by [`set_traffic_shares`].
+    pub traffic_share: f32,
+    /// When the endpoint's first request was made, if known.
+    #[serde(skip)]
+    pub first_seen: Option<DateTime<Local>>,
+    /// When the endpoint's last request was made, if known.
+    #[serde(skip)]
+    pub last_seen: Option<DateTime<Local>>,
+    /// How many of the endpoint's requests reused a connection, if known.
+    pub connection_reuse: Option<ConnectionReuse>,
+}
+
+/// How many requests were sent over a reused connection, rather than a newly
+/// established one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ConnectionReuse {
+    /// Requests sent over a reused, kept alive, connection.
+    pub reused: usize,
+    /// Requests sent over a newly established connection.
+    pub new: usize,
+}
+
+impl ConnectionReuse {
+    /// The percentage of requests that reused a connection, or `None` without requests.
+    pub fn percentage(&self) -> Option<f32> {
+        let total = self.reused + self.new;
+        if total == 0 {
+            None
+        } else {
+            Some(self.reused as f32 / total as f32 * 100.0)
+        }
+    }
+}
+
+impl RequestMetric {
+    /// Whether any response had a 4xx or 5xx status code, whether or not Goose counted
+    /// the request as failed.
+    pub fn has_error_status(&self) -> bool {
+        self.status_code_counts
+            .iter()
+            .any(|(status_code, count)| *status_code >= 400 && *count > 0)
+    }
 }
 
 /// Defines the metrics reported about Coordinated Omission requests.
@@ -53,6 +1443,7 @@ pub struct CORequestMetric {
 pub struct ResponseMetric {
     pub method: String,
     pub name: String,
+    pub response_time_average: String,
     pub percentile_50: String,
     pub percentile_60: String,
     pub percentile_70: String,
@@ -61,6 +1452,44 @@ pub struct ResponseMetric {
     pub percentile_95: String,
     pub percentile_99: String,
     pub percentile_100: String,
+    /// The number and percentage of responses slower than
+    /// [`GooseReportOptions::over_threshold_ms`], if configured.
+    pub over_threshold: String,
+}
+
+impl ResponseMetric {
+    /// Count the responses slower than `threshold` milliseconds, if set.
+    pub fn with_over_threshold(
+        mut self,
+        response_times: &BTreeMap<usize, usize>,
+        threshold: Option<usize>,
+    ) -> ResponseMetric {
+        if let Some(threshold) = threshold {
+            self.over_threshold = format_over_threshold(response_times, threshold);
+        }
+        self
+    }
+}
+
+/// Format the number and percentage of responses in the histogram slower than
+/// `threshold` milliseconds, for example "3 (30%)".
+pub fn format_over_threshold(response_times: &BTreeMap<usize, usize>, threshold: usize) -> String {
+    let total = response_times.values().sum::<usize>();
+    let over = response_times
+        .range(threshold + 1..)
+        .map(|(_, count)| count)
+        .sum::<usize>();
+    let percentage = if total > 0 {
+        over as f64 * 100.0 / total as f64
+    } else {
+        0.0
+    };
+    let percentage = format!("{:.1}", percentage);
+    format!(
+        "{} ({}%)",
+        metrics::format_number(over),
+        percentage.strip_suffix(".0").unwrap_or(&percentage)
+    )
 }
 
 /// Defines the metrics reported about tasks.
@@ -85,16 +1514,99 @@ pub struct StatusCodeMetric {
     pub status_codes: String,
 }
 
+/// The parallel lower and upper bound series of a confidence band, each pairing a
+/// formatted time with a response time in milliseconds.
+pub type ConfidenceBand<'a> = (&'a [(String, u32)], &'a [(String, u32)]);
+
+/// The start and end of a warmup period, shaded on time-series graphs.
+pub type WarmupPeriod = (DateTime<Local>, DateTime<Local>);
+
+/// The phases of a load test shaded on the time-series graphs. The starting and stopping
+/// regions are only shaded if both of their timestamps are known.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GraphPhases {
+    pub starting: Option<DateTime<Local>>,
+    pub started: Option<DateTime<Local>>,
+    pub stopping: Option<DateTime<Local>>,
+    pub stopped: Option<DateTime<Local>>,
+    /// The warmup period excluded from the request metrics table, shaded whether or not the
+    /// starting and stopping regions are.
+    pub warmup: Option<WarmupPeriod>,
+}
+
+impl GraphPhases {
+    /// The regions shaded behind a line, labeled with `strings`: the starting and stopping
+    /// regions if their timestamps are known and `ramp_shading` is enabled, then the
+    /// warmup period.
+    fn shaded_areas<'s>(
+        &self,
+        ramp_shading: bool,
+        strings: &'s ReportStrings,
+    ) -> Vec<(&'s str, DateTime<Local>, DateTime<Local>)> {
+        let mut areas = Vec::new();
+        if ramp_shading {
+            for (name, from, to) in [
+                (strings.starting.as_str(), self.starting, self.started),
+                (strings.stopping.as_str(), self.stopping, self.stopped),
+            ] {
+                if let (Some(from), Some(to)) = (from, to) {
+                    areas.push((name, from, to));
+                }
+            }
+        }
+        if let Some((from, to)) = self.warmup {
+            areas.push((strings.warmup.as_str(), from, to));
+        }
+        areas
+    }
+
+    /// The ECharts `markArea` of a line, shading the regions of
+    /// [`GraphPhases::shaded_areas`].
+    fn mark_area(&self, ramp_shading: bool, strings: &ReportStrings) -> serde_json::Value {
+        let datetime_format = "%Y-%m-%d %H:%M:%S";
+        let areas = self
+            .shaded_areas(ramp_shading, strings)
+            .into_iter()
+            .map(|(name, from, to)| {
+                json!([
+                    { "name": name, "xAxis": from.format(datetime_format).to_string() },
+                    { "xAxis": to.format(datetime_format).to_string() },
+                ])
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "itemStyle": { "color": "rgba(6, 6, 6, 0.10)" },
+            "data": areas,
+        })
+    }
+}
+
+/// Graphs with fewer points than this mark each point with a symbol, so individual
+/// samples of short load tests remain visible.
+pub const SPARSE_GRAPH_POINTS: usize = 20;
+
 /// Defines the HTML graph data.
 #[derive(Debug)]
 struct Graph<'a, T: Serialize> {
     pub html_id: &'a str,
     pub y_axis_label: &'a str,
     pub data: &'a [(String, T)],
-    pub starting: Option<DateTime<Local>>,
-    pub started: Option<DateTime<Local>>,
-    pub stopping: Option<DateTime<Local>>,
-    pub stopped: Option<DateTime<Local>>,
+    pub phases: GraphPhases,
+    pub threshold: Option<u32>,
+    pub sampling_threshold: usize,
+    pub color_role: ColorRole,
+    pub ramp_shading: bool,
+    pub confidence_band: Option<ConfidenceBand<'a>>,
+    /// The observed minimum and maximum series, shaded behind the line.
+    pub envelope: Option<ConfidenceBand<'a>>,
+    /// The ECharts symbol marking each point, `'none'` unless the data is sparse.
+    pub symbol: &'a str,
+    /// The maximum decimals of the y axis labels. Only in the markup, as the exported
+    /// option object is JSON, which can't hold the formatter function.
+    pub axis_decimals: Option<usize>,
+    pub palette: Palette,
+    /// The labels of the shaded regions, series and error placeholder.
+    pub strings: &'a ReportStrings,
 }
 
 impl<'a, T: Serialize> Graph<'a, T> {
@@ -103,164 +1615,619 @@ impl<'a, T: Serialize> Graph<'a, T> {
         html_id: &'a str,
         y_axis_label: &'a str,
         data: &'a [(String, T)],
-        starting: Option<DateTime<Local>>,
-        started: Option<DateTime<Local>>,
-        stopping: Option<DateTime<Local>>,
-        stopped: Option<DateTime<Local>>,
+        phases: GraphPhases,
+        strings: &'a ReportStrings,
     ) -> Graph<'a, T> {
         Graph {
             html_id,
             y_axis_label,
             data,
-            starting,
-            started,
-            stopping,
-            stopped,
+            phases,
+            strings,
+            threshold: None,
+            sampling_threshold: 0,
+            color_role: ColorRole::default(),
+            ramp_shading: true,
+            confidence_band: None,
+            envelope: None,
+            axis_decimals: None,
+            palette: Palette::default(),
+            symbol: if data.len() < SPARSE_GRAPH_POINTS {
+                "circle"
+            } else {
+                "none"
+            },
         }
     }
 
-    /// Helper function to build HTML charts powered by the
-    /// [ECharts](https://echarts.apache.org) library.
-    fn generate_markup(self) -> String {
-        let datetime_format = "%Y-%m-%d %H:%M:%S";
+    /// Shades the band between parallel lower and upper bound series, such as the mean
+    /// plus and minus one standard deviation.
+    fn with_confidence_band(mut self, confidence_band: Option<ConfidenceBand<'a>>) -> Graph<'a, T> {
+        self.confidence_band = confidence_band;
+        self
+    }
 
-        let starting_area = if self.starting.is_some() && self.started.is_some() {
-            format!(
-                r#"[
-                    {{
-                        name: 'Starting',
-                        xAxis: '{starting}'
-                    }},
-                    {{
-                        xAxis: '{started}'
-                    }}
-                ],"#,
-                starting = self.starting.unwrap().format(datetime_format),
-                started = self.started.unwrap().format(datetime_format),
-            )
-        } else {
-            "".to_string()
-        };
+    /// Shades the band between the observed minimum and maximum series, such as the
+    /// fastest and slowest response of each second, behind the line.
+    fn with_envelope(mut self, envelope: Option<ConfidenceBand<'a>>) -> Graph<'a, T> {
+        self.envelope = envelope;
+        self
+    }
 
-        let stopping_area = if self.stopping.is_some() && self.stopped.is_some() {
-            format!(
-                r#"[
-                    {{
-                        name: 'Stopping',
-                        xAxis: '{stopping}'
-                    }},
-                    {{
-                        xAxis: '{stopped}'
-                    }}
-                ],"#,
-                stopping = self.stopping.unwrap().format(datetime_format),
-                stopped = self.stopped.unwrap().format(datetime_format),
-            )
-        } else {
-            "".to_string()
-        };
+    /// Shades the starting and stopping regions, if their timestamps are known. Enabled
+    /// by default.
+    fn with_ramp_shading(mut self, ramp_shading: bool) -> Graph<'a, T> {
+        self.ramp_shading = ramp_shading;
+        self
+    }
 
-        format!(
-            r#"<div class="graph">
-                <div id="{html_id}" style="width: 1000px; height:500px; background: white;"></div>
+    /// Draws the line and area in the colors of the given role.
+    fn with_color_role(mut self, color_role: ColorRole) -> Graph<'a, T> {
+        self.color_role = color_role;
+        self
+    }
 
-                <script type="text/javascript">
-                    var chartDom = document.getElementById('{html_id}');
-                    var myChart = echarts.init(chartDom);
+    /// Draws the chart in the colors of the given palette.
+    fn with_palette(mut self, palette: Palette) -> Graph<'a, T> {
+        self.palette = palette;
+        self
+    }
 
-                    myChart.setOption({{
-                        color: ['#2c664f'],
-                        tooltip: {{ trigger: 'axis' }},
-                        toolbox: {{
-                            feature: {{
-                                dataZoom: {{ yAxisIndex: 'none' }},
-                                restore: {{}},
-                                saveAsImage: {{}}
-                            }}
-                        }},
-                        dataZoom: [
-                            {{
-                                type: 'inside',
-                                start: 0,
-                                end: 100,
-                                fillerColor: 'rgba(34, 80, 61, 0.25)',
-                                selectedDataBackground: {{
-                                    lineStyle: {{ color: '#2c664f' }},
-                                    areaStyle: {{ color: '#378063' }}
-                                }}
-                            }},
-                            {{
-                                start: 0,
-                                end: 100,
-                                fillerColor: 'rgba(34, 80, 61, 0.25)',
-                                selectedDataBackground: {{
-                                    lineStyle: {{ color: '#2c664f' }},
-                                    areaStyle: {{ color: '#378063' }}
-                                }}
-                            }},
-                        ],
-                        xAxis: {{ type: 'time' }},
-                        yAxis: {{
-                            name: '{y_axis_label}',
-                            nameLocation: 'center',
-                            nameRotate: 90,
-                            nameGap: 45,
-                            type: 'value'
-                        }},
-                        series: [
-                            {{
-                                type: 'line',
-                                symbol: 'none',
-                                sampling: 'lttb',
-                                lineStyle: {{ color: '#2c664f' }},
-                                areaStyle: {{ color: '#378063' }},
-                                markArea: {{
-                                    itemStyle: {{ color: 'rgba(6, 6, 6, 0.10)' }},
-                                    data: [
-                                        {starting_area}
-                                        {stopping_area}
-                                    ]
-                                }},
-                                data: {values},
-                            }}
-                        ]
-                    }});
+    /// Only downsample the line with LTTB sampling if it has at least this many points,
+    /// so small data sets are drawn exactly.
+    fn with_sampling_threshold(mut self, sampling_threshold: usize) -> Graph<'a, T> {
+        self.sampling_threshold = sampling_threshold;
+        self
+    }
+
+    /// Rounds the y axis labels to at most this many decimals.
+    fn with_axis_decimals(mut self, axis_decimals: Option<usize>) -> Graph<'a, T> {
+        self.axis_decimals = axis_decimals;
+        self
+    }
+
+    /// Draws a horizontal line labeled "SLO" at the given y value.
+    fn with_threshold(mut self, threshold: Option<u32>) -> Graph<'a, T> {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The [ECharts](https://echarts.apache.org) option object of the chart, as inlined in
+    /// the markup, for apps that render the chart themselves.
+    fn option(&self) -> serde_json::Result<serde_json::Value> {
+        let data = serde_json::to_value(self.data)?;
+        let (line_color, area_color) = self.color_role.colors(self.palette);
+
+        let mut line = json!({
+            "type": "line",
+            "symbol": self.symbol,
+            "lineStyle": { "color": line_color },
+            "areaStyle": { "color": area_color },
+            "markArea": self.phases.mark_area(self.ramp_shading, self.strings),
+            "data": data,
+        });
+        if self.data.len() >= self.sampling_threshold {
+            line["sampling"] = json!("lttb");
+        }
+        if let Some(threshold) = self.threshold {
+            line["markLine"] = json!({
+                "symbol": "none",
+                "label": { "formatter": "SLO" },
+                "lineStyle": { "color": self.palette.color("#c0392b"), "type": "dashed" },
+                "data": [{ "yAxis": threshold }],
+            });
+        }
+        let mut series = Vec::new();
+        if let Some((minimum, maximum)) = self.envelope {
+            let width = minimum
+                .iter()
+                .zip(maximum.iter())
+                .map(|((time, minimum), (_, maximum))| (time, maximum.saturating_sub(*minimum)))
+                .collect::<Vec<_>>();
+            series.push(json!({
+                "name": self.strings.observed_minimum,
+                "type": "line",
+                "symbol": "none",
+                "stack": "envelope",
+                "z": 1,
+                "lineStyle": { "opacity": 0 },
+                "data": minimum,
+            }));
+            series.push(json!({
+                "name": self.strings.observed_maximum,
+                "type": "line",
+                "symbol": "none",
+                "stack": "envelope",
+                "z": 1,
+                "lineStyle": { "opacity": 0 },
+                "areaStyle": { "color": area_color, "opacity": 0.3 },
+                "data": width,
+            }));
+        }
+        series.push(line);
+        if let Some((lower, upper)) = self.confidence_band {
+            let width = lower
+                .iter()
+                .zip(upper.iter())
+                .map(|((time, lower), (_, upper))| (time, upper.saturating_sub(*lower)))
+                .collect::<Vec<_>>();
+            series.push(json!({
+                "name": self.strings.lower_bound,
+                "type": "line",
+                "symbol": "none",
+                "stack": "confidence-band",
+                "lineStyle": { "opacity": 0 },
+                "data": lower,
+            }));
+            series.push(json!({
+                "name": self.strings.upper_bound,
+                "type": "line",
+                "symbol": "none",
+                "stack": "confidence-band",
+                "lineStyle": { "opacity": 0 },
+                "areaStyle": { "color": line_color, "opacity": 0.25 },
+                "data": width,
+            }));
+        }
+
+        let data_zoom = |inside: bool| {
+            let mut data_zoom = json!({
+                "start": 0,
+                "end": 100,
+                "fillerColor": "rgba(34, 80, 61, 0.25)",
+                "selectedDataBackground": {
+                    "lineStyle": { "color": line_color },
+                    "areaStyle": { "color": area_color },
+                },
+            });
+            if inside {
+                data_zoom["type"] = json!("inside");
+            }
+            data_zoom
+        };
+
+        Ok(json!({
+            "color": [line_color],
+            "tooltip": { "trigger": "axis" },
+            "toolbox": {
+                "feature": {
+                    "dataZoom": { "yAxisIndex": "none" },
+                    "restore": {},
+                    "saveAsImage": {},
+                },
+            },
+            "dataZoom": [data_zoom(true), data_zoom(false)],
+            "xAxis": { "type": "time" },
+            "yAxis": {
+                "name": self.y_axis_label,
+                "nameLocation": "center",
+                "nameRotate": 90,
+                "nameGap": 45,
+                "type": "value",
+            },
+            "series": series,
+        }))
+    }
+
+    /// Rasterize the chart as a PNG, mirroring the markup without axis labels. Returns
+    /// `None` if the data isn't numeric or is empty.
+    #[cfg(feature = "chart-png")]
+    fn png(&self) -> Option<Vec<u8>> {
+        use tiny_skia::{
+            Color, FillRule, Paint, PathBuilder, Pixmap, Rect, Stroke, StrokeDash, Transform,
+        };
+
+        const WIDTH: f32 = 1000.0;
+        const HEIGHT: f32 = 500.0;
+        const LEFT: f32 = 60.0;
+        const RIGHT: f32 = 20.0;
+        const TOP: f32 = 20.0;
+        const BOTTOM: f32 = 40.0;
+
+        let values = self
+            .data
+            .iter()
+            .map(|(_, value)| serde_json::to_value(value).ok()?.as_f64())
+            .collect::<Option<Vec<_>>>()?;
+        if values.is_empty() {
+            return None;
+        }
+        let max = values
+            .iter()
+            .copied()
+            .chain(self.threshold.map(f64::from))
+            .fold(1.0, f64::max);
+
+        // Palette colors are hex strings such as "#2c664f".
+        let color = |hex: &str, alpha: u8| {
+            let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+            Some(Color::from_rgba8(
+                channel(1)?,
+                channel(3)?,
+                channel(5)?,
+                alpha,
+            ))
+        };
+        let paint = |color: Color| {
+            let mut paint = Paint::default();
+            paint.set_color(color);
+            paint.anti_alias = true;
+            paint
+        };
+        let plot_width = WIDTH - LEFT - RIGHT;
+        let plot_height = HEIGHT - TOP - BOTTOM;
+        let x = |index: usize| {
+            LEFT + if values.len() > 1 {
+                index as f32 * plot_width / (values.len() - 1) as f32
+            } else {
+                plot_width / 2.0
+            }
+        };
+        let y = |value: f64| TOP + plot_height - (value / max) as f32 * plot_height;
+
+        let mut pixmap = Pixmap::new(WIDTH as u32, HEIGHT as u32)?;
+        pixmap.fill(Color::WHITE);
+
+        // Shade the starting, stopping and warmup regions, by the points inside them.
+        let datetime_format = "%Y-%m-%d %H:%M:%S";
+        for (_, from, to) in self.phases.shaded_areas(self.ramp_shading, self.strings) {
+            let from = from.format(datetime_format).to_string();
+            let to = to.format(datetime_format).to_string();
+            let inside = self
+                .data
+                .iter()
+                .enumerate()
+                .filter(|(_, (time, _))| *time >= from && *time <= to)
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>();
+            if let (Some(first), Some(last)) = (inside.first(), inside.last()) {
+                if let Some(rect) = Rect::from_ltrb(
+                    x(*first),
+                    TOP,
+                    x(*last).max(x(*first) + 1.0),
+                    TOP + plot_height,
+                ) {
+                    pixmap.fill_rect(
+                        rect,
+                        &paint(Color::from_rgba8(6, 6, 6, 26)),
+                        Transform::identity(),
+                        None,
+                    );
+                }
+            }
+        }
+
+        // Horizontal grid lines.
+        let grid = paint(Color::from_rgba8(224, 230, 241, 255));
+        for step in 0..=4 {
+            let mut path = PathBuilder::new();
+            let grid_y = TOP + plot_height * step as f32 / 4.0;
+            path.move_to(LEFT, grid_y);
+            path.line_to(LEFT + plot_width, grid_y);
+            if let Some(path) = path.finish() {
+                pixmap.stroke_path(
+                    &path,
+                    &grid,
+                    &Stroke::default(),
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+
+        let (line_color, area_color) = self.color_role.colors(self.palette);
+        let mut line = PathBuilder::new();
+        for (index, value) in values.iter().enumerate() {
+            if index == 0 {
+                line.move_to(x(index), y(*value));
+            } else {
+                line.line_to(x(index), y(*value));
+            }
+        }
+        let mut area = line.clone();
+        area.line_to(x(values.len() - 1), y(0.0));
+        area.line_to(x(0), y(0.0));
+        area.close();
+        if let Some(area) = area.finish() {
+            pixmap.fill_path(
+                &area,
+                &paint(color(area_color, 128)?),
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+        if values.len() == 1 {
+            // A single point has no line to stroke.
+            line.push_circle(x(0), y(values[0]), 3.0);
+        }
+        let line = line.finish()?;
+        pixmap.stroke_path(
+            &line,
+            &paint(color(line_color, 255)?),
+            &Stroke {
+                width: 2.0,
+                ..Default::default()
+            },
+            Transform::identity(),
+            None,
+        );
+
+        if let Some(threshold) = self.threshold {
+            let mut path = PathBuilder::new();
+            path.move_to(LEFT, y(threshold as f64));
+            path.line_to(LEFT + plot_width, y(threshold as f64));
+            if let Some(path) = path.finish() {
+                pixmap.stroke_path(
+                    &path,
+                    &paint(color(self.palette.color("#c0392b"), 255)?),
+                    &Stroke {
+                        width: 1.5,
+                        dash: StrokeDash::new(vec![6.0, 4.0], 0.0),
+                        ..Default::default()
+                    },
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+
+        pixmap.encode_png().ok()
+    }
+
+    /// Helper function to build HTML charts powered by the
+    /// [ECharts](https://echarts.apache.org) library. The chart is drawn from the same
+    /// option object as [`Graph::option`], inlined as JSON.
+    ///
+    /// If the data can't be serialized, an error placeholder is rendered in place of the
+    /// chart.
+    fn generate_markup(self) -> String {
+        let option = match self
+            .option()
+            .and_then(|option| serde_json::to_string(&option))
+        {
+            Ok(option) => option,
+            Err(e) => {
+                warn!("failed to serialize data of graph {}: {}", self.html_id, e);
+                return format!(
+                    r#"<div class="graph graph-error" id="{html_id}">{message}</div>"#,
+                    html_id = self.html_id,
+                    message = self
+                        .strings
+                        .graph_error
+                        .replace("{error}", &escape_html(&e.to_string())),
+                );
+            }
+        };
+
+        // JSON can't hold the formatter function, so it's merged into the option after it
+        // is set. Rounding with toFixed, then parsing, drops trailing zeros such as "12.50".
+        let axis_label = match self.axis_decimals {
+            Some(decimals) => format!(
+                "\n                    myChart.setOption({{ yAxis: {{ axisLabel: {{ formatter: function (value) {{ return parseFloat(value.toFixed({})); }} }} }} }});",
+                decimals
+            ),
+            None => "".to_string(),
+        };
+
+        format!(
+            r#"<div class="graph">
+                <div id="{html_id}" style="width: 1000px; height:500px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('{html_id}');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({option});{axis_label}
                 </script>
             </div>"#,
             html_id = self.html_id,
-            values = json!(self.data),
-            starting_area = starting_area,
-            stopping_area = stopping_area,
-            y_axis_label = self.y_axis_label,
+            option = option,
+            axis_label = axis_label,
         )
     }
 }
 
+/// Strip redundant whitespace from an html report.
+///
+/// Whitespace runs in the markup are collapsed to a single space, and the indentation
+/// between tags is removed. The content of `<pre>` and `<textarea>` elements is left
+/// untouched, and the indentation of `<script>` elements is removed line by line
+/// without touching JavaScript string literals or comments, so no statement relies on
+/// automatic semicolon insertion across a removed newline.
+pub fn minify_html(html: &str) -> String {
+    let mut minified = String::with_capacity(html.len());
+    let mut rest = html;
+    loop {
+        // Find the next element whose content isn't plain markup.
+        let next = ["<pre", "<textarea", "<script", "<style"]
+            .iter()
+            .filter_map(|tag| find_opening_tag(rest, tag).map(|index| (index, *tag)))
+            .min();
+        let (index, tag) = match next {
+            Some(next) => next,
+            None => {
+                minify_markup(rest, &mut minified);
+                return minified;
+            }
+        };
+        minify_markup(&rest[..index], &mut minified);
+
+        let element = &rest[index..];
+        let open_end = element.find('>').map_or(element.len(), |end| end + 1);
+        let close = format!("</{}>", &tag[1..]);
+        let close_start = element[open_end..]
+            .find(&close)
+            .map_or(element.len(), |start| open_end + start);
+        let close_end = (close_start + close.len()).min(element.len());
+
+        minify_markup(&element[..open_end], &mut minified);
+        let content = &element[open_end..close_start];
+        match tag {
+            "<script" => minify_script(content, &mut minified),
+            "<style" => minify_markup(content, &mut minified),
+            _ => minified.push_str(content),
+        }
+        minified.push_str(&element[close_start..close_end]);
+        rest = &element[close_end..];
+    }
+}
+
+/// Find the start of the first `tag`, such as `<pre`, in `html`, ignoring longer tag
+/// names that share the prefix.
+fn find_opening_tag(html: &str, tag: &str) -> Option<usize> {
+    html.match_indices(tag)
+        .find(|(index, _)| {
+            html[index + tag.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c == '>' || c.is_whitespace())
+        })
+        .map(|(index, _)| index)
+}
+
+/// Collapse whitespace runs of markup to a single space, removing runs spanning lines
+/// between two tags.
+fn minify_markup(markup: &str, minified: &mut String) {
+    let mut chars = markup.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !c.is_whitespace() {
+            minified.push(c);
+            continue;
+        }
+        let mut newline = c == '\n';
+        while let Some(next) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            newline |= *next == '\n';
+            chars.next();
+        }
+        let after_tag = minified.is_empty() || minified.ends_with('>');
+        let before_tag = chars.peek().is_none_or(|next| *next == '<');
+        if !(newline && after_tag && before_tag) {
+            minified.push(' ');
+        }
+    }
+}
+
+/// Remove the indentation and blank lines of a script, copying string literals and
+/// comments as is.
+fn minify_script(script: &str, minified: &mut String) {
+    let mut chars = script.chars().peekable();
+    let mut string: Option<char> = None;
+    let mut escaped = false;
+    let mut line_comment = false;
+    let mut block_comment = false;
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        if line_start {
+            if c.is_whitespace() {
+                continue;
+            }
+            line_start = false;
+        }
+        if let Some(quote) = string {
+            minified.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                string = None;
+            }
+            continue;
+        }
+        if block_comment {
+            minified.push(c);
+            if c == '*' && chars.peek() == Some(&'/') {
+                minified.push(chars.next().unwrap());
+                block_comment = false;
+            }
+            continue;
+        }
+        match c {
+            '\n' => {
+                line_comment = false;
+                if !minified.ends_with('\n') {
+                    minified.push('\n');
+                }
+                line_start = true;
+            }
+            _ if line_comment => minified.push(c),
+            '\'' | '"' | '`' => {
+                minified.push(c);
+                string = Some(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                minified.push(c);
+                line_comment = true;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                minified.push(c);
+                minified.push(chars.next().unwrap());
+                block_comment = true;
+            }
+            _ => minified.push(c),
+        }
+    }
+}
+
+/// Helper to calculate the response time percentiles shown in the report, as numbers.
+///
+/// Returns `(percentile, milliseconds)` pairs for each reported percentile, in ascending
+/// order, for exporters and assertions that consume numbers rather than the formatted
+/// strings of a [`ResponseMetric`]. A `Vec` is used instead of a map because `f64` keys
+/// aren't `Ord`.
+pub fn get_response_metric_raw(
+    response_times: &BTreeMap<usize, usize>,
+    total_request_count: usize,
+    response_time_minimum: usize,
+    response_time_maximum: usize,
+) -> Vec<(f64, f64)> {
+    PERCENTILES
+        .iter()
+        .map(|(percent, _)| {
+            (
+                *percent as f64,
+                metrics::response_time_percentile(
+                    response_times,
+                    total_request_count,
+                    response_time_minimum,
+                    response_time_maximum,
+                    *percent,
+                ) as f64,
+            )
+        })
+        .collect()
+}
+
 /// Helper to generate a single response metric.
 pub fn get_response_metric(
     method: &str,
     name: &str,
     response_times: &BTreeMap<usize, usize>,
     total_request_count: usize,
+    response_time_average: f32,
     response_time_minimum: usize,
     response_time_maximum: usize,
 ) -> ResponseMetric {
-    // Calculate percentiles in a loop.
-    let mut percentiles = Vec::new();
-    for percent in &[0.5, 0.6, 0.7, 0.8, 0.9, 0.95, 0.99, 1.0] {
-        percentiles.push(metrics::calculate_response_time_percentile(
-            response_times,
-            total_request_count,
-            response_time_minimum,
-            response_time_maximum,
-            *percent,
-        ));
-    }
+    // Format the raw percentiles for display.
+    let mut percentiles = get_response_metric_raw(
+        response_times,
+        total_request_count,
+        response_time_minimum,
+        response_time_maximum,
+    )
+    .into_iter()
+    .map(|(_, milliseconds)| metrics::format_number(milliseconds as usize))
+    .collect::<Vec<_>>();
 
     // Now take the Strings out of the Vector and build a ResponseMetric object.
     ResponseMetric {
         method: method.to_string(),
         name: name.to_string(),
+        response_time_average: format!("{:.2}", response_time_average),
         percentile_50: mem::take(&mut percentiles[0]),
         percentile_60: mem::take(&mut percentiles[1]),
         percentile_70: mem::take(&mut percentiles[2]),
@@ -269,1307 +2236,7936 @@ pub fn get_response_metric(
         percentile_95: mem::take(&mut percentiles[5]),
         percentile_99: mem::take(&mut percentiles[6]),
         percentile_100: mem::take(&mut percentiles[7]),
+        over_threshold: "".to_string(),
     }
 }
 
-/// Build an individual row of raw request metrics in the html report.
-pub fn raw_request_metrics_row(metric: RequestMetric) -> String {
-    format!(
-        r#"<tr>
-        <td>{method}</td>
-        <td>{name}</td>
-        <td>{number_of_requests}</td>
-        <td>{number_of_failures}</td>
-        <td>{response_time_average}</td>
-        <td>{response_time_minimum}</td>
-        <td>{response_time_maximum}</td>
-        <td>{requests_per_second}</td>
-        <td>{failures_per_second}</td>
-    </tr>"#,
-        method = metric.method,
-        name = metric.name,
-        number_of_requests = metric.number_of_requests,
-        number_of_failures = metric.number_of_failures,
-        response_time_average = metric.response_time_average,
-        response_time_minimum = metric.response_time_minimum,
-        response_time_maximum = metric.response_time_maximum,
-        requests_per_second = metric.requests_per_second,
-        failures_per_second = metric.failures_per_second,
-    )
-}
+/// Export per-endpoint request metrics in the InfluxDB line protocol.
+///
+/// Emits one `goose_request` measurement per endpoint, tagged with `method` and `name`,
+/// with the request and failure counts, the average response time, and each response
+/// time percentile as fields. All lines use the supplied `timestamp`, in nanoseconds
+/// since the Unix epoch.
+pub fn build_influx_line_protocol(metrics: &metrics::GooseMetrics, timestamp: i64) -> String {
+    let mut lines = Vec::new();
+    for (request_key, request) in metrics.requests.iter().sorted() {
+        let method = format!("{}", request.method);
+        // The request_key is "{method} {name}", so by stripping the "{method} "
+        // prefix we get the name.
+        let name = request_key
+            .strip_prefix(&format!("{} ", request.method))
+            .unwrap_or(request_key);
 
-/// Build an individual row of response metrics in the html report.
-pub fn response_metrics_row(metric: ResponseMetric) -> String {
-    format!(
-        r#"<tr>
-            <td>{method}</td>
-            <td>{name}</td>
-            <td>{percentile_50}</td>
-            <td>{percentile_60}</td>
-            <td>{percentile_70}</td>
-            <td>{percentile_80}</td>
-            <td>{percentile_90}</td>
-            <td>{percentile_95}</td>
-            <td>{percentile_99}</td>
-            <td>{percentile_100}</td>
-        </tr>"#,
-        method = metric.method,
-        name = metric.name,
-        percentile_50 = metric.percentile_50,
-        percentile_60 = metric.percentile_60,
-        percentile_70 = metric.percentile_70,
-        percentile_80 = metric.percentile_80,
-        percentile_90 = metric.percentile_90,
-        percentile_95 = metric.percentile_95,
-        percentile_99 = metric.percentile_99,
-        percentile_100 = metric.percentile_100,
-    )
-}
+        let mut fields = vec![
+            format!("requests={}i", request.success_count + request.fail_count),
+            format!("failures={}i", request.fail_count),
+        ];
+        if request.raw_data.counter > 0 {
+            fields.push(format!(
+                "response_time_average={}",
+                request.raw_data.total_time as f64 / request.raw_data.counter as f64
+            ));
+        }
+        for (percentile, milliseconds) in get_response_metric_raw(
+            &request.raw_data.times,
+            request.raw_data.counter,
+            request.raw_data.minimum_time,
+            request.raw_data.maximum_time,
+        ) {
+            fields.push(format!(
+                "p{}={}i",
+                (percentile * 100.0).round(),
+                milliseconds
+            ));
+        }
 
-/// If Coordinated Omission Mitigation is triggered, add a relevant request table to the
-/// html report.
-pub fn coordinated_omission_request_metrics_template(co_requests_rows: &str) -> String {
-    format!(
-        r#"<div class="CO requests">
-        <h2>Request Metrics With Coordinated Omission Mitigation</h2>
-        <table>
-            <thead>
-                <tr>
-                    <th>Method</th>
-                    <th>Name</th>
-                    <th>Average (ms)</th>
-                    <th>Standard deviation (ms)</th>
-                    <th>Max (ms)</th>
-                </tr>
-            </thead>
-            <tbody>
-                {co_requests_rows}
-            </tbody>
-        </table>
-    </div>"#,
-        co_requests_rows = co_requests_rows,
-    )
+        lines.push(format!(
+            "goose_request,method={method},name={name} {fields} {timestamp}",
+            method = escape_influx_tag(&method),
+            name = escape_influx_tag(name),
+            fields = fields.join(","),
+            timestamp = timestamp,
+        ));
+    }
+    lines.join("\n")
 }
 
-/// Build an individual row of Coordinated Omission Mitigation request metrics in
-/// the html report.
-pub fn coordinated_omission_request_metrics_row(metric: CORequestMetric) -> String {
-    format!(
-        r#"<tr>
-            <td>{method}</td>
-            <td>{name}</td>
-            <td>{average})</td>
-            <td>{standard_deviation}</td>
-            <td>{maximum}</td>
-        </tr>"#,
-        method = metric.method,
-        name = metric.name,
-        average = metric.response_time_average,
-        standard_deviation = metric.response_time_standard_deviation,
-        maximum = metric.response_time_maximum,
-    )
+/// The most endpoints listed in the slowest endpoints section of a Slack message.
+pub const SLACK_MAX_ENDPOINTS: usize = 5;
+
+/// Build a [Slack Block Kit](https://api.slack.com/block-kit) payload summarizing the
+/// metrics, for posting results to Slack, which renders neither html nor Markdown tables.
+///
+/// The message has a header, a summary of the total requests, failure rate and 95th
+/// percentile response time, and the slowest endpoints by 95th percentile. At most
+/// [`SLACK_MAX_ENDPOINTS`] endpoints are listed, to stay within Slack's block limits.
+pub fn build_slack_blocks(metrics: &metrics::GooseMetrics) -> String {
+    let mut requests = 0;
+    let mut failures = 0;
+    let mut minimum = 0;
+    let mut maximum = 0;
+    let mut histograms = Vec::new();
+    let mut endpoints = Vec::new();
+    for (request_key, request) in metrics.requests.iter() {
+        requests += request.success_count + request.fail_count;
+        failures += request.fail_count;
+        minimum = metrics::update_min_time(minimum, request.raw_data.minimum_time);
+        maximum = maximum.max(request.raw_data.maximum_time);
+        histograms.push(request.raw_data.times.clone());
+        if request.raw_data.counter > 0 {
+            endpoints.push((
+                request_key,
+                metrics::response_time_percentile(
+                    &request.raw_data.times,
+                    request.raw_data.counter,
+                    request.raw_data.minimum_time,
+                    request.raw_data.maximum_time,
+                    0.95,
+                ),
+                request.success_count + request.fail_count,
+            ));
+        }
+    }
+    let times = metrics::merge_response_histograms(&histograms);
+    let p95 =
+        metrics::response_time_percentile(&times, times.values().sum(), minimum, maximum, 0.95);
+    let failure_rate = if requests > 0 {
+        failures as f32 / requests as f32 * 100.0
+    } else {
+        0.0
+    };
+
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": "Goose Attack Report" },
+        }),
+        json!({
+            "type": "section",
+            "fields": [
+                {
+                    "type": "mrkdwn",
+                    "text": format!("*Requests*\n{}", metrics::format_number(requests)),
+                },
+                {
+                    "type": "mrkdwn",
+                    "text": format!(
+                        "*Failures*\n{} ({:.2}%)",
+                        metrics::format_number(failures),
+                        failure_rate
+                    ),
+                },
+                {
+                    "type": "mrkdwn",
+                    "text": format!("*95%ile*\n{} ms", metrics::format_number(p95)),
+                },
+                {
+                    "type": "mrkdwn",
+                    "text": format!("*Users*\n{}", metrics::format_number(metrics.users)),
+                },
+            ],
+        }),
+    ];
+
+    if !endpoints.is_empty() {
+        // Slowest first, then by name for a stable order.
+        endpoints.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let mut lines = endpoints
+            .iter()
+            .take(SLACK_MAX_ENDPOINTS)
+            .map(|(request_key, p95, requests)| {
+                format!(
+                    "• `{}`: {} ms ({} requests)",
+                    escape_slack(request_key),
+                    metrics::format_number(*p95),
+                    metrics::format_number(*requests)
+                )
+            })
+            .collect::<Vec<_>>();
+        if endpoints.len() > SLACK_MAX_ENDPOINTS {
+            lines.push(format!(
+                "_{} more endpoints not shown_",
+                endpoints.len() - SLACK_MAX_ENDPOINTS
+            ));
+        }
+        blocks.push(json!({ "type": "divider" }));
+        blocks.push(json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!("*Slowest endpoints (95%ile)*\n{}", lines.join("\n")),
+            },
+        }));
+    }
+
+    json!({ "blocks": blocks }).to_string()
 }
 
-/// If Coordinated Omission Mitigation is triggered, add a relevant response table to the
-/// html report.
-pub fn coordinated_omission_response_metrics_template(co_responses_rows: &str) -> String {
-    format!(
-        r#"<div class="responses">
-        <h2>Response Time Metrics With Coordinated Omission Mitigation</h2>
-        <table>
-            <thead>
-                <tr>
-                    <th>Method</th>
-                    <th>Name</th>
-                    <th>50%ile (ms)</th>
-                    <th>60%ile (ms)</th>
-                    <th>70%ile (ms)</th>
-                    <th>80%ile (ms)</th>
-                    <th>90%ile (ms)</th>
-                    <th>95%ile (ms)</th>
-                    <th>99%ile (ms)</th>
-                    <th>100%ile (ms)</th>
-                </tr>
-            </thead>
-            <tbody>
-                {co_responses_rows}
-            </tbody>
-        </table>
-    </div>"#,
-        co_responses_rows = co_responses_rows,
-    )
+/// Escape the characters Slack reserves for links and mentions in message text.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
 
-/// Build an individual row of Coordinated Omission Mitigation request metrics in
-/// the html report.
-pub fn coordinated_omission_response_metrics_row(metric: ResponseMetric) -> String {
-    format!(
-        r#"<tr>
-            <td>{method}</td>
-            <td>{name}</td>
-            <td>{percentile_50}</td>
-            <td>{percentile_60}</td>
-            <td>{percentile_70}</td>
-            <td>{percentile_80}</td>
-            <td>{percentile_90}</td>
-            <td>{percentile_95}</td>
-            <td>{percentile_99}</td>
-            <td>{percentile_100}</td>
-        </tr>"#,
-        method = metric.method,
-        name = metric.name,
-        percentile_50 = metric.percentile_50,
-        percentile_60 = metric.percentile_60,
-        percentile_70 = metric.percentile_70,
-        percentile_80 = metric.percentile_80,
-        percentile_90 = metric.percentile_90,
-        percentile_95 = metric.percentile_95,
-        percentile_99 = metric.percentile_99,
-        percentile_100 = metric.percentile_100,
-    )
+/// Escape commas, equals signs and spaces in InfluxDB tag values.
+fn escape_influx_tag(value: &str) -> String {
+    value
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
 }
 
-/// If status code metrics are enabled, add a status code metrics table to the
-/// html report.
-pub fn status_code_metrics_template(status_code_rows: &str) -> String {
+/// Byte order mark prepended to CSV exports when requested, so spreadsheet applications
+/// such as Excel detect the UTF-8 encoding.
+const UTF8_BOM: char = '\u{FEFF}';
+
+/// Export the request metrics table as CSV, one row per endpoint. Rates are exported
+/// unformatted, so they can be parsed as numbers.
+///
+/// When `with_bom` is true the output starts with a UTF-8 byte order mark so that Excel
+/// correctly renders non-ASCII endpoint names. Most Unix tooling expects no BOM.
+pub fn request_metrics_csv(metrics: &[RequestMetric], with_bom: bool) -> String {
+    let mut rows =
+        vec!["method,name,requests,failures,average,min,max,rps,failures_per_second".to_string()];
+    for metric in metrics {
+        rows.push(format!(
+            "{},{},{},{},{},{},{},{},{}",
+            csv_field(&metric.method),
+            csv_field(&metric.name),
+            metric.number_of_requests,
+            metric.number_of_failures,
+            csv_field(&metric.response_time_average),
+            metric.response_time_minimum,
+            metric.response_time_maximum,
+            metric.requests_per_second_value,
+            metric.failures_per_second_value,
+        ));
+    }
+    csv_document(rows, with_bom)
+}
+
+/// Export the response time table as CSV, one row per endpoint.
+///
+/// See [`request_metrics_csv`] for the meaning of `with_bom`.
+pub fn response_metrics_csv(metrics: &[ResponseMetric], with_bom: bool) -> String {
+    let mut header = vec![
+        "method".to_string(),
+        "name".to_string(),
+        "average".to_string(),
+    ];
+    header.extend(PERCENTILES.iter().map(|(_, label)| label.to_string()));
+    let mut rows = vec![header.join(",")];
+    for metric in metrics {
+        rows.push(
+            [
+                &metric.method,
+                &metric.name,
+                &metric.response_time_average,
+                &metric.percentile_50,
+                &metric.percentile_60,
+                &metric.percentile_70,
+                &metric.percentile_80,
+                &metric.percentile_90,
+                &metric.percentile_95,
+                &metric.percentile_99,
+                &metric.percentile_100,
+            ]
+            .iter()
+            .map(|value| csv_field(value))
+            .join(","),
+        );
+    }
+    csv_document(rows, with_bom)
+}
+
+/// Quote a CSV field, doubling any embedded quotes.
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// Join CSV rows into a document, optionally prefixed with a UTF-8 byte order mark.
+fn csv_document(rows: Vec<String>, with_bom: bool) -> String {
+    let mut document = String::new();
+    if with_bom {
+        document.push(UTF8_BOM);
+    }
+    document.push_str(&rows.join("\n"));
+    document.push('\n');
+    document
+}
+
+/// Aggregate per-second graph data into points covering `interval` seconds each. The
+/// final point covers any remaining seconds if the data doesn't divide evenly.
+pub fn aggregate_graph_data<T>(data: &[T], interval: usize, aggregation: GraphAggregation) -> Vec<T>
+where
+    T: Copy + std::iter::Sum<T> + std::ops::Div<Output = T> + std::convert::TryFrom<usize>,
+{
+    if interval <= 1 {
+        return data.to_vec();
+    }
+
+    data.chunks(interval)
+        .map(|chunk| {
+            let total: T = chunk.iter().copied().sum();
+            match aggregation {
+                GraphAggregation::Sum => total,
+                GraphAggregation::Average => match T::try_from(chunk.len()) {
+                    Ok(length) => total / length,
+                    Err(_) => total,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Calculate the apdex score of a response time histogram with target response time
+/// `target` in milliseconds. Returns `None` if there are no response times.
+pub fn apdex(response_times: &BTreeMap<usize, usize>, target: usize) -> Option<f32> {
+    let mut total = 0;
+    let mut satisfied = 0;
+    let mut tolerating = 0;
+    for (response_time, count) in response_times {
+        total += count;
+        if *response_time <= target {
+            satisfied += count;
+        } else if *response_time <= target * 4 {
+            tolerating += count;
+        }
+    }
+    if total == 0 {
+        None
+    } else {
+        Some((satisfied as f32 + tolerating as f32 / 2.0) / total as f32)
+    }
+}
+
+/// Format an apdex score for display, or an empty string if there is no score.
+pub fn format_apdex(apdex: Option<f32>) -> String {
+    match apdex {
+        Some(apdex) => format!("{:.2}", apdex),
+        None => "".to_string(),
+    }
+}
+
+/// Format a requests or failures per second rate for display. Rates that aren't finite,
+/// such as when dividing by a duration of zero, are displayed as zero instead of as `NaN`
+/// or `inf`.
+pub fn format_rate(rate: f32) -> String {
+    if rate.is_finite() {
+        format!("{:.2}", rate)
+    } else {
+        format!("{:.2}", 0.0)
+    }
+}
+
+/// Add the counts and rates of `metric` to `existing`, weighting the averages by each
+/// metric's number of requests.
+fn add_request_metric(existing: &mut RequestMetric, metric: RequestMetric) {
+    let parse = |value: &str| value.parse::<f32>().unwrap_or(0.0);
+    let number_of_requests = existing.number_of_requests + metric.number_of_requests;
+    if number_of_requests > 0 {
+        existing.response_time_average = format!(
+            "{:.2}",
+            (parse(&existing.response_time_average) * existing.number_of_requests as f32
+                + parse(&metric.response_time_average) * metric.number_of_requests as f32)
+                / number_of_requests as f32
+        );
+    }
+    if number_of_requests > 0 && !metric.apdex.is_empty() {
+        existing.apdex = if existing.apdex.is_empty() {
+            metric.apdex.clone()
+        } else {
+            format!(
+                "{:.2}",
+                (parse(&existing.apdex) * existing.number_of_requests as f32
+                    + parse(&metric.apdex) * metric.number_of_requests as f32)
+                    / number_of_requests as f32
+            )
+        };
+    }
+    existing.number_of_requests = number_of_requests;
+    existing.number_of_failures += metric.number_of_failures;
+    existing.traffic_share += metric.traffic_share;
+    existing.first_seen = match (existing.first_seen, metric.first_seen) {
+        (Some(existing), Some(first_seen)) => Some(existing.min(first_seen)),
+        (existing, first_seen) => existing.or(first_seen),
+    };
+    existing.last_seen = existing.last_seen.max(metric.last_seen);
+    existing.connection_reuse = match (existing.connection_reuse, metric.connection_reuse) {
+        (Some(existing), Some(connection_reuse)) => Some(ConnectionReuse {
+            reused: existing.reused + connection_reuse.reused,
+            new: existing.new + connection_reuse.new,
+        }),
+        (existing, connection_reuse) => existing.or(connection_reuse),
+    };
+    for (status_code, count) in &metric.status_code_counts {
+        *existing.status_code_counts.entry(*status_code).or_insert(0) += count;
+    }
+    existing.response_time_minimum =
+        metrics::update_min_time(existing.response_time_minimum, metric.response_time_minimum);
+    existing.response_time_maximum =
+        metrics::update_max_time(existing.response_time_maximum, metric.response_time_maximum);
+    existing.requests_per_second_value += metric.requests_per_second_value;
+    existing.failures_per_second_value += metric.failures_per_second_value;
+    existing.requests_per_second = format_rate(existing.requests_per_second_value);
+    existing.failures_per_second = format_rate(existing.failures_per_second_value);
+    if let Some(series) = metric.requests_per_second_series {
+        let existing_series = existing
+            .requests_per_second_series
+            .get_or_insert_with(Vec::new);
+        if existing_series.len() < series.len() {
+            existing_series.resize(series.len(), 0);
+        }
+        for (second, count) in series.into_iter().enumerate() {
+            existing_series[second] += count;
+        }
+    }
+}
+
+/// Set each endpoint's share of all the requests, as a percentage.
+pub fn set_traffic_shares(metrics: &mut [RequestMetric]) {
+    let total = metrics
+        .iter()
+        .map(|metric| metric.number_of_requests)
+        .sum::<usize>();
+    for metric in metrics {
+        metric.traffic_share = if total > 0 {
+            metric.number_of_requests as f32 * 100.0 / total as f32
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Limit the number of rows in a table to `max_rows`, keeping the rows with the highest
+/// `impact` in their original order. Returns all rows if `max_rows` is `None`.
+pub fn truncate_rows<T, F>(rows: Vec<T>, max_rows: Option<usize>, impact: F) -> Vec<T>
+where
+    F: Fn(&T) -> usize,
+{
+    let max_rows = match max_rows {
+        Some(max_rows) if rows.len() > max_rows => max_rows,
+        _ => return rows,
+    };
+
+    let mut kept = (0..rows.len()).collect::<Vec<_>>();
+    kept.sort_by_key(|&index| std::cmp::Reverse(impact(&rows[index])));
+    kept.truncate(max_rows);
+    kept.sort_unstable();
+
+    let mut kept = kept.into_iter().peekable();
+    rows.into_iter()
+        .enumerate()
+        .filter_map(|(index, row)| {
+            if kept.peek() == Some(&index) {
+                kept.next();
+                Some(row)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Sort the request and response time metrics together by the given column, so each
+/// endpoint has the same position in both tables. Response metrics are matched to their
+/// request metric by method and name. Ties keep their original order.
+pub fn sort_metrics(
+    requests: &mut [RequestMetric],
+    responses: &mut [ResponseMetric],
+    sort_by: SortBy,
+    direction: SortDirection,
+) {
+    // Formatted numbers may include thousands separators.
+    let parse = |value: &str| value.replace(',', "").parse::<f64>().unwrap_or(0.0);
+    let percentile_95 = responses
+        .iter()
+        .map(|metric| {
+            (
+                (metric.method.clone(), metric.name.clone()),
+                parse(&metric.percentile_95),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let key = |metric: &RequestMetric| match sort_by {
+        SortBy::Name => 0.0,
+        SortBy::Requests => metric.number_of_requests as f64,
+        SortBy::Failures => metric.number_of_failures as f64,
+        SortBy::AvgResponseTime => parse(&metric.response_time_average),
+        SortBy::P95 => percentile_95
+            .get(&(metric.method.clone(), metric.name.clone()))
+            .copied()
+            .unwrap_or(0.0),
+        SortBy::Rps => metric.requests_per_second_value as f64,
+    };
+    requests.sort_by(|a, b| {
+        let ordering = match sort_by {
+            SortBy::Name => (&a.name, &a.method).cmp(&(&b.name, &b.method)),
+            _ => key(a)
+                .partial_cmp(&key(b))
+                .unwrap_or(std::cmp::Ordering::Equal),
+        };
+        match direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    });
+
+    let positions = requests
+        .iter()
+        .enumerate()
+        .map(|(position, metric)| ((metric.method.clone(), metric.name.clone()), position))
+        .collect::<HashMap<_, _>>();
+    responses.sort_by_key(|metric| {
+        positions
+            .get(&(metric.method.clone(), metric.name.clone()))
+            .copied()
+            .unwrap_or(usize::MAX)
+    });
+}
+
+/// Build a table row explaining that only `shown` of `total` rows are displayed, or an
+/// empty string if nothing was truncated.
+pub fn truncated_rows_notice(
+    shown: usize,
+    total: usize,
+    columns: usize,
+    strings: &ReportStrings,
+) -> String {
+    if shown >= total {
+        return "".to_string();
+    }
     format!(
-        r#"<div class="status_codes">
-        <h2>Status Code Metrics</h2>
-        <table>
-            <thead>
-                <tr>
-                    <th>Method</th>
-                    <th colspan="2">Name</th>
-                    <th colspan="3">Status Codes</th>
-                </tr>
-            </thead>
-            <tbody>
-                {status_code_rows}
-            </tbody>
-        </table>
-    </div>"#,
-        status_code_rows = status_code_rows,
+        r#"<tr class="truncated">
+        <td colspan="{columns}">{notice}</td>
+    </tr>"#,
+        columns = columns,
+        notice = strings
+            .truncated_rows
+            .replace("{shown}", &metrics::format_number(shown))
+            .replace("{total}", &metrics::format_number(total)),
     )
 }
 
-/// Build an individual row of status code metrics in the html report.
-pub fn status_code_metrics_row(metric: StatusCodeMetric) -> String {
+/// Calculate the percentage of the SLA error budget used: the failure percentage as a
+/// percentage of the `sla_failure_percentage` allowed, so 50.0 means half the budget was
+/// used. Returns `None` if there were no requests, or if the SLA doesn't allow a positive
+/// failure percentage, as there's no budget to use.
+pub fn sla_budget_used(
+    number_of_requests: usize,
+    number_of_failures: usize,
+    sla_failure_percentage: f32,
+) -> Option<f64> {
+    if number_of_requests == 0 || sla_failure_percentage.is_nan() || sla_failure_percentage <= 0.0 {
+        return None;
+    }
+    let failure_percentage = number_of_failures as f64 * 100.0 / number_of_requests as f64;
+    Some(failure_percentage * 100.0 / sla_failure_percentage as f64)
+}
+
+/// Build the cell displaying the percentage of the SLA error budget used, with a
+/// background shading from green at 0% to red at 100% and above.
+fn sla_budget_cell(metric: &RequestMetric, sla_failure_percentage: f32) -> String {
+    match sla_budget_used(
+        metric.number_of_requests,
+        metric.number_of_failures,
+        sla_failure_percentage,
+    ) {
+        Some(used) => format!(
+            r#"<td class="sla-budget" style="background-color: hsl({hue:.0}, 70%, 80%);">{used:.0}%</td>"#,
+            hue = 120.0 * (1.0 - used.min(100.0) / 100.0),
+            used = used,
+        ),
+        None => "<td></td>".to_string(),
+    }
+}
+
+/// Build the header of the request metrics table in the html report.
+pub fn request_metrics_header(options: &GooseReportOptions) -> String {
+    let mut cells = REQUEST_COLUMNS
+        .iter()
+        .filter(|column| options.shows_request_column(**column))
+        .map(|column| {
+            format!(
+                "<th>{}</th>",
+                column.label(&options.strings, options.rate_unit)
+            )
+        })
+        .collect::<Vec<_>>();
+    if options.show_first_last_seen {
+        cells.push(format!("<th>{}</th>", options.strings.first_seen));
+        cells.push(format!("<th>{}</th>", options.strings.last_seen));
+    }
+    if options.show_connection_reuse {
+        cells.push(format!("<th>{}</th>", options.strings.connection_reuse));
+    }
+    if options.sla_failure_percentage.is_some() {
+        cells.push(format!("<th>{}</th>", options.strings.sla_budget_used));
+    }
+
     format!(
         r#"<tr>
-        <td>{method}</td>
-        <td colspan="2">{name}</td>
-        <td colspan="3">{status_codes}</td>
+                        <th>{method}</th>
+                        <th>{name}</th>
+                        {cells}
+                    </tr>"#,
+        method = options.strings.method,
+        name = options.strings.name,
+        cells = cells.join("\n                        "),
+    )
+}
+
+/// The number of columns of the request metrics table.
+pub fn request_metrics_columns(options: &GooseReportOptions) -> usize {
+    2 + REQUEST_COLUMNS
+        .iter()
+        .filter(|column| options.shows_request_column(**column))
+        .count()
+        + if options.show_first_last_seen { 2 } else { 0 }
+        + if options.show_connection_reuse { 1 } else { 0 }
+        + if options.sla_failure_percentage.is_some() {
+            1
+        } else {
+            0
+        }
+}
+
+/// Build the rows of the request metrics table grouped by scenario, each group under a
+/// header naming its scenario and followed by a subtotal of its requests.
+///
+/// Goose doesn't record which task set made each request, so `scenarios` must pair each
+/// scenario's name with the request metrics of the requests it made.
+pub fn scenario_request_metrics_rows(
+    scenarios: Vec<(String, Vec<RequestMetric>)>,
+    options: &GooseReportOptions,
+) -> Vec<String> {
+    let mut rows = Vec::new();
+    for (scenario, metrics) in scenarios {
+        rows.push(format!(
+            r#"<tr class="scenario">
+        <td colspan="{columns}" align="left"><strong>{scenario}</strong></td>
     </tr>"#,
-        method = metric.method,
-        name = metric.name,
-        status_codes = metric.status_codes,
+            columns = request_metrics_columns(options),
+            scenario = escape_html(&scenario),
+        ));
+        let mut subtotal: Option<RequestMetric> = None;
+        for metric in metrics {
+            match subtotal.as_mut() {
+                Some(subtotal) => add_request_metric(subtotal, metric.clone()),
+                None => {
+                    subtotal = Some(RequestMetric {
+                        method: "".to_string(),
+                        name: options.strings.subtotal.clone(),
+                        ..metric.clone()
+                    })
+                }
+            }
+            rows.push(raw_request_metrics_row(metric, options));
+        }
+        if let Some(subtotal) = subtotal {
+            rows.push(raw_request_metrics_row(subtotal, options));
+        }
+    }
+    rows
+}
+
+/// Build an individual row of raw request metrics in the html report.
+pub fn raw_request_metrics_row(metric: RequestMetric, options: &GooseReportOptions) -> String {
+    let sparkline = match metric.requests_per_second_series.as_ref() {
+        Some(series) => format!(" {}", sparkline(series, options.palette)),
+        None => "".to_string(),
+    };
+    let locale = &options.locale;
+    let mut cells = REQUEST_COLUMNS
+        .iter()
+        .filter(|column| options.shows_request_column(**column))
+        .map(|column| match column {
+            RequestColumn::Requests => format!(
+                r#"<td data-count="{count}" data-rps="{rate}">{count}</td>"#,
+                count = metric.number_of_requests,
+                rate = metric.requests_per_second,
+            ),
+            RequestColumn::Failures => format!(
+                r#"<td data-count="{count}" data-rps="{rate}">{count}</td>"#,
+                count = metric.number_of_failures,
+                rate = metric.failures_per_second,
+            ),
+            RequestColumn::Average => {
+                format!("<td>{}</td>", locale.localize(&metric.response_time_average))
+            }
+            RequestColumn::Minimum => format!("<td>{}</td>", metric.response_time_minimum),
+            RequestColumn::Maximum => format!("<td>{}</td>", metric.response_time_maximum),
+            RequestColumn::RequestsPerSecond => format!(
+                "<td>{}{}</td>",
+                locale.localize(&metric.requests_per_second),
+                sparkline
+            ),
+            RequestColumn::FailuresPerSecond => {
+                format!("<td>{}</td>", locale.localize(&metric.failures_per_second))
+            }
+            RequestColumn::Apdex => format!("<td>{}</td>", locale.localize(&metric.apdex)),
+            RequestColumn::TrafficShare => format!(
+                r#"<td class="traffic-share">{value}%<div class="bar" style="width: {share:.1}%;"></div></td>"#,
+                value = locale.localize(&format!("{:.1}", metric.traffic_share)),
+                share = metric.traffic_share,
+            ),
+        })
+        .collect::<Vec<_>>();
+    if options.show_first_last_seen {
+        for seen in &[metric.first_seen, metric.last_seen] {
+            cells.push(format!(
+                r#"<td class="seen">{}</td>"#,
+                seen.as_ref()
+                    .map(|seen| locale.format_datetime(seen))
+                    .unwrap_or_default()
+            ));
+        }
+    }
+    if options.show_connection_reuse {
+        cells.push(format!(
+            "<td>{}</td>",
+            metric
+                .connection_reuse
+                .and_then(|connection_reuse| connection_reuse.percentage())
+                .map(|percentage| locale.localize(&format!("{:.0}%", percentage)))
+                .unwrap_or_default()
+        ));
+    }
+    if let Some(sla_failure_percentage) = options.sla_failure_percentage {
+        cells.push(sla_budget_cell(&metric, sla_failure_percentage));
+    }
+
+    let class = if options.highlight_error_status && metric.has_error_status() {
+        r#" class="error-status""#
+    } else {
+        ""
+    };
+
+    format!(
+        r#"<tr data-name="{data_name}"{class}>
+        {method}
+        <td>{name}</td>
+        {cells}
+    </tr>"#,
+        data_name = escape_html(&metric.name),
+        class = class,
+        method = method_cell(&metric.method, options),
+        name = request_name(&metric.name, options),
+        cells = cells.join("\n        "),
     )
 }
 
-/// If task metrics are enabled, add a task metrics table to the html report.
-pub fn task_metrics_template(task_rows: &str, graph_tasks_per_second: &str) -> String {
+/// Build the header of the response metrics table in the html report.
+pub fn response_metrics_header(options: &GooseReportOptions) -> String {
     format!(
-        r#"<div class="tasks">
-        <h2>Task Metrics</h2>
+        r#"<tr>
+                        <th>{method}</th>
+                        <th>{name}</th>
+                        {cells}
+                    </tr>"#,
+        method = options.strings.method,
+        name = options.strings.name,
+        cells = response_metrics_header_cells(options).join("\n                        "),
+    )
+}
 
-        {graph_tasks_per_second}
+/// Build the average and percentile header cells of a response time metrics table.
+fn response_metrics_header_cells(options: &GooseReportOptions) -> Vec<String> {
+    let mut cells = Vec::new();
+    if options.shows_response_average() {
+        cells.push(format!("<th>{}</th>", options.strings.average));
+    }
+    for ((percentile, label), column) in PERCENTILES.iter().zip(PERCENTILE_COLUMNS.iter()) {
+        if !options.shows_response_column(*column) {
+            continue;
+        }
+        let label: &str = if *percentile == 1.0 && options.max_percentile == MaxPercentile::Max {
+            &options.strings.max
+        } else {
+            label
+        };
+        let custom_label = options
+            .percentile_labels
+            .iter()
+            .find(|(labeled, _)| labeled == percentile)
+            .map(|(_, suffix)| suffix);
+        cells.push(format!(
+            "<th{class}>{label} ({unit})</th>",
+            class = percentile_class(*percentile, options),
+            // Labeled percentiles are shortened, such as "p95 (SLA)", to keep the header
+            // narrow.
+            label = match custom_label {
+                Some(suffix) => format!(
+                    "{} ({})",
+                    match label.strip_suffix("%ile") {
+                        Some(number) => format!("p{}", number),
+                        None => label.to_string(),
+                    },
+                    escape_html(suffix)
+                ),
+                None => label.to_string(),
+            },
+            unit = if options.percentiles_relative_to_median {
+                "&times; p50"
+            } else {
+                "ms"
+            },
+        ));
+    }
+    if let Some(threshold) = options.over_threshold_ms {
+        cells.push(format!(
+            "<th>&gt; {} ms</th>",
+            options.locale.format_number(threshold)
+        ));
+    }
+    cells
+}
 
-        <table>
-            <thead>
-                <tr>
-                    <th colspan="2">Task</th>
-                    <th># Times Run</th>
-                    <th># Fails</th>
-                    <th>Average (ms)</th>
-                    <th>Min (ms)</th>
-                    <th>Max (ms)</th>
-                    <th>RPS</th>
-                    <th>Failures/s</th>
-                </tr>
-            </thead>
-            <tbody>
-                {task_rows}
-            </tbody>
-        </table>
-    </div>"#,
-        task_rows = task_rows,
-        graph_tasks_per_second = graph_tasks_per_second,
+/// The number of columns of the response time metrics table.
+pub fn response_metrics_columns(options: &GooseReportOptions) -> usize {
+    2 + PERCENTILE_COLUMNS
+        .iter()
+        .filter(|column| options.shows_response_column(**column))
+        .count()
+        + if options.shows_response_average() {
+            1
+        } else {
+            0
+        }
+        + if options.over_threshold_ms.is_some() {
+            1
+        } else {
+            0
+        }
+}
+
+/// Build a tiny inline SVG line showing how a per-second series changed over time.
+pub fn sparkline(series: &[u32], palette: Palette) -> String {
+    let width = 60.0;
+    let height = 16.0;
+    let max = series.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let step = if series.len() > 1 {
+        width / (series.len() - 1) as f32
+    } else {
+        0.0
+    };
+
+    let points = series
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            format!(
+                "{:.1},{:.1}",
+                index as f32 * step,
+                height - (*value as f32 / max * height)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        r##"<svg class="sparkline" width="{width}" height="{height}" viewBox="0 0 {width} {height}"><polyline fill="none" stroke="{color}" stroke-width="1" points="{points}" /></svg>"##,
+        color = palette.color("#2c664f"),
+        width = width,
+        height = height,
+        points = points,
     )
 }
 
-/// Build an individual row of task metrics in the html report.
-pub fn task_metrics_row(metric: TaskMetric) -> String {
-    if metric.is_task_set {
-        format!(
-            r#"<tr>
-            <td colspan="10" align="left"><strong>{name}</strong></td>
+/// Build an individual row of response metrics in the html report.
+pub fn response_metrics_row(metric: ResponseMetric, options: &GooseReportOptions) -> String {
+    format!(
+        r#"<tr data-name="{data_name}">
+            {method}
+            <td>{name}</td>
+            {cells}
         </tr>"#,
-            name = metric.name,
-        )
+        data_name = escape_html(&metric.name),
+        method = method_cell(&metric.method, options),
+        name = request_name(&metric.name, options),
+        cells = response_metrics_cells(&metric, options).join("\n            "),
+    )
+}
+
+/// Build the average and percentile cells of a row of a response time metrics table.
+fn response_metrics_cells(metric: &ResponseMetric, options: &GooseReportOptions) -> Vec<String> {
+    let values = [
+        &metric.percentile_50,
+        &metric.percentile_60,
+        &metric.percentile_70,
+        &metric.percentile_80,
+        &metric.percentile_90,
+        &metric.percentile_95,
+        &metric.percentile_99,
+        &metric.percentile_100,
+    ];
+
+    let mut cells = Vec::new();
+    if options.shows_response_average() {
+        cells.push(format!(
+            "<td>{}</td>",
+            options.locale.localize(&metric.response_time_average)
+        ));
+    }
+    for (((percentile, _), value), column) in PERCENTILES
+        .iter()
+        .zip(values.iter())
+        .zip(PERCENTILE_COLUMNS.iter())
+    {
+        if !options.shows_response_column(*column) {
+            continue;
+        }
+        cells.push(format!(
+            "<td{class}>{value}</td>",
+            class = percentile_class(*percentile, options),
+            value = options
+                .locale
+                .localize(&percentile_value(value, metric, options)),
+        ));
+    }
+    if options.over_threshold_ms.is_some() {
+        cells.push(format!(
+            "<td>{}</td>",
+            options.locale.localize(&metric.over_threshold)
+        ));
+    }
+    cells
+}
+
+/// Format a percentile of the response time metrics tables, in milliseconds or as a
+/// multiple of the endpoint's median.
+fn percentile_value(value: &str, metric: &ResponseMetric, options: &GooseReportOptions) -> String {
+    if !options.percentiles_relative_to_median {
+        return value.to_string();
+    }
+    match parse_formatted_number(&metric.percentile_50) {
+        0 => "&ndash;".to_string(),
+        median => format!(
+            "{:.1}&times;",
+            parse_formatted_number(value) as f64 / median as f64
+        ),
+    }
+}
+
+/// Returns the class attribute used to emphasize or de-emphasize a percentile column, or
+/// nothing if no key percentiles are configured.
+fn percentile_class(percentile: f32, options: &GooseReportOptions) -> &'static str {
+    if options.key_percentiles.is_empty() {
+        ""
+    } else if options.key_percentiles.contains(&percentile) {
+        r#" class="key""#
     } else {
-        format!(
-            r#"<tr>
-            <td colspan="2">{task} {name}</strong></td>
-            <td>{number_of_requests}</td>
-            <td>{number_of_failures}</td>
-            <td>{response_time_average}</td>
-            <td>{response_time_minimum}</td>
-            <td>{response_time_maximum}</td>
-            <td>{requests_per_second}</td>
-            <td>{failures_per_second}</td>
-        </tr>"#,
-            task = metric.task,
-            name = metric.name,
-            number_of_requests = metrics::format_number(metric.number_of_requests),
-            number_of_failures = metrics::format_number(metric.number_of_failures),
-            response_time_average = metric.response_time_average,
-            response_time_minimum = metric.response_time_minimum,
-            response_time_maximum = metric.response_time_maximum,
-            requests_per_second = metric.requests_per_second,
-            failures_per_second = metric.failures_per_second,
-        )
+        r#" class="minor""#
     }
 }
 
-/// If there are errors, add an errors table to the html report.
-pub fn errors_template(error_rows: &str, graph: &str) -> String {
+/// Build a footer row for the response metrics table showing, for each percentile column,
+/// the slowest response time seen across all endpoints and which endpoint it was seen on.
+pub fn response_metrics_footer(metrics: &[ResponseMetric], options: &GooseReportOptions) -> String {
+    if metrics.is_empty() {
+        return "".to_string();
+    }
+
+    let columns: [fn(&ResponseMetric) -> &str; 8] = [
+        |metric| &metric.percentile_50,
+        |metric| &metric.percentile_60,
+        |metric| &metric.percentile_70,
+        |metric| &metric.percentile_80,
+        |metric| &metric.percentile_90,
+        |metric| &metric.percentile_95,
+        |metric| &metric.percentile_99,
+        |metric| &metric.percentile_100,
+    ];
+
+    let mut cells = Vec::new();
+    if options.shows_response_average() {
+        cells.push("<td></td>".to_string());
+    }
+    for (((percentile, _), column), response_column) in PERCENTILES
+        .iter()
+        .zip(columns.iter())
+        .zip(PERCENTILE_COLUMNS.iter())
+    {
+        if !options.shows_response_column(*response_column) {
+            continue;
+        }
+        // Pick the first endpoint with the largest value, ties are won by the earlier row.
+        let mut slowest = &metrics[0];
+        for metric in metrics.iter().skip(1) {
+            if parse_formatted_number(column(metric)) > parse_formatted_number(column(slowest)) {
+                slowest = metric;
+            }
+        }
+        cells.push(format!(
+            r#"<td{class}>{value}<br /><small>{method} {name}</small></td>"#,
+            class = percentile_class(*percentile, options),
+            value = options
+                .locale
+                .localize(&percentile_value(column(slowest), slowest, options)),
+            method = slowest.method,
+            name = slowest.name,
+        ));
+    }
+    if options.over_threshold_ms.is_some() {
+        cells.push("<td></td>".to_string());
+    }
+
     format!(
-        r#"<div class="errors">
-        <h2>Errors</h2>
+        r#"<tr>
+            <td colspan="2"><strong>{slowest}</strong></td>
+            {cells}
+        </tr>"#,
+        slowest = options.strings.slowest,
+        cells = cells.join("\n            "),
+    )
+}
 
-        {graph}
+/// Convert a number formatted by `metrics::format_number` back into a number.
+fn parse_formatted_number(value: &str) -> usize {
+    value.replace(',', "").parse().unwrap_or(0)
+}
 
+/// If Coordinated Omission Mitigation is triggered, add a relevant request table to the
+/// html report.
+pub fn coordinated_omission_request_metrics_template(
+    co_requests_rows: &str,
+    strings: &ReportStrings,
+) -> String {
+    format!(
+        r#"<div class="CO requests">
+        <h2>{co_request_metrics}</h2>
         <table>
             <thead>
                 <tr>
-                    <th>#</th>
-                    <th colspan="3">Error</th>
+                    <th>{method}</th>
+                    <th>{name}</th>
+                    <th>{average}</th>
+                    <th>{standard_deviation}</th>
+                    <th>{maximum}</th>
                 </tr>
             </thead>
             <tbody>
-                {error_rows}
+                {co_requests_rows}
             </tbody>
         </table>
     </div>"#,
-        error_rows = error_rows,
-        graph = graph,
+        co_requests_rows = co_requests_rows,
+        co_request_metrics = strings.co_request_metrics,
+        method = strings.method,
+        name = strings.name,
+        average = strings.average,
+        standard_deviation = strings.standard_deviation,
+        maximum = strings.maximum,
     )
 }
 
-/// Build an individual error row in the html report.
-pub fn error_row(error: &metrics::GooseErrorMetricAggregate) -> String {
+/// Build an individual row of Coordinated Omission Mitigation request metrics in
+/// the html report.
+pub fn coordinated_omission_request_metrics_row(metric: CORequestMetric) -> String {
     format!(
         r#"<tr>
-        <td>{occurrences}</td>
-        <td colspan="4">{error}</strong></td>
-    </tr>"#,
-        occurrences = error.occurrences,
-        error = error.error,
-    )
-}
-
-/// Build a requests per second graph.
-pub fn graph_rps_template(
-    rps: &[(String, u32)],
-    starting: Option<DateTime<Local>>,
-    started: Option<DateTime<Local>>,
-    stopping: Option<DateTime<Local>>,
-    stopped: Option<DateTime<Local>>,
-) -> String {
-    Graph::new(
-        "graph-rps",
-        "Requests #",
-        rps,
-        starting,
-        started,
-        stopping,
-        stopped,
-    )
-    .generate_markup()
+            <td>{method}</td>
+            <td>{name}</td>
+            <td>{average})</td>
+            <td>{standard_deviation}</td>
+            <td>{maximum}</td>
+        </tr>"#,
+        method = metric.method,
+        name = metric.name,
+        average = metric.response_time_average,
+        standard_deviation = metric.response_time_standard_deviation,
+        maximum = metric.response_time_maximum,
+    )
 }
 
-/// Build an errors per second graph.
-pub fn graph_eps_template(
-    eps: &[(String, u32)],
-    starting: Option<DateTime<Local>>,
-    started: Option<DateTime<Local>>,
-    stopping: Option<DateTime<Local>>,
-    stopped: Option<DateTime<Local>>,
+/// If Coordinated Omission Mitigation is triggered, add a relevant response table to the
+/// html report.
+pub fn coordinated_omission_response_metrics_template(
+    co_responses_rows: &str,
+    strings: &ReportStrings,
 ) -> String {
-    Graph::new(
-        "graph-eps",
-        "Errors #",
-        eps,
-        starting,
-        started,
-        stopping,
-        stopped,
+    format!(
+        r#"<div class="responses">
+        <h2>{co_response_time_metrics}</h2>
+        <table>
+            <thead>
+                <tr>
+                    <th>{method}</th>
+                    <th>{name}</th>
+                    <th>50%ile (ms)</th>
+                    <th>60%ile (ms)</th>
+                    <th>70%ile (ms)</th>
+                    <th>80%ile (ms)</th>
+                    <th>90%ile (ms)</th>
+                    <th>95%ile (ms)</th>
+                    <th>99%ile (ms)</th>
+                    <th>100%ile (ms)</th>
+                </tr>
+            </thead>
+            <tbody>
+                {co_responses_rows}
+            </tbody>
+        </table>
+    </div>"#,
+        co_responses_rows = co_responses_rows,
+        co_response_time_metrics = strings.co_response_time_metrics,
+        method = strings.method,
+        name = strings.name,
+    )
+}
+
+/// Build an individual row of Coordinated Omission Mitigation request metrics in
+/// the html report.
+pub fn coordinated_omission_response_metrics_row(metric: ResponseMetric) -> String {
+    format!(
+        r#"<tr>
+            <td>{method}</td>
+            <td>{name}</td>
+            <td>{percentile_50}</td>
+            <td>{percentile_60}</td>
+            <td>{percentile_70}</td>
+            <td>{percentile_80}</td>
+            <td>{percentile_90}</td>
+            <td>{percentile_95}</td>
+            <td>{percentile_99}</td>
+            <td>{percentile_100}</td>
+        </tr>"#,
+        method = metric.method,
+        name = metric.name,
+        percentile_50 = metric.percentile_50,
+        percentile_60 = metric.percentile_60,
+        percentile_70 = metric.percentile_70,
+        percentile_80 = metric.percentile_80,
+        percentile_90 = metric.percentile_90,
+        percentile_95 = metric.percentile_95,
+        percentile_99 = metric.percentile_99,
+        percentile_100 = metric.percentile_100,
+    )
+}
+
+/// If status code metrics are enabled, add a status code metrics table to the
+/// html report.
+pub fn status_code_metrics_template(
+    status_code_rows: &str,
+    graph: &str,
+    strings: &ReportStrings,
+) -> String {
+    format!(
+        r#"<div class="status_codes">
+        <h2>{status_code_metrics}</h2>
+
+        {graph}
+
+        <table>
+            <thead>
+                <tr>
+                    <th>{method}</th>
+                    <th colspan="2">{name}</th>
+                    <th colspan="3">{status_codes}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {status_code_rows}
+            </tbody>
+        </table>
+    </div>"#,
+        status_code_rows = status_code_rows,
+        graph = graph,
+        status_code_metrics = strings.status_code_metrics,
+        method = strings.method,
+        name = strings.name,
+        status_codes = strings.status_codes,
+    )
+}
+
+/// Group status code counts into classes such as `2xx` and `5xx`. Codes outside the
+/// 100-599 range, such as the `0` recorded when no response was received, are grouped as
+/// `Other`.
+pub fn status_classes(status_code_counts: &HashMap<u16, usize>) -> BTreeMap<String, usize> {
+    let mut classes = BTreeMap::new();
+    for (status_code, count) in status_code_counts {
+        let class = if (100..600).contains(status_code) {
+            format!("{}xx", status_code / 100)
+        } else {
+            "Other".to_string()
+        };
+        *classes.entry(class).or_insert(0) += count;
+    }
+    classes
+}
+
+/// Build a donut chart of the requests in each status class, as grouped by
+/// [`status_classes`], with 2xx in green, 3xx in blue, 4xx in yellow and 5xx in red.
+pub fn graph_status_class_donut_template(
+    counts: &BTreeMap<String, usize>,
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    let data = counts
+        .iter()
+        .map(|(class, count)| {
+            let color = match class.as_str() {
+                "2xx" => ColorRole::Success.colors(palette).0,
+                "3xx" => palette.color("#2f6fa3"),
+                "4xx" => palette.color("#d9b310"),
+                "5xx" => ColorRole::Danger.colors(palette).0,
+                _ => ColorRole::Neutral.colors(palette).0,
+            };
+            let name = if class == "Other" {
+                &strings.other
+            } else {
+                class
+            };
+            json!({ "name": name, "value": count, "itemStyle": { "color": color } })
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-status-classes" style="width: 1000px; height:300px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-status-classes');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        tooltip: {{ trigger: 'item', formatter: '{{b}}: {{c}} ({{d}}%)' }},
+                        legend: {{ orient: 'vertical', left: 'left' }},
+                        series: [
+                            {{
+                                name: {status_class},
+                                type: 'pie',
+                                radius: ['40%', '70%'],
+                                data: {data}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        data = json!(data),
+        status_class = json!(strings.status_class),
+    )
+}
+
+/// Build an individual row of status code metrics in the html report.
+pub fn status_code_metrics_row(metric: StatusCodeMetric) -> String {
+    format!(
+        r#"<tr data-name="{data_name}">
+        <td>{method}</td>
+        <td colspan="2">{name}</td>
+        <td colspan="3">{status_codes}</td>
+    </tr>"#,
+        data_name = escape_html(&metric.name),
+        method = metric.method,
+        name = metric.name,
+        status_codes = metric.status_codes,
+    )
+}
+
+/// Build a status code metrics table row counting the endpoints hidden by
+/// [`GooseReportOptions::hide_successful_status_codes`], or an empty string if none were.
+pub fn hidden_successful_endpoints_notice(hidden: usize, strings: &ReportStrings) -> String {
+    if hidden == 0 {
+        return "".to_string();
+    }
+    let notice = if hidden == 1 {
+        &strings.hidden_endpoint
+    } else {
+        &strings.hidden_endpoints
+    };
+    format!(
+        r#"<tr class="truncated">
+        <td colspan="6">{}</td>
+    </tr>"#,
+        notice.replace("{hidden}", &metrics::format_number(hidden)),
+    )
+}
+
+/// If task metrics are enabled, add a task metrics table to the html report.
+pub fn task_metrics_template(
+    task_rows: &str,
+    graph_tasks_per_second: &str,
+    strings: &ReportStrings,
+) -> String {
+    format!(
+        r#"<div class="tasks">
+        <h2>{task_metrics}</h2>
+
+        {graph_tasks_per_second}
+
+        <table>
+            <thead>
+                <tr>
+                    <th colspan="2">{task}</th>
+                    <th>{times_run}</th>
+                    <th>{failures}</th>
+                    <th>{average}</th>
+                    <th>{minimum}</th>
+                    <th>{maximum}</th>
+                    <th>{requests_per_second}</th>
+                    <th>{failures_per_second}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {task_rows}
+            </tbody>
+        </table>
+    </div>"#,
+        task_rows = task_rows,
+        graph_tasks_per_second = graph_tasks_per_second,
+        task_metrics = strings.task_metrics,
+        task = strings.task,
+        requests_per_second = strings.requests_per_second,
+        failures_per_second = strings.failures_per_second,
+        times_run = strings.times_run,
+        failures = strings.failures,
+        average = strings.average,
+        minimum = strings.minimum,
+        maximum = strings.maximum,
+    )
+}
+
+/// Build an individual row of task metrics in the html report.
+pub fn task_metrics_row(metric: TaskMetric) -> String {
+    if metric.is_task_set {
+        format!(
+            r#"<tr>
+            <td colspan="10" align="left"><strong>{name}</strong></td>
+        </tr>"#,
+            name = metric.name,
+        )
+    } else {
+        format!(
+            r#"<tr>
+            <td colspan="2">{task} {name}</strong></td>
+            <td data-count="{number_of_requests}" data-rps="{requests_per_second}">{number_of_requests}</td>
+            <td data-count="{number_of_failures}" data-rps="{failures_per_second}">{number_of_failures}</td>
+            <td>{response_time_average}</td>
+            <td>{response_time_minimum}</td>
+            <td>{response_time_maximum}</td>
+            <td>{requests_per_second}</td>
+            <td>{failures_per_second}</td>
+        </tr>"#,
+            task = metric.task,
+            name = metric.name,
+            number_of_requests = metrics::format_number(metric.number_of_requests),
+            number_of_failures = metrics::format_number(metric.number_of_failures),
+            response_time_average = metric.response_time_average,
+            response_time_minimum = metric.response_time_minimum,
+            response_time_maximum = metric.response_time_maximum,
+            requests_per_second = metric.requests_per_second,
+            failures_per_second = metric.failures_per_second,
+        )
+    }
+}
+
+/// If task percentiles are enabled, add a table of task run time percentiles to the
+/// html report, with the same columns as the response time metrics table.
+pub fn task_response_metrics_template(
+    task_response_rows: &str,
+    options: &GooseReportOptions,
+) -> String {
+    format!(
+        r#"<div class="tasks">
+        <h2>{task_run_time_metrics}</h2>
+
+        <table>
+            <thead>
+                <tr>
+                    <th colspan="2">{task}</th>
+                    {cells}
+                </tr>
+            </thead>
+            <tbody>
+                {task_response_rows}
+            </tbody>
+        </table>
+    </div>"#,
+        cells = response_metrics_header_cells(options).join("\n                    "),
+        task_response_rows = task_response_rows,
+        task_run_time_metrics = options.strings.task_run_time_metrics,
+        task = options.strings.task,
+    )
+}
+
+/// Build an individual row of task run time percentiles in the html report. The
+/// `metric` is built by [`get_response_metric`] from the task's run time histogram, with
+/// its name used as the task's name.
+pub fn task_response_metrics_row(
+    task: &str,
+    metric: ResponseMetric,
+    options: &GooseReportOptions,
+) -> String {
+    format!(
+        r#"<tr>
+            <td colspan="2">{task} {name}</td>
+            {cells}
+        </tr>"#,
+        task = task,
+        name = metric.name,
+        cells = response_metrics_cells(&metric, options).join("\n            "),
+    )
+}
+
+/// When the first error of a load test occurred, and the load at that time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FirstError {
+    /// When the first error occurred, to the second.
+    pub time: DateTime<Local>,
+    /// The number of active users when the first error occurred.
+    pub users: usize,
+    /// The requests per second when the first error occurred.
+    pub requests_per_second: u32,
+}
+
+/// Find the first second with errors, and correlate it with the active users and requests
+/// per second in that same second. Each series has one value per second since `starting`,
+/// so they're correlated before being aggregated for the graphs, whose intervals may
+/// differ. Returns `None` if there were no errors.
+pub fn first_error(
+    eps: &[u32],
+    users: &[usize],
+    rps: &[u32],
+    starting: &DateTime<Local>,
+) -> Option<FirstError> {
+    let second = eps.iter().position(|count| *count > 0)?;
+    Some(FirstError {
+        time: *starting + chrono::Duration::seconds(second as i64),
+        users: users.get(second).copied().unwrap_or(0),
+        requests_per_second: rps.get(second).copied().unwrap_or(0),
+    })
+}
+
+/// Build the line describing when the first error occurred, or an empty string if
+/// unknown.
+pub fn first_error_template(first_error: Option<&FirstError>, strings: &ReportStrings) -> String {
+    match first_error {
+        Some(first_error) => format!(
+            "<p>{}</p>",
+            strings
+                .first_error
+                // Only the time of day is displayed, the date is in the report header.
+                .replace("{time}", &first_error.time.format("%H:%M:%S").to_string())
+                .replace("{users}", &metrics::format_number(first_error.users))
+                .replace(
+                    "{rps}",
+                    &metrics::format_number(first_error.requests_per_second as usize)
+                ),
+        ),
+        None => "".to_string(),
+    }
+}
+
+/// If there are errors, add an errors table to the html report.
+pub fn errors_template(
+    error_rows: &str,
+    graph: &str,
+    first_error: Option<&FirstError>,
+    strings: &ReportStrings,
+) -> String {
+    format!(
+        r#"<div class="errors">
+        <h2>{errors}</h2>
+        {first_error}
+
+        {graph}
+
+        <table>
+            <thead>
+                <tr>
+                    <th>#</th>
+                    <th colspan="3">{error}</th>
+                </tr>
+            </thead>
+            <tbody>
+                {error_rows}
+            </tbody>
+        </table>
+    </div>"#,
+        error_rows = error_rows,
+        graph = graph,
+        first_error = first_error_template(first_error, strings),
+        errors = strings.errors,
+        error = strings.error,
     )
-    .generate_markup()
 }
 
-/// Build an average response time graph.
-pub fn graph_average_response_time_template(
-    response_times: &[(String, u32)],
-    starting: Option<DateTime<Local>>,
-    started: Option<DateTime<Local>>,
-    stopping: Option<DateTime<Local>>,
-    stopped: Option<DateTime<Local>>,
-) -> String {
-    Graph::new(
-        "graph-avg-response-time",
-        "Response time [ms]",
-        response_times,
-        starting,
-        started,
-        stopping,
-        stopped,
-    )
-    .generate_markup()
-}
+/// Errors longer than this many characters are truncated, and displayed in full in a
+/// collapsible block, by default.
+pub const DEFAULT_MAX_ERROR_LENGTH: usize = 500;
+
+/// Build an individual error row in the html report.
+///
+/// Multi-line and long errors, such as chains of connection errors or server error
+/// bodies, are displayed in a collapsible `<details>` block summarized by their first
+/// line, truncated to the report's `max_error_length`, so they don't break the table
+/// layout.
+pub fn error_row(
+    error: &metrics::GooseErrorMetricAggregate,
+    options: &GooseReportOptions,
+) -> String {
+    let max_error_length = options.max_error_length.unwrap_or(DEFAULT_MAX_ERROR_LENGTH);
+    let error_text = if options.preserve_ansi_escapes {
+        error.error.to_string()
+    } else {
+        strip_ansi_escapes(&error.error)
+    };
+    let text = error_text.trim_end();
+    let error_markup = if text.contains('\n') || text.chars().count() > max_error_length {
+        let summary = text.lines().next().unwrap_or_default();
+        let summary = if summary.chars().count() > max_error_length {
+            format!(
+                "{}&hellip;",
+                escape_html(&summary.chars().take(max_error_length).collect::<String>())
+            )
+        } else {
+            escape_html(summary)
+        };
+        format!(
+            "<details><summary>{}</summary><pre>{}</pre></details>",
+            summary,
+            escape_html(text)
+        )
+    } else {
+        escape_html(text)
+    };
+    format!(
+        r#"<tr>
+        <td>{occurrences}</td>
+        <td colspan="4">{error}</strong></td>
+    </tr>"#,
+        occurrences = error.occurrences,
+        error = error_markup,
+    )
+}
+
+/// Remove ANSI escape sequences, such as terminal colors, from text, which would
+/// otherwise display as garbage in html. Runs before html escaping.
+pub fn strip_ansi_escapes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // Control sequences, such as colors: parameters and intermediate bytes up
+                // to a final byte.
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // Operating system commands, such as hyperlinks: up to a bell or string
+                // terminator.
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Other escapes are two characters long.
+                _ => {}
+            },
+            // The single character control sequence introducer.
+            '\u{9b}' => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Escape text for display in html.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Build a requests per second graph. With [`RateUnit::PerMinute`] the per-second values
+/// are scaled to requests per minute and the y axis is relabeled.
+pub fn graph_rps_template(
+    rps: &[(String, u32)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> String {
+    let data = rps
+        .iter()
+        .map(|(time, count)| {
+            (
+                time.to_string(),
+                options.rate_unit.scale(*count as f32) as u32,
+            )
+        })
+        .collect::<Vec<_>>();
+    report_graph(ReportGraph::RequestsPerSecond, &data, phases, options).generate_markup()
+}
+
+/// Build a time-series graph of the html report, styled by `options`.
+fn report_graph<'a, T: Serialize>(
+    graph: ReportGraph,
+    data: &'a [(String, T)],
+    phases: GraphPhases,
+    options: &'a GooseReportOptions,
+) -> Graph<'a, T> {
+    let mut chart = Graph::new(
+        graph.html_id(),
+        graph.y_axis_label(options),
+        data,
+        phases,
+        &options.strings,
+    )
+    .with_sampling_threshold(
+        options
+            .graph_sampling_threshold
+            .unwrap_or(DEFAULT_GRAPH_SAMPLING_THRESHOLD),
+    )
+    .with_color_role(graph.color_role(options))
+    .with_palette(options.palette)
+    .with_ramp_shading(options.shows_ramp_shading(graph));
+    if graph == ReportGraph::AverageResponseTime {
+        chart = chart
+            .with_threshold(options.slow_request_threshold)
+            .with_axis_decimals(options.graph_axis_decimals);
+    }
+    chart
+}
+
+/// Export the [ECharts](https://echarts.apache.org) option object of a time-series graph
+/// as JSON, the same object the html report passes to `setOption`, so an app that
+/// already bundles ECharts can render the graph itself with `chart.setOption(option)`
+/// instead of running the report's inline scripts. The graph is styled by `options` as
+/// in the html report. Fails if the data can't be serialized.
+pub fn export_graph_option_json<T: Serialize>(
+    graph: ReportGraph,
+    data: &[(String, T)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> serde_json::Result<String> {
+    let chart = report_graph(graph, data, phases, options);
+    serde_json::to_string(&chart.option()?)
+}
+
+/// Render a time-series graph as PNG bytes without a browser, for pipelines that attach
+/// graphs to tickets or chat messages. The line, area, starting and stopping regions and
+/// SLO line are drawn as in the html report, styled by `options`, but there are no axis
+/// labels or legend.
+///
+/// Requires the `chart-png` feature, and returns `None` without it, or if the graph has
+/// no numeric data.
+pub fn export_graph_png<T: Serialize>(
+    graph: ReportGraph,
+    data: &[(String, T)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> Option<Vec<u8>> {
+    let chart = report_graph(graph, data, phases, options);
+    #[cfg(feature = "chart-png")]
+    {
+        chart.png()
+    }
+    #[cfg(not(feature = "chart-png"))]
+    {
+        let _ = chart;
+        None
+    }
+}
+
+/// Build the markup of a time-series graph without data, as configured by
+/// [`GooseReportOptions::empty_graphs`]: an empty string to skip the graph, or a
+/// placeholder. Returns `None` if the graph has data, or empty graphs are rendered.
+pub fn empty_graph_template<T>(
+    graph: ReportGraph,
+    data: &[T],
+    options: &GooseReportOptions,
+) -> Option<String> {
+    if !data.is_empty() {
+        return None;
+    }
+    match options.empty_graphs {
+        EmptyGraphs::Render => None,
+        EmptyGraphs::Skip => Some("".to_string()),
+        EmptyGraphs::Placeholder => Some(format!(
+            r#"<div class="graph graph-empty" id="{}">{}</div>"#,
+            graph.html_id(),
+            options.strings.no_data,
+        )),
+    }
+}
+
+/// Build an errors per second graph.
+pub fn graph_eps_template(
+    eps: &[(String, u32)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> String {
+    report_graph(ReportGraph::ErrorsPerSecond, eps, phases, options).generate_markup()
+}
+
+/// Build an average response time graph, with a horizontal "SLO" line at the
+/// [`GooseReportOptions::slow_request_threshold`] marking which response times are
+/// considered slow, and an optional shaded `confidence_band` between lower and upper bound
+/// series, such as the average plus and minus one standard deviation of each interval. An
+/// `envelope` of per-second minimum and maximum series shades the raw range of observed
+/// response times behind the average.
+pub fn graph_average_response_time_template(
+    response_times: &[(String, u32)],
+    phases: GraphPhases,
+    confidence_band: Option<ConfidenceBand>,
+    envelope: Option<ConfidenceBand>,
+    options: &GooseReportOptions,
+) -> String {
+    report_graph(
+        ReportGraph::AverageResponseTime,
+        response_times,
+        phases,
+        options,
+    )
+    .with_confidence_band(confidence_band)
+    .with_envelope(envelope)
+    .generate_markup()
+}
+
+/// Build a users per second graph.
+pub fn graph_users_per_second_template(
+    active_users: &[(String, usize)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> String {
+    report_graph(ReportGraph::ActiveUsers, active_users, phases, options).generate_markup()
+}
+
+/// Build a tasks per second graph.
+pub fn graph_tasks_per_second_template<T: Serialize>(
+    tps: &[(String, T)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> String {
+    report_graph(ReportGraph::TasksPerSecond, tps, phases, options).generate_markup()
+}
+
+/// Describes the load test a JSON export was generated from, so each exported file is
+/// self-contained.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportMetadata {
+    /// The title of the report.
+    pub title: String,
+    /// The host(s) that were load tested.
+    pub hosts: String,
+    /// The number of users.
+    pub users: String,
+    /// When the load test ran, for example `2021-11-21 21:20:30 - 2021-11-21 21:25:30`.
+    pub report_range: String,
+    /// When the export was generated, in RFC 3339 format.
+    pub generated: String,
+    /// The version of Goose that generated the export.
+    pub version: String,
+    /// When the load test started launching users, if known.
+    pub starting: Option<String>,
+    /// When all users were launched, if known.
+    pub started: Option<String>,
+    /// When the load test started stopping users, if known.
+    pub stopping: Option<String>,
+    /// When all users were stopped, if known.
+    pub stopped: Option<String>,
+}
+
+impl ReportMetadata {
+    /// Creates metadata for a report generated now by this version of Goose, with the
+    /// default title and no starting or stopping phase boundaries.
+    pub fn new(hosts: &str, users: &str, report_range: &str) -> ReportMetadata {
+        ReportMetadata {
+            title: "Goose Attack Report".to_string(),
+            hosts: hosts.to_string(),
+            users: users.to_string(),
+            report_range: report_range.to_string(),
+            generated: Local::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            starting: None,
+            started: None,
+            stopping: None,
+            stopped: None,
+        }
+    }
+
+    /// Sets the boundaries of the starting and stopping phases.
+    pub fn with_phases(
+        mut self,
+        starting: Option<DateTime<Local>>,
+        started: Option<DateTime<Local>>,
+        stopping: Option<DateTime<Local>>,
+        stopped: Option<DateTime<Local>>,
+    ) -> ReportMetadata {
+        let marker = |datetime: Option<DateTime<Local>>| {
+            datetime.map(|datetime| datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+        };
+        self.starting = marker(starting);
+        self.started = marker(started);
+        self.stopping = marker(stopping);
+        self.stopped = marker(stopped);
+        self
+    }
+}
+
+/// The time series displayed in the html report graphs. Each series is an array of
+/// `[timestamp, value]` pairs, in the same format as the graph data of the html report.
+#[derive(Debug, Serialize)]
+pub struct ReportSeries<'a> {
+    pub requests_per_second: &'a [(String, u32)],
+    pub errors_per_second: &'a [(String, u32)],
+    pub average_response_time: &'a [(String, u32)],
+    pub active_users: &'a [(String, usize)],
+    pub tasks_per_second: &'a [(String, usize)],
+}
+
+/// An error as listed in the errors table, exported to JSON.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReportError {
+    pub error: String,
+    pub occurrences: usize,
+    /// The kind of error, as returned by [`error_category`].
+    pub category: &'static str,
+}
+
+/// Classify an error as `client_error` or `server_error` for 4xx and 5xx responses,
+/// `http_status` for other unexpected status codes, `timeout`, `connection` for requests
+/// that couldn't be sent, or `other`.
+pub fn error_category(error: &str) -> &'static str {
+    // Goose records unexpected status codes as "{status_code}: {request_name}", where the
+    // status code is displayed with its reason, such as "503 Service Unavailable".
+    let status_code = error
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|status_code| status_code.parse::<u16>().ok());
+    match status_code {
+        Some(400..=499) => "client_error",
+        Some(500..=599) => "server_error",
+        Some(_) => "http_status",
+        None if error.to_lowercase().contains("timed out") => "timeout",
+        None if error.starts_with("error sending request") => "connection",
+        None => "other",
+    }
+}
+
+/// The errors of a load test, most frequent first.
+pub fn report_errors(errors: &metrics::GooseErrorMetrics) -> Vec<ReportError> {
+    let mut report_errors = errors
+        .values()
+        .map(|error| ReportError {
+            error: error.error.clone(),
+            occurrences: error.occurrences,
+            category: error_category(&error.error),
+        })
+        .collect::<Vec<_>>();
+    report_errors.sort_by_key(|error| std::cmp::Reverse(error.occurrences));
+    report_errors
+}
+
+/// A serializable report, as exported to JSON.
+#[derive(Debug, Serialize)]
+pub struct GooseReport<'a> {
+    pub metadata: ReportMetadata,
+    /// The outcome of the assertions, omitted if none were evaluated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<ReportVerdict>,
+    pub series: ReportSeries<'a>,
+    /// The errors, most frequent first.
+    pub errors: Vec<ReportError>,
+}
+
+/// Compute a fingerprint of the request, response time and error metrics, as 16
+/// hexadecimal digits, so CI can tell whether the metrics of two runs changed without
+/// diffing the reports.
+///
+/// Only the metric values are hashed: dates and times, the report's html and the order
+/// of the rows don't affect the fingerprint, so runs with identical metrics have the same
+/// fingerprint. The hash is computed with FNV-1a, which unlike the standard library's
+/// hashers is stable across Rust releases.
+pub fn metrics_fingerprint(
+    requests: &[RequestMetric],
+    responses: &[ResponseMetric],
+    errors: &[ReportError],
+) -> String {
+    // Serializing through a `Value` sorts the keys of maps such as the status code counts.
+    let rows = |metrics: Vec<serde_json::Value>| {
+        metrics
+            .iter()
+            .map(|metric| metric.to_string())
+            .sorted()
+            .join("\n")
+    };
+    let metrics = [
+        rows(requests.iter().map(|metric| json!(metric)).collect()),
+        rows(responses.iter().map(|metric| json!(metric)).collect()),
+        rows(errors.iter().map(|error| json!(error)).collect()),
+    ]
+    .join("\n\n");
+
+    let hash = metrics
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// Build the line displaying the metrics fingerprint at the bottom of the report.
+pub fn fingerprint_template(fingerprint: &str, strings: &ReportStrings) -> String {
+    format!(
+        r#"<p class="fingerprint"><small>{}: <code>{}</code></small></p>"#,
+        strings.metrics_fingerprint,
+        escape_html(fingerprint)
+    )
+}
+
+/// Export the time series displayed in the html report graphs as JSON, for charting with
+/// external tools and archival.
+///
+/// The exported [`GooseReport`] includes the [`ReportMetadata`] describing the load test,
+/// with the boundaries of the starting and stopping phases `null` if unknown. If a
+/// `verdict` is supplied, as returned by [`evaluate_assertions`], it's exported with the
+/// overall `passed` flag and an `assertions` array, so CI pipelines can read the
+/// pass/fail results without parsing the html report. The `errors` are exported as an
+/// array of `{error, occurrences, category}` objects, most frequent first, so tooling can
+/// alert on specific errors without scraping the errors table.
+#[allow(clippy::too_many_arguments)]
+pub fn export_timeseries_json(
+    rps: &[(String, u32)],
+    eps: &[(String, u32)],
+    response_times: &[(String, u32)],
+    active_users: &[(String, usize)],
+    tasks: &[(String, usize)],
+    metadata: ReportMetadata,
+    verdict: Option<ReportVerdict>,
+    errors: &metrics::GooseErrorMetrics,
+) -> String {
+    let report = GooseReport {
+        metadata,
+        verdict,
+        series: ReportSeries {
+            requests_per_second: rps,
+            errors_per_second: eps,
+            average_response_time: response_times,
+            active_users,
+            tasks_per_second: tasks,
+        },
+        errors: report_errors(errors),
+    };
+    serde_json::to_string(&report).expect("unexpected serde failure")
+}
+
+/// Build an overview graph with requests per second, errors per second, average response
+/// times and active users stacked in four linked subplots. The subplots share a single
+/// time axis, so hovering or zooming one applies to all of them. Each subplot is labeled,
+/// colored and shaded as its own graph, styled by `options`.
+pub fn graph_overview_template(
+    rps: &[(String, u32)],
+    eps: &[(String, u32)],
+    response_times: &[(String, u32)],
+    users: &[(String, usize)],
+    phases: GraphPhases,
+    options: &GooseReportOptions,
+) -> String {
+    let rps = rps
+        .iter()
+        .map(|(time, count)| (time, options.rate_unit.scale(*count as f32) as u32))
+        .collect::<Vec<_>>();
+    let subplots = [
+        (ReportGraph::RequestsPerSecond, json!(rps)),
+        (ReportGraph::ErrorsPerSecond, json!(eps)),
+        (ReportGraph::AverageResponseTime, json!(response_times)),
+        (ReportGraph::ActiveUsers, json!(users)),
+    ];
+
+    let mut colors = Vec::new();
+    let mut grids = Vec::new();
+    let mut x_axes = Vec::new();
+    let mut y_axes = Vec::new();
+    let mut series = Vec::new();
+    for (index, (graph, data)) in subplots.iter().enumerate() {
+        let (line_color, area_color) = graph.color_role(options).colors(options.palette);
+        colors.push(format!("'{}'", line_color));
+        grids.push(format!(
+            "{{ left: 70, right: 30, top: {}, height: 150 }}",
+            30 + index * 180
+        ));
+        x_axes.push(format!(
+            "{{ type: 'time', gridIndex: {index}, axisLabel: {{ show: {show_labels} }} }}",
+            index = index,
+            show_labels = index == subplots.len() - 1,
+        ));
+        y_axes.push(format!(
+            "{{ type: 'value', gridIndex: {index}, name: {y_axis_label}, nameLocation: 'center', nameRotate: 90, nameGap: 50 }}",
+            index = index,
+            y_axis_label = json!(graph.y_axis_label(options)),
+        ));
+        series.push(format!(
+            "{{ type: 'line', xAxisIndex: {index}, yAxisIndex: {index}, symbol: 'none', lineStyle: {{ color: '{line_color}' }}, areaStyle: {{ color: '{area_color}' }}, markArea: {mark_area}, data: {data} }}",
+            index = index,
+            line_color = line_color,
+            area_color = area_color,
+            mark_area = phases.mark_area(options.shows_ramp_shading(*graph), &options.strings),
+            data = data,
+        ));
+    }
+    let axis_indexes = (0..subplots.len()).join(", ");
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-overview" style="width: 1000px; height:800px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-overview');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        color: [{colors}],
+                        tooltip: {{ trigger: 'axis' }},
+                        axisPointer: {{ link: [{{ xAxisIndex: 'all' }}] }},
+                        toolbox: {{
+                            feature: {{
+                                dataZoom: {{ yAxisIndex: 'none' }},
+                                restore: {{}},
+                                saveAsImage: {{}}
+                            }}
+                        }},
+                        dataZoom: [
+                            {{ type: 'inside', xAxisIndex: [{axis_indexes}], start: 0, end: 100 }},
+                            {{ xAxisIndex: [{axis_indexes}], start: 0, end: 100, bottom: 10 }}
+                        ],
+                        grid: [
+                            {grids}
+                        ],
+                        xAxis: [
+                            {x_axes}
+                        ],
+                        yAxis: [
+                            {y_axes}
+                        ],
+                        series: [
+                            {series}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        colors = colors.join(", "),
+        axis_indexes = axis_indexes,
+        grids = grids.join(",\n                            "),
+        x_axes = x_axes.join(",\n                            "),
+        y_axes = y_axes.join(",\n                            "),
+        series = series.join(",\n                            "),
+    )
+}
+
+/// Calculate the five-number summary of a response time histogram: the minimum, 25th
+/// percentile, median, 75th percentile and maximum, in milliseconds.
+pub fn five_number_summary(
+    response_times: &BTreeMap<usize, usize>,
+    total_request_count: usize,
+    response_time_minimum: usize,
+    response_time_maximum: usize,
+) -> [usize; 5] {
+    let percentile = |percent| {
+        metrics::response_time_percentile(
+            response_times,
+            total_request_count,
+            response_time_minimum,
+            response_time_maximum,
+            percent,
+        )
+    };
+    [
+        response_time_minimum,
+        percentile(0.25),
+        percentile(0.5),
+        percentile(0.75),
+        response_time_maximum,
+    ]
+}
+
+/// The totals of one endpoint of a multi-series graph, displayed in its tooltip.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SeriesSummary {
+    /// The endpoint, for example `GET /about`, also the name of its series.
+    pub name: String,
+    /// The total number of requests to the endpoint.
+    pub requests: usize,
+    /// The total number of failed requests to the endpoint.
+    pub failures: usize,
+}
+
+/// Build a graph of requests per second with one line per endpoint. Each element of
+/// `series` is an endpoint's [`SeriesSummary`] and its requests per second. Hovering the
+/// graph displays each endpoint's instantaneous requests per second alongside its total
+/// requests and failure rate.
+pub fn graph_endpoint_rps_template(
+    series: &[(SeriesSummary, Vec<(String, u32)>)],
+    strings: &ReportStrings,
+) -> String {
+    let totals = series
+        .iter()
+        .map(|(summary, _)| {
+            let failure_rate = if summary.requests > 0 {
+                summary.failures as f64 * 100.0 / summary.requests as f64
+            } else {
+                0.0
+            };
+            (
+                summary.name.clone(),
+                json!(strings
+                    .endpoint_totals
+                    .replace("{requests}", &metrics::format_number(summary.requests))
+                    .replace("{failure_rate}", &format!("{:.2}", failure_rate))),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let series = series
+        .iter()
+        .map(|(summary, data)| {
+            json!({
+                "name": summary.name,
+                "type": "line",
+                "symbol": "none",
+                "data": data,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-endpoint-rps" style="width: 1000px; height:500px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-endpoint-rps');
+                    var myChart = echarts.init(chartDom);
+                    var totals = {totals};
+
+                    myChart.setOption({{
+                        legend: {{ type: 'scroll', top: 0 }},
+                        tooltip: {{
+                            trigger: 'axis',
+                            formatter: function (params) {{
+                                return params[0].axisValueLabel + params.map(function (param) {{
+                                    return '<br>' + param.marker + param.seriesName + ': ' + param.value[1]
+                                        + ' (' + totals[param.seriesName] + ')';
+                                }}).join('');
+                            }}
+                        }},
+                        xAxis: {{ type: 'time' }},
+                        yAxis: {{
+                            name: {requests_axis},
+                            nameLocation: 'center',
+                            nameRotate: 90,
+                            nameGap: 45,
+                            type: 'value'
+                        }},
+                        series: {series}
+                    }});
+                </script>
+            </div>"#,
+        totals = json!(totals),
+        series = json!(series),
+        requests_axis = json!(strings.requests_axis),
+    )
+}
+
+/// Build a box plot of response times with one box per endpoint. Each element of `data`
+/// is an endpoint name and its [`five_number_summary`].
+pub fn graph_response_boxplot_template(
+    data: &[(String, [usize; 5])],
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    let (names, summaries): (Vec<_>, Vec<_>) = data.iter().cloned().unzip();
+    let (line_color, area_color) = ColorRole::Success.colors(palette);
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-response-boxplot" style="width: 1000px; height:{height}px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-response-boxplot');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        tooltip: {{ trigger: 'item' }},
+                        grid: {{ left: 200, right: 30 }},
+                        xAxis: {{
+                            type: 'value',
+                            name: {response_time_axis},
+                            nameLocation: 'center',
+                            nameGap: 30
+                        }},
+                        yAxis: {{ type: 'category', data: {names} }},
+                        series: [
+                            {{
+                                name: {response_time},
+                                type: 'boxplot',
+                                itemStyle: {{ color: '{area_color}', borderColor: '{line_color}' }},
+                                data: {summaries}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        height = 100 + 40 * data.len(),
+        names = json!(names),
+        summaries = json!(summaries),
+        line_color = line_color,
+        area_color = area_color,
+        response_time_axis = json!(strings.response_time_axis),
+        response_time = json!(strings.response_time),
+    )
+}
+
+/// The response time scatter plot displays at most this many samples by default.
+pub const DEFAULT_SCATTER_POINTS: usize = 10_000;
+
+/// Downsample `samples` to at most `max_points`, keeping the slowest sample of each run of
+/// consecutive samples so spikes remain visible.
+pub fn downsample_scatter(samples: &[(String, u32)], max_points: usize) -> Vec<(String, u32)> {
+    if samples.len() <= max_points || max_points == 0 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(samples.len().div_ceil(max_points))
+        .filter_map(|chunk| chunk.iter().max_by_key(|(_, response_time)| *response_time))
+        .cloned()
+        .collect()
+}
+
+/// Build a scatter plot of individual response times over time, revealing patterns such
+/// as periodic spikes that averages hide. Each element of `samples` is the formatted time
+/// of a request and its response time in milliseconds.
+///
+/// Goose only retains response time histograms, so the samples must be collected by the
+/// caller, for example from the request log. Large sample sets are downsampled with
+/// [`downsample_scatter`] to `max_points`, which defaults to [`DEFAULT_SCATTER_POINTS`].
+pub fn graph_response_scatter_template(
+    samples: &[(String, u32)],
+    max_points: Option<usize>,
+    color_role: ColorRole,
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    let samples = downsample_scatter(samples, max_points.unwrap_or(DEFAULT_SCATTER_POINTS));
+    let (line_color, _) = color_role.colors(palette);
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-response-scatter" style="width: 1000px; height:500px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-response-scatter');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        tooltip: {{ trigger: 'item' }},
+                        toolbox: {{
+                            feature: {{
+                                dataZoom: {{}},
+                                restore: {{}},
+                                saveAsImage: {{}}
+                            }}
+                        }},
+                        dataZoom: [{{ type: 'inside' }}, {{}}],
+                        xAxis: {{ type: 'time' }},
+                        yAxis: {{
+                            name: {response_time_axis},
+                            nameLocation: 'center',
+                            nameRotate: 90,
+                            nameGap: 45,
+                            type: 'value'
+                        }},
+                        series: [
+                            {{
+                                name: {response_time},
+                                type: 'scatter',
+                                large: true,
+                                symbolSize: 3,
+                                itemStyle: {{ color: '{line_color}', opacity: 0.5 }},
+                                data: {samples}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        samples = json!(samples),
+        line_color = line_color,
+        response_time_axis = json!(strings.response_time_axis),
+        response_time = json!(strings.response_time),
+    )
+}
+
+/// Endpoints with less than this percentage of all requests are combined into a single
+/// "Other" rectangle of the requests treemap by default.
+pub const DEFAULT_TREEMAP_OTHER_PERCENTAGE: f64 = 1.0;
+
+/// Combine the endpoints with less than `other_percentage` percent of all requests into
+/// a single trailing entry named `other`, keeping the other endpoints in order. Each
+/// element of `data` is an endpoint name and its number of requests.
+pub fn treemap_data(
+    data: &[(String, usize)],
+    other_percentage: f64,
+    other: &str,
+) -> Vec<(String, usize)> {
+    let total: usize = data.iter().map(|(_, requests)| requests).sum();
+    let (mut large, small): (Vec<_>, Vec<_>) = data
+        .iter()
+        .cloned()
+        .partition(|(_, requests)| *requests as f64 * 100.0 >= other_percentage * total as f64);
+    let requests: usize = small.iter().map(|(_, requests)| requests).sum();
+    if requests > 0 {
+        large.push((other.to_string(), requests));
+    }
+    large
+}
+
+/// Build a treemap of the requests to each endpoint, with each endpoint's rectangle sized
+/// by its number of requests. Each element of `data` is an endpoint name and its number
+/// of requests. Endpoints with less than `other_percentage` percent of all requests are
+/// combined into a rectangle labeled [`ReportStrings::other`]; `other_percentage` defaults to
+/// [`DEFAULT_TREEMAP_OTHER_PERCENTAGE`].
+pub fn graph_requests_treemap_template(
+    data: &[(String, usize)],
+    other_percentage: Option<f64>,
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    let data = treemap_data(
+        data,
+        other_percentage.unwrap_or(DEFAULT_TREEMAP_OTHER_PERCENTAGE),
+        &strings.other,
+    )
+    .into_iter()
+    .map(|(name, requests)| json!({ "name": name, "value": requests }))
+    .collect::<Vec<_>>();
+    let (line_color, area_color) = ColorRole::Success.colors(palette);
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-requests-treemap" style="width: 1000px; height:400px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-requests-treemap');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        tooltip: {{ formatter: '{{b}}: {{c}}' }},
+                        series: [
+                            {{
+                                name: {request_count},
+                                type: 'treemap',
+                                roam: false,
+                                nodeClick: false,
+                                breadcrumb: {{ show: false }},
+                                itemStyle: {{ color: '{area_color}', borderColor: '{line_color}', borderWidth: 1, gapWidth: 1 }},
+                                data: {data}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        data = json!(data),
+        line_color = line_color,
+        area_color = area_color,
+        request_count = json!(strings.request_count),
+    )
+}
+
+/// Calculate the cumulative distribution of a response time histogram: each response
+/// time in milliseconds, paired with the percentage of requests that completed within it.
+pub fn response_time_cdf(response_times: &BTreeMap<usize, usize>) -> Vec<(usize, f64)> {
+    let total: usize = response_times.values().sum();
+    let mut cumulative = 0;
+    response_times
+        .iter()
+        .map(|(response_time, count)| {
+            cumulative += count;
+            (*response_time, cumulative as f64 * 100.0 / total as f64)
+        })
+        .collect()
+}
+
+/// Build a graph of the cumulative distribution of response times, showing the
+/// percentage of requests that completed within any response time, as calculated by
+/// [`response_time_cdf`].
+pub fn graph_response_time_cdf_template(
+    response_times: &BTreeMap<usize, usize>,
+    color_role: ColorRole,
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    let data = response_time_cdf(response_times)
+        .into_iter()
+        .map(|(response_time, percent)| json!([response_time, format!("{:.2}", percent)]))
+        .collect::<Vec<_>>();
+    let (line_color, _) = color_role.colors(palette);
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-response-time-cdf" style="width: 1000px; height:500px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-response-time-cdf');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        color: ['{line_color}'],
+                        tooltip: {{ trigger: 'axis' }},
+                        xAxis: {{
+                            type: 'value',
+                            name: {response_time_axis},
+                            nameLocation: 'center',
+                            nameGap: 30
+                        }},
+                        yAxis: {{
+                            type: 'value',
+                            name: {requests_percentage_axis},
+                            min: 0,
+                            max: 100
+                        }},
+                        series: [
+                            {{
+                                name: {requests_within},
+                                type: 'line',
+                                step: 'end',
+                                symbol: 'none',
+                                data: {data}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        data = json!(data),
+        response_time_axis = json!(strings.response_time_axis),
+        requests_percentage_axis = json!(strings.requests_percentage_axis),
+        requests_within = json!(strings.requests_within),
+        line_color = line_color,
+    )
+}
+
+/// Default upper bounds, in milliseconds, of the latency buckets in the response time
+/// heatmap.
+pub const HEATMAP_LATENCY_BUCKETS: [usize; 9] = [10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Upper bounds, in milliseconds, of the finest latency buckets added by
+/// [`fine_latency_buckets`], labeled `<1 ms` and `1-2 ms`.
+pub const FINE_LATENCY_BUCKETS: [usize; 2] = [0, 2];
+
+/// Add the [`FINE_LATENCY_BUCKETS`] before the ascending upper bounds of
+/// `latency_buckets`, so the response times of fast services don't all crowd into the
+/// first bucket of the response time heatmap. Goose records response times in whole
+/// milliseconds, so responses faster than 1 ms are recorded as 0 ms.
+pub fn fine_latency_buckets(latency_buckets: &[usize]) -> Vec<usize> {
+    FINE_LATENCY_BUCKETS
+        .iter()
+        .chain(
+            latency_buckets
+                .iter()
+                .filter(|upper| **upper > FINE_LATENCY_BUCKETS[FINE_LATENCY_BUCKETS.len() - 1]),
+        )
+        .copied()
+        .collect()
+}
+
+/// Build a response time heatmap, with time on the x axis, latency buckets on the y axis
+/// and cells colored by the number of requests.
+///
+/// Each element of `data` is the formatted time of an interval and that interval's
+/// response time histogram, mapping response times in milliseconds to request counts.
+/// Response times are grouped into buckets using the ascending upper bounds in
+/// `latency_buckets` (for example [`HEATMAP_LATENCY_BUCKETS`], or
+/// [`fine_latency_buckets`] for fast services), with slower responses grouped into a final
+/// overflow bucket.
+pub fn graph_response_time_heatmap_template(
+    data: &[(String, BTreeMap<usize, usize>)],
+    latency_buckets: &[usize],
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    let mut labels = Vec::new();
+    let mut lower = 0;
+    for upper in latency_buckets {
+        labels.push(if *upper == 0 {
+            "<1 ms".to_string()
+        } else if lower == *upper {
+            format!("{} ms", upper)
+        } else {
+            format!("{}-{} ms", lower, upper)
+        });
+        lower = upper + 1;
+    }
+    labels.push(format!("{}+ ms", lower));
+
+    let mut cells = Vec::new();
+    let mut max_count = 0;
+    for (x, (_, histogram)) in data.iter().enumerate() {
+        let mut counts = vec![0; labels.len()];
+        for (response_time, count) in histogram {
+            let y = latency_buckets
+                .iter()
+                .position(|upper| response_time <= upper)
+                .unwrap_or(latency_buckets.len());
+            counts[y] += count;
+        }
+        for (y, count) in counts.into_iter().enumerate() {
+            if count > 0 {
+                max_count = max_count.max(count);
+                cells.push((x, y, count));
+            }
+        }
+    }
+
+    format!(
+        r#"<div class="graph">
+                <div id="graph-response-time-heatmap" style="width: 1000px; height:500px; background: white;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('graph-response-time-heatmap');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        tooltip: {{ position: 'top' }},
+                        toolbox: {{
+                            feature: {{
+                                saveAsImage: {{}}
+                            }}
+                        }},
+                        xAxis: {{ type: 'category', data: {times} }},
+                        yAxis: {{
+                            name: {response_time},
+                            nameLocation: 'center',
+                            nameRotate: 90,
+                            nameGap: 70,
+                            type: 'category',
+                            data: {labels}
+                        }},
+                        visualMap: {{
+                            min: 0,
+                            max: {max_count},
+                            calculable: true,
+                            orient: 'horizontal',
+                            left: 'center',
+                            bottom: 0,
+                            inRange: {{ color: ['{low_color}', '{high_color}'] }}
+                        }},
+                        series: [
+                            {{
+                                type: 'heatmap',
+                                data: {cells}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        times = json!(data.iter().map(|(time, _)| time).collect::<Vec<_>>()),
+        labels = json!(labels),
+        max_count = max_count,
+        cells = json!(cells),
+        response_time = json!(strings.response_time),
+        low_color = palette.color("#e6f2ed"),
+        high_color = palette.color("#2c664f"),
+    )
+}
+
+/// Evaluate assertions against the overall request metrics of a load test.
+///
+/// This can be called with the [`GooseMetrics`](../metrics/struct.GooseMetrics.html)
+/// returned by [`GooseAttack::execute`](../struct.GooseAttack.html#method.execute) to
+/// set an exit code from the same verdict displayed in the html report.
+pub fn evaluate_assertions(
+    metrics: &metrics::GooseMetrics,
+    assertions: &[ReportAssertion],
+) -> ReportVerdict {
+    let mut total_count = 0;
+    let mut fail_count = 0;
+    let mut response_times = metrics::GooseRequestMetricTimingData::new(None);
+    for request in metrics.requests.values() {
+        total_count += request.success_count + request.fail_count;
+        fail_count += request.fail_count;
+        response_times.merge(&request.raw_data);
+    }
+
+    let results = assertions
+        .iter()
+        .map(|assertion| match assertion {
+            ReportAssertion::MaxResponseTimePercentile(percentile, max) => {
+                let actual = metrics::response_time_percentile(
+                    &response_times.times,
+                    response_times.counter,
+                    response_times.minimum_time,
+                    response_times.maximum_time,
+                    *percentile,
+                );
+                AssertionResult {
+                    description: format!("p{} < {} ms", percentile * 100.0, max),
+                    threshold: *max as f64,
+                    value: actual as f64,
+                    actual: format!("{} ms", actual),
+                    passed: actual < *max,
+                }
+            }
+            ReportAssertion::MaxFailurePercentage(max) => {
+                let actual = if total_count > 0 {
+                    fail_count as f32 / total_count as f32 * 100.0
+                } else {
+                    0.0
+                };
+                AssertionResult {
+                    description: format!("failures < {}%", max),
+                    threshold: *max as f64,
+                    value: actual as f64,
+                    actual: format!("{:.2}%", actual),
+                    passed: actual < *max,
+                }
+            }
+            ReportAssertion::MinRequestsPerSecond(min) => {
+                let (actual, _) =
+                    metrics::per_second_calculations(metrics.duration, total_count, fail_count);
+                AssertionResult {
+                    description: format!("RPS > {}", min),
+                    threshold: *min as f64,
+                    value: actual as f64,
+                    actual: format_rate(actual),
+                    passed: actual > *min,
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ReportVerdict {
+        passed: results.iter().all(|result| result.passed),
+        results,
+    }
+}
+
+/// The throttle is considered the binding constraint when the achieved requests per
+/// second are within this fraction of the configured limit.
+const THROTTLE_BINDING_RATIO: f32 = 0.95;
+
+/// A summary of how the throttle limited a load test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThrottleSummary {
+    /// The configured maximum requests per second.
+    pub throttle_requests: usize,
+    /// The achieved requests per second over the whole load test.
+    pub requests_per_second: f32,
+    /// The total time requests waited on the throttle, if tracked.
+    pub throttled_time: Option<std::time::Duration>,
+}
+
+impl ThrottleSummary {
+    /// Whether the throttle, rather than the server, capped the achieved requests per
+    /// second.
+    pub fn is_binding(&self) -> bool {
+        self.requests_per_second >= self.throttle_requests as f32 * THROTTLE_BINDING_RATIO
+    }
+}
+
+/// Build a section explaining whether the throttle capped the achieved requests per
+/// second, so a flat requests per second graph isn't mistaken for the server's limit.
+/// Returns an empty string if the load test wasn't throttled.
+pub fn throttle_template(throttle: Option<&ThrottleSummary>, strings: &ReportStrings) -> String {
+    let throttle = match throttle {
+        Some(throttle) => throttle,
+        None => return "".to_string(),
+    };
+    let constraint = if throttle.is_binding() {
+        &strings.throttle_binding
+    } else {
+        &strings.throttle_not_binding
+    };
+    let throttled_time = match throttle.throttled_time {
+        Some(throttled_time) => format!(
+            "\n            <p>{}</p>",
+            strings
+                .throttle_waited
+                .replace("{seconds}", &format!("{:.1}", throttled_time.as_secs_f64()))
+        ),
+        None => "".to_string(),
+    };
+
+    format!(
+        r#"<div class="throttle">
+            <h2>{title}</h2>
+            <p>{summary}</p>
+            <p>{constraint}</p>{throttled_time}
+        </div>"#,
+        title = strings.throttle,
+        summary = strings
+            .throttle_summary
+            .replace(
+                "{limit}",
+                &metrics::format_number(throttle.throttle_requests)
+            )
+            .replace("{achieved}", &format_rate(throttle.requests_per_second)),
+        constraint = constraint,
+        throttled_time = throttled_time,
+    )
+}
+
+/// The time spent recording metrics during a load test, to judge whether collecting the
+/// metrics skewed the results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricsOverhead {
+    /// The total time spent recording metrics.
+    pub recording_time: std::time::Duration,
+    /// The duration of the load test.
+    pub duration: std::time::Duration,
+}
+
+impl MetricsOverhead {
+    /// The time spent recording metrics as a percentage of the load test duration.
+    pub fn percentage(&self) -> f64 {
+        if self.duration.as_secs_f64() > 0.0 {
+            self.recording_time.as_secs_f64() / self.duration.as_secs_f64() * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Build a collapsed diagnostics section with the metrics collection overhead.
+///
+/// Goose doesn't time its own metrics collection, so the overhead must be measured by
+/// the caller. Returns an empty string without overhead data.
+pub fn diagnostics_template(overhead: Option<&MetricsOverhead>, strings: &ReportStrings) -> String {
+    let overhead = match overhead {
+        Some(overhead) => overhead,
+        None => return "".to_string(),
+    };
+    format!(
+        r#"<details class="diagnostics">
+            <summary>{title}</summary>
+            <p>{label}: <span>{detail}</span></p>
+        </details>"#,
+        title = strings.diagnostics,
+        label = strings.metrics_overhead,
+        detail = strings
+            .metrics_overhead_detail
+            .replace(
+                "{seconds}",
+                &format!("{:.2}", overhead.recording_time.as_secs_f64())
+            )
+            .replace("{percentage}", &format!("{:.2}", overhead.percentage())),
+    )
+}
+
+/// Build the PASS/FAIL verdict displayed at the top of the html report.
+pub fn verdict_template(verdict: &ReportVerdict, strings: &ReportStrings) -> String {
+    let (class, title) = if verdict.passed {
+        ("pass", &strings.pass)
+    } else {
+        ("fail", &strings.fail)
+    };
+    let rows = verdict
+        .results
+        .iter()
+        .map(|result| {
+            format!(
+                r#"<tr class="{class}">
+                    <td>{description}</td>
+                    <td>{actual}</td>
+                    <td>{outcome}</td>
+                </tr>"#,
+                class = if result.passed { "pass" } else { "fail" },
+                description = result.description,
+                actual = result.actual,
+                outcome = if result.passed {
+                    &strings.met
+                } else {
+                    &strings.not_met
+                },
+            )
+        })
+        .join("\n");
+
+    format!(
+        r#"<div class="verdict {class}">
+            <h2>{title}</h2>
+            <table>
+                <thead>
+                    <tr>
+                        <th>{assertion}</th>
+                        <th>{actual}</th>
+                        <th>{result}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows}
+                </tbody>
+            </table>
+        </div>"#,
+        class = class,
+        title = title,
+        assertion = strings.assertion,
+        actual = strings.actual,
+        result = strings.result,
+        rows = rows,
+    )
+}
+
+/// Returns true if no requests, tasks or errors were recorded, in which case the html
+/// report would be nothing more than empty tables and graphs.
+pub fn is_empty_report(
+    request_metrics: &[RequestMetric],
+    task_metrics: &[TaskMetric],
+    errors: &metrics::GooseErrorMetrics,
+) -> bool {
+    request_metrics
+        .iter()
+        .all(|metric| metric.number_of_requests == 0)
+        && task_metrics
+            .iter()
+            .all(|metric| metric.number_of_requests == 0)
+        && errors.is_empty()
+}
+
+/// Returns true if tasks ran but no requests were recorded, for example when transactions
+/// are timed with custom logic rather than Goose's built-in client, in which case the
+/// request, response time and status code tables would be empty.
+pub fn is_tasks_only_report(
+    request_metrics: &[RequestMetric],
+    task_metrics: &[TaskMetric],
+) -> bool {
+    request_metrics
+        .iter()
+        .all(|metric| metric.number_of_requests == 0)
+        && task_metrics
+            .iter()
+            .any(|metric| metric.number_of_requests > 0)
+}
+
+/// Build the html report when only task metrics were recorded, omitting the empty
+/// request, response time and status code sections but keeping the task and user
+/// sections.
+pub fn build_tasks_only_report<'a>(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    templates: GooseReportTemplates<'a>,
+    options: &GooseReportOptions,
+) -> String {
+    let options = GooseReportOptions {
+        sections: ReportSections {
+            show_requests: false,
+            show_responses: false,
+            show_status_codes: false,
+            ..options.sections.clone()
+        },
+        ..options.clone()
+    };
+    build_report(users, report_range, hosts, templates, &options)
+}
+
+/// Build a header line with a badge showing whether the report is final, or was built
+/// while the load test is still running, in which case `updated` is when it was built.
+/// A load test without a `stopped` timestamp is still running.
+pub fn freshness_badge(
+    stopped: Option<DateTime<Local>>,
+    updated: DateTime<Local>,
+    strings: &ReportStrings,
+) -> String {
+    match stopped {
+        Some(_) => format!(
+            r#"<p><span class="badge final">{}</span></p>"#,
+            strings.final_report
+        ),
+        None => format!(
+            r#"<p><span class="badge live">{}</span></p>"#,
+            strings
+                .live_report
+                .replace("{time}", &updated.format("%H:%M:%S").to_string())
+        ),
+    }
+}
+
+/// Build a header line comparing the `target` number of users to the peak of the
+/// per-second active user counts, such as "80/100 users (shortfall)". A shortfall
+/// explains a lower than expected request rate.
+pub fn achieved_users_row(
+    target: usize,
+    users_per_second: &[usize],
+    locale: &LocaleConfig,
+    strings: &ReportStrings,
+) -> String {
+    let peak = users_per_second.iter().copied().max().unwrap_or(0);
+    if peak < target {
+        format!(
+            r#"<p>{}: <span class="shortfall">{}/{} {} ({})</span></p>"#,
+            strings.achieved_users,
+            locale.format_number(peak),
+            locale.format_number(target),
+            strings.users_unit,
+            strings.shortfall,
+        )
+    } else {
+        format!(
+            "<p>{}: <span>{}/{} {}</span></p>",
+            strings.achieved_users,
+            locale.format_number(peak),
+            locale.format_number(target),
+            strings.users_unit,
+        )
+    }
+}
+
+/// Build a line of the report header describing when a phase of the load test, such as
+/// "Running", started and stopped, and how long it lasted.
+pub fn report_range_row(
+    label: &str,
+    start: &DateTime<Local>,
+    end: &DateTime<Local>,
+    (hours, minutes, seconds): (i64, i64, i64),
+    locale: &LocaleConfig,
+    strings: &ReportStrings,
+) -> String {
+    format!(
+        "<p>{}: <span>{} - {} ({}: {:02}:{:02}:{:02})</span></p>",
+        label,
+        locale.format_datetime(start),
+        locale.format_datetime(end),
+        strings.duration,
+        hours,
+        minutes,
+        seconds,
+    )
+}
+
+/// Build the html report.
+pub fn build_report<'a>(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    templates: GooseReportTemplates<'a>,
+    options: &GooseReportOptions,
+) -> String {
+    let body = report_body(templates, options);
+    report_page(users, report_range, hosts, &body, options)
+}
+
+/// Build the html report as a `data:text/html;base64,...` URI, for emailing or embedding
+/// the report as a link without hosting a file.
+///
+/// The report must be self-contained, so the graphs are only included with ECharts
+/// inlined by [`GooseReportOptions::echarts_script`]. Without it the graphs are omitted, as
+/// they would load ECharts from a CDN.
+pub fn build_report_data_uri<'a>(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    templates: GooseReportTemplates<'a>,
+    options: &GooseReportOptions,
+) -> String {
+    let options = GooseReportOptions {
+        sections: ReportSections {
+            show_graphs: options.sections.show_graphs && options.echarts_script.is_some(),
+            ..options.sections.clone()
+        },
+        ..options.clone()
+    };
+    let report = build_report(users, report_range, hosts, templates, &options);
+    format!("data:text/html;base64,{}", base64_encode(report.as_bytes()))
+}
+
+/// Encode bytes as standard, padded base64.
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |triple, (index, byte)| {
+                triple | (*byte as u32) << (16 - 8 * index)
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// A labeled phase of a load test, such as a warm-up, baseline or spike, reported in
+/// its own section of a phased html report.
+pub struct ReportPhase<'a> {
+    /// The title of the phase's section.
+    pub label: &'a str,
+    /// The tables and graphs built from the metrics collected during this phase.
+    pub templates: GooseReportTemplates<'a>,
+}
+
+/// Build an html report with a titled section of tables and graphs for each phase,
+/// followed by an overall section.
+///
+/// Goose doesn't tag metrics by phase, so each phase's templates must be built from the
+/// metrics collected while it ran, for example by running each phase as its own load test.
+pub fn build_phased_report<'a>(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    phases: Vec<ReportPhase<'a>>,
+    overall: GooseReportTemplates<'a>,
+    options: &GooseReportOptions,
+) -> String {
+    let mut body = Vec::new();
+    for (index, phase) in phases.into_iter().enumerate() {
+        // Graph ids must be unique within the page.
+        let phase_body = report_body(phase.templates, options)
+            .replace(r#"id="graph-"#, &format!(r#"id="phase-{}-graph-"#, index))
+            .replace(
+                "getElementById('graph-",
+                &format!("getElementById('phase-{}-graph-", index),
+            );
+        body.push(format!(
+            r#"<div class="phase">
+            <h2 class="phase-title">{label}</h2>
+
+            {phase_body}
+        </div>"#,
+            label = phase.label,
+            phase_body = phase_body,
+        ));
+    }
+    body.push(format!(
+        r#"<div class="phase">
+            <h2 class="phase-title">{label}</h2>
+
+            {overall_body}
+        </div>"#,
+        label = options.strings.overall,
+        overall_body = report_body(overall, options),
+    ));
+
+    report_page(
+        users,
+        report_range,
+        hosts,
+        &body.join("\n\n        "),
+        options,
+    )
+}
+
+/// The metrics of the requests made by one scenario of a load test, for
+/// [`build_scenario_reports`].
+#[derive(Clone, Debug)]
+pub struct ScenarioMetrics {
+    /// The name of the scenario.
+    pub name: String,
+    /// The request metrics of the requests made by the scenario.
+    pub requests: Vec<RequestMetric>,
+    /// The response time metrics of the requests made by the scenario.
+    pub responses: Vec<ResponseMetric>,
+}
+
+/// The file name of the index page built by [`build_scenario_reports`].
+pub const SCENARIO_INDEX_FILE: &str = "index.html";
+
+/// Build an html report for each scenario of a load test, and an index page linking to
+/// them, instead of a single report that is unwieldy for large load tests.
+///
+/// Returns the file name and html of each page, starting with the index page,
+/// [`SCENARIO_INDEX_FILE`]. The index links to the scenario reports by file name, so all
+/// pages must be written to the same directory. Goose doesn't record which task set made
+/// each request, so the metrics must be grouped by scenario by the caller.
+pub fn build_scenario_reports(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    scenarios: &[ScenarioMetrics],
+    options: &GooseReportOptions,
+) -> Vec<(String, String)> {
+    let mut reports = Vec::new();
+    let mut links = Vec::new();
+    let mut file_names = HashSet::new();
+    for scenario in scenarios {
+        let file_name = scenario_file_name(&scenario.name, &mut file_names);
+
+        let mut requests = scenario.requests.clone();
+        let aggregate = scenario
+            .requests
+            .iter()
+            .cloned()
+            .reduce(|mut aggregate, metric| {
+                add_request_metric(&mut aggregate, metric);
+                aggregate
+            })
+            .map(|aggregate| RequestMetric {
+                method: "".to_string(),
+                name: options.strings.aggregated.clone(),
+                ..aggregate
+            });
+        let (number_of_requests, number_of_failures) =
+            aggregate.as_ref().map_or((0, 0), |aggregate| {
+                (aggregate.number_of_requests, aggregate.number_of_failures)
+            });
+        requests.extend(aggregate);
+
+        let raw_requests_template = requests
+            .into_iter()
+            .map(|metric| raw_request_metrics_row(metric, options))
+            .join("\n");
+        let raw_responses_template = scenario
+            .responses
+            .iter()
+            .cloned()
+            .map(|metric| response_metrics_row(metric, options))
+            .join("\n");
+        let raw_responses_footer_template = response_metrics_footer(&scenario.responses, options);
+        let scenario_options = GooseReportOptions {
+            strings: ReportStrings {
+                title: format!("{}: {}", options.strings.title, escape_html(&scenario.name)),
+                ..options.strings.clone()
+            },
+            ..options.clone()
+        };
+        let report = build_report(
+            users,
+            report_range,
+            hosts,
+            GooseReportTemplates {
+                raw_requests_template: &raw_requests_template,
+                raw_responses_template: &raw_responses_template,
+                raw_responses_footer_template: &raw_responses_footer_template,
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: "",
+                status_codes_template: "",
+                errors_template: "",
+                graph_rps_template: "",
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: "",
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: "",
+            },
+            &scenario_options,
+        );
+
+        links.push(format!(
+            r#"<li><a href="{file_name}">{name}</a>: {requests} requests, {failures} failed</li>"#,
+            file_name = escape_html(&file_name),
+            name = escape_html(&scenario.name),
+            requests = metrics::format_number(number_of_requests),
+            failures = metrics::format_number(number_of_failures),
+        ));
+        reports.push((file_name, report));
+    }
+
+    let index = report_page(
+        users,
+        report_range,
+        hosts,
+        &format!(
+            r#"<div class="scenarios">
+            <h2>{scenarios}</h2>
+            <ul>
+                {links}
+            </ul>
+        </div>"#,
+            scenarios = options.strings.scenarios,
+            links = links.join("\n                "),
+        ),
+        options,
+    );
+    reports.insert(0, (SCENARIO_INDEX_FILE.to_string(), index));
+    reports
+}
+
+/// Build a file name for the report of a scenario from its name, such as
+/// `scenario-checkout.html`, numbered if another scenario already has the same file name.
+fn scenario_file_name(name: &str, file_names: &mut HashSet<String>) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .join("-");
+    let base = if slug.is_empty() {
+        "scenario".to_string()
+    } else {
+        format!("scenario-{}", slug)
+    };
+    let mut file_name = format!("{}.html", base);
+    let mut number = 2;
+    while !file_names.insert(file_name.clone()) {
+        file_name = format!("{}-{}.html", base, number);
+        number += 1;
+    }
+    file_name
+}
+
+/// Build the sections of the html report that are enabled and not empty.
+fn report_body<'a>(templates: GooseReportTemplates<'a>, options: &GooseReportOptions) -> String {
+    let sections = &options.sections;
+    // Graphs are only included if enabled.
+    let graph = |template: &'a str| if sections.show_graphs { template } else { "" };
+
+    // Each section is paired with the name of the function that produced its rows, or
+    // the section itself, for debug annotations.
+    let mut body = vec![
+        ("verdict_template", templates.verdict_template.to_string()),
+        (
+            "graph_overview_template",
+            graph(templates.graph_overview_template).to_string(),
+        ),
+    ];
+    if sections.show_requests {
+        body.push((
+            "raw_request_metrics_row",
+            format!(
+                r#"<div class="requests">
+            <h2>{request_metrics}</h2>
+
+            {graph_rps_template}
+
+            <table>
+                <thead>
+                    {raw_requests_header}
+                </thead>
+                <tbody>
+                    {raw_requests_template}
+                </tbody>
+            </table>
+        </div>"#,
+                raw_requests_template = templates.raw_requests_template,
+                graph_rps_template = graph(templates.graph_rps_template),
+                raw_requests_header = request_metrics_header(options),
+                request_metrics = options.strings.request_metrics,
+            ),
+        ));
+        body.push((
+            "coordinated_omission_request_metrics_row",
+            templates.co_requests_template.to_string(),
+        ));
+        body.push(("throttle_template", templates.throttle_template.to_string()));
+    }
+
+    if sections.show_responses {
+        body.push((
+            "response_metrics_row",
+            format!(
+                r#"<div class="responses">
+            <h2>{response_time_metrics}{successful_only}</h2>
+
+            {graph_average_response_time_template}
+
+            <table>
+                <thead>
+                    {raw_responses_header}
+                </thead>
+                <tbody>
+                    {raw_responses_template}
+                </tbody>
+                <tfoot>
+                    {raw_responses_footer_template}
+                </tfoot>
+            </table>
+        </div>"#,
+                raw_responses_header = response_metrics_header(options),
+                response_time_metrics = options.strings.response_time_metrics,
+                successful_only = if options.percentiles_exclude_failures {
+                    format!(
+                        " <small>({})</small>",
+                        options.strings.successful_responses_only
+                    )
+                } else {
+                    "".to_string()
+                },
+                raw_responses_template = templates.raw_responses_template,
+                raw_responses_footer_template = templates.raw_responses_footer_template,
+                graph_average_response_time_template =
+                    graph(templates.graph_average_response_time_template),
+            ),
+        ));
+        body.push((
+            "coordinated_omission_response_metrics_row",
+            templates.co_responses_template.to_string(),
+        ));
+    }
+
+    if sections.show_status_codes {
+        body.push((
+            "status_code_metrics_row",
+            templates.status_codes_template.to_string(),
+        ));
+    }
+
+    if sections.show_tasks {
+        body.push(("task_metrics_row", templates.tasks_template.to_string()));
+    }
+
+    if sections.show_users {
+        body.push((
+            "graph_users_per_second_template",
+            format!(
+                r#"<div class="users">
+        <h2>{user_metrics}</h2>
+            {graph_users_per_second}
+        </div>"#,
+                graph_users_per_second = graph(templates.graph_users_per_second),
+                user_metrics = options.strings.user_metrics,
+            ),
+        ));
+    }
+
+    if sections.show_errors {
+        body.push(("error_row", templates.errors_template.to_string()));
+    }
+
+    body.push((
+        "diagnostics_template",
+        templates.diagnostics_template.to_string(),
+    ));
+    body.push((
+        "fingerprint_template",
+        templates.fingerprint_template.to_string(),
+    ));
+
+    body.retain(|(_, section)| !section.is_empty());
+    body.into_iter()
+        .map(|(producer, section)| {
+            if options.debug_annotations {
+                annotate_section(producer, &section)
+            } else {
+                section
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n        ")
+}
+
+/// Wrap a section of the report with html comments naming the function that produced it
+/// and, if it contains a table, the number of rows in the table body.
+fn annotate_section(producer: &str, section: &str) -> String {
+    let rows = section
+        .split("<tbody>")
+        .skip(1)
+        .map(|table| {
+            table
+                .split("</tbody>")
+                .next()
+                .unwrap_or_default()
+                .matches("<tr")
+                .count()
+        })
+        .sum::<usize>();
+    let count = if section.contains("<tbody>") {
+        format!(" x{}", rows)
+    } else {
+        "".to_string()
+    };
+    format!(
+        "<!-- {producer}{count} -->\n        {section}\n        <!-- /{producer} -->",
+        producer = producer,
+        count = count,
+        section = section,
+    )
+}
+
+/// Build the html report when no metrics were collected, replacing the empty tables
+/// and graphs with an explanation.
+pub fn build_empty_report(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    options: &GooseReportOptions,
+) -> String {
+    report_page(
+        users,
+        report_range,
+        hosts,
+        &format!(
+            r#"<div class="empty">
+            <h2>{}</h2>
+            <p>{}</p>
+        </div>"#,
+            options.strings.no_metrics, options.strings.no_metrics_reason,
+        ),
+        options,
+    )
+}
+
+/// Build a button switching cells with `data-count` and `data-rps` attributes between
+/// absolute counts and per-second rates. The button's labels are kept in data attributes,
+/// so the script doesn't embed them.
+fn rate_toggle(strings: &ReportStrings) -> String {
+    format!(
+        r#"
+        <p><button id="rate-toggle" type="button" data-show-rates="{show_rates}" data-show-counts="{show_counts}">{show_rates}</button></p>
+        <script type="text/javascript">
+            document.getElementById('rate-toggle').addEventListener('click', function () {{
+                var showRates = this.dataset.showing !== 'rps';
+                this.dataset.showing = showRates ? 'rps' : 'count';
+                this.textContent = showRates ? this.dataset.showCounts : this.dataset.showRates;
+                document.querySelectorAll('td[data-count]').forEach(function (cell) {{
+                    cell.textContent = showRates ? cell.dataset.rps : cell.dataset.count;
+                }});
+            }});
+        </script>
+"#,
+        show_rates = strings.show_rates,
+        show_counts = strings.show_counts,
+    )
+}
+
+/// Build a search box hiding the rows with a `data-name` attribute that doesn't contain
+/// the search text, ignoring case.
+fn search_box(strings: &ReportStrings) -> String {
+    format!(
+        r#"
+        <p><input id="table-search" type="search" placeholder="{filter_endpoints}"></p>
+        <script type="text/javascript">
+            document.getElementById('table-search').addEventListener('input', function () {{
+                var search = this.value.toLowerCase();
+                document.querySelectorAll('tr[data-name]').forEach(function (row) {{
+                    var matches = row.dataset.name.toLowerCase().indexOf(search) !== -1;
+                    row.style.display = matches ? '' : 'none';
+                }});
+            }});
+        </script>
+"#,
+        filter_endpoints = strings.filter_endpoints,
+    )
+}
+
+/// Changes from a baseline within this percentage aren't flagged by default.
+pub const DEFAULT_REGRESSION_THRESHOLD_PCT: f64 = 10.0;
+
+/// Build the change of a metric from its value in a baseline load test, for example
+/// `+25.0%`, colored as a regression or improvement if it exceeds
+/// [`GooseReportOptions::regression_threshold_pct`]. `higher_is_worse` is set for metrics
+/// such as response times and failures, and unset for metrics such as requests per second.
+///
+/// Goose doesn't keep the metrics of previous load tests, so the baseline must be supplied
+/// by the caller. A change from a baseline of zero can't be expressed as a percentage, and
+/// is displayed as a neutral dash.
+pub fn baseline_delta_template(
+    current: f64,
+    baseline: f64,
+    higher_is_worse: bool,
+    options: &GooseReportOptions,
+) -> String {
+    if baseline == 0.0 {
+        return r#"<span class="delta">&ndash;</span>"#.to_string();
+    }
+    let change = (current - baseline) / baseline * 100.0;
+    let threshold = options
+        .regression_threshold_pct
+        .unwrap_or(DEFAULT_REGRESSION_THRESHOLD_PCT);
+    let class = if change.abs() <= threshold {
+        "delta"
+    } else if (change > 0.0) == higher_is_worse {
+        "delta regression"
+    } else {
+        "delta improvement"
+    };
+    format!(r#"<span class="{}">{:+.1}%</span>"#, class, change)
+}
+
+/// Build a small trend chart of the overall p95 response time of previous load tests,
+/// displayed in the report header. Returns an empty string if there's no history.
+pub fn history_trend_template(
+    history: &[(String, f64)],
+    palette: Palette,
+    strings: &ReportStrings,
+) -> String {
+    if history.is_empty() {
+        return "".to_string();
+    }
+
+    let (labels, values): (Vec<_>, Vec<_>) = history.iter().cloned().unzip();
+    format!(
+        r#"<div class="history-trend">
+                <div id="history-trend" style="width: 300px; height:80px;"></div>
+
+                <script type="text/javascript">
+                    var chartDom = document.getElementById('history-trend');
+                    var myChart = echarts.init(chartDom);
+
+                    myChart.setOption({{
+                        title: {{ text: {p95_trend}, textStyle: {{ color: '#b3c3bc', fontSize: 11 }} }},
+                        tooltip: {{ trigger: 'axis' }},
+                        grid: {{ left: 5, right: 5, top: 25, bottom: 5 }},
+                        xAxis: {{ type: 'category', show: false, data: {labels} }},
+                        yAxis: {{ type: 'value', show: false, scale: true }},
+                        series: [
+                            {{
+                                name: 'p95',
+                                type: 'line',
+                                symbolSize: 4,
+                                lineStyle: {{ color: '{color}' }},
+                                itemStyle: {{ color: '{color}' }},
+                                data: {values}
+                            }}
+                        ]
+                    }});
+                </script>
+            </div>"#,
+        labels = json!(labels),
+        values = json!(values),
+        color = palette.color("#00ca5a"),
+        p95_trend = json!(strings.p95_trend),
+    )
+}
+
+/// Build the report footer linking to the files generated alongside the html report, or
+/// an empty string if there are none.
+pub fn downloads_template(downloads: &[String], strings: &ReportStrings) -> String {
+    if downloads.is_empty() {
+        return "".to_string();
+    }
+
+    let links = downloads
+        .iter()
+        .map(|path| {
+            format!(
+                r#"<li><a href="{path}">{path}</a></li>"#,
+                path = escape_html(path)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        r#"
+        <div class="footer">
+            <div class="download">
+                <h3>{downloads}</h3>
+                <ul>
+                    {links}
+                </ul>
+            </div>
+        </div>
+"#,
+        links = links.join("\n                    "),
+        downloads = strings.downloads,
+    )
+}
+
+/// Build the style sheet of the html report. It's inlined in the report, unless
+/// [`GooseReportOptions::stylesheet_href`] is set, in which case it must be served from
+/// that location.
+pub fn report_stylesheet(options: &GooseReportOptions) -> String {
+    let (cell_height, cell_padding) = options.table_density.cell_size();
+
+    let palette = options.palette;
+    format!(
+        r#"        .container {{
+            width: 1000px;
+            margin: 0 auto;
+            padding: 10px;
+            background: #173529;
+            font-family: Arial, Helvetica, sans-serif;
+            font-size: 14px;
+            color: #fff;
+        }}
+
+        .info span{{
+            color: #b3c3bc;
+        }}
+
+        table {{
+            border-collapse: collapse;
+            text-align: center;
+            width: 100%;
+        }}
+
+        td, th {{
+            border: 1px solid #cad9ea;
+            color: #666;
+            height: {cell_height}px;
+            padding: {cell_padding}px;
+        }}
+
+        thead th {{
+            background-color: #cce8eb;
+            width: 100px;
+        }}
+{striping}
+        .charts-container .chart {{
+            width: 100%;
+            height: 350px;
+            margin-bottom: 30px;
+        }}
+
+        .download {{
+            float: right;
+        }}
+
+        .download a {{
+            color: {link};
+        }}
+
+        .footer {{
+            overflow: hidden;
+        }}
+
+        .info span.badge {{
+            padding: 2px 8px;
+            border-radius: 3px;
+            color: #fff;
+            font-weight: bold;
+        }}
+
+        span.badge.live {{
+            background-color: {danger};
+        }}
+
+        span.badge.final {{
+            background-color: {success};
+        }}
+
+        .info span.shortfall {{
+            color: {danger};
+            font-weight: bold;
+        }}
+
+        .description {{
+            margin-bottom: 1em;
+            padding: 0.5em 1em;
+            border-left: 4px solid {success};
+            background-color: {success_background};
+        }}
+
+        span.method {{
+            padding: 2px 6px;
+            border-radius: 3px;
+            color: #fff;
+            font-size: 0.85em;
+            font-weight: bold;
+        }}
+
+        .graph {{
+            margin-bottom: 1em;
+        }}
+
+        td.key, th.key {{
+            font-weight: bold;
+            background-color: {success_background};
+        }}
+
+        td.traffic-share div.bar {{
+            height: 3px;
+            background-color: {success};
+        }}
+
+        td.minor, th.minor {{
+            color: #999;
+        }}
+
+        td details summary {{
+            cursor: pointer;
+        }}
+
+        td details pre {{
+            text-align: left;
+            white-space: pre-wrap;
+        }}
+
+        tr.truncated td {{
+            font-style: italic;
+        }}
+
+        tr.error-status td {{
+            background-color: {danger_background};
+        }}
+
+        span.delta.regression {{
+            color: {danger};
+            font-weight: bold;
+        }}
+
+        span.delta.improvement {{
+            color: {success};
+        }}
+
+        h2.phase-title {{
+            font-size: 1.8em;
+            border-bottom: 2px solid #b3c3bc;
+        }}
+
+        .graph-error {{
+            padding: 20px;
+            background: #fff;
+            color: {danger};
+        }}
+
+        .graph-empty {{
+            padding: 20px;
+            background: #fff;
+            color: #999;
+            text-align: center;
+        }}
+
+        .history-trend {{
+            float: right;
+        }}
+
+        img.logo {{
+            max-height: 60px;
+            max-width: 300px;
+            margin-top: 10px;
+        }}
+
+        .verdict h2 {{
+            padding: 10px;
+        }}
+
+        .verdict.pass h2 {{
+            background-color: {success};
+        }}
+
+        .verdict.fail h2 {{
+            background-color: {danger};
+        }}
+
+        .verdict tr.fail td {{
+            color: {danger};
+            font-weight: bold;
+        }}
+"#,
+        success = palette.color("#2c664f"),
+        danger = palette.color("#a32a2a"),
+        link = palette.color("#00ca5a"),
+        success_background = palette.color("#e6f2ed"),
+        danger_background = palette.color("#fbe3e0"),
+        cell_height = cell_height,
+        cell_padding = cell_padding,
+        striping = if options.no_table_striping {
+            ""
+        } else {
+            r#"
+        tr:nth-child(odd) {
+            background: #fff;
+        }
+
+        tr:nth-child(even) {
+            background: #f5fafa;
+        }
+"#
+        },
+    )
+}
+
+/// Build the line crediting the tool that generated the report, and the Goose version.
+pub fn generator_template(options: &GooseReportOptions) -> String {
+    let goose = format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let generator = match options.generator_name.as_ref() {
+        Some(name) => {
+            let name = match options.generator_url.as_ref() {
+                Some(url) => format!(
+                    r#"<a href="{url}">{name}</a>"#,
+                    url = escape_html(url),
+                    name = escape_html(name),
+                ),
+                None => escape_html(name),
+            };
+            options
+                .strings
+                .generated_by
+                .replace("{generator}", &name)
+                .replace("{goose}", &goose)
+        }
+        None => goose,
+    };
+    format!("<p><span><small><em>{}</em></small></span></p>", generator)
+}
+
+/// Wrap the report body with the html page, styles and summary header.
+fn report_page(
+    users: &str,
+    report_range: &str,
+    hosts: &str,
+    body: &str,
+    options: &GooseReportOptions,
+) -> String {
+    let hosts = redact(hosts, &options.redactions);
+
+    let report = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{title}</title>
+    {stylesheet}
+    {echarts}
+</head>
+<body>
+    <div class="container">
+        {logo}<h1>{title}</h1>
+
+        <div class="info">
+            {history_trend}
+            <p>{users_label}: <span>{users}</span> </p>
+            <p>{target_host}: <span>{hosts}</span></p>
+            {report_range}
+            {build_info}
+            {generator}
+        </div>
+        {description}
+{search_box}{rate_toggle}
+        {body}
+{downloads}
+    </div>
+</body>
+</html>"#,
+        title = options.strings.title,
+        logo = logo_template(options.logo_data_uri.as_deref()),
+        users_label = options.strings.users,
+        target_host = options.strings.target_host,
+        users = users,
+        report_range = report_range,
+        hosts = hosts,
+        build_info = build_info_template(&options.build_info, &options.strings),
+        generator = generator_template(options),
+        description =
+            description_template(options.description.as_deref(), options.description_format),
+        stylesheet = match options.stylesheet_href.as_ref() {
+            Some(href) => format!(r#"<link rel="stylesheet" href="{}">"#, escape_html(href)),
+            None => format!("<style>\n{}    </style>", report_stylesheet(options)),
+        },
+        search_box = if options.search_box {
+            search_box(&options.strings)
+        } else {
+            "".to_string()
+        },
+        rate_toggle = if options.rate_toggle {
+            rate_toggle(&options.strings)
+        } else {
+            "".to_string()
+        },
+        // The trend chart requires echarts, which is only loaded with the graphs.
+        history_trend = if options.sections.show_graphs {
+            history_trend_template(&options.history, options.palette, &options.strings)
+        } else {
+            "".to_string()
+        },
+        echarts = match options.echarts_script.as_ref() {
+            _ if !options.sections.show_graphs => "".to_string(),
+            // Escape closing tags in the library, which would otherwise end the script.
+            Some(echarts) => format!("<script>{}</script>", echarts.replace("</", "<\\/")),
+            None => r#"<script src="https://cdn.jsdelivr.net/npm/echarts@5.2.2/dist/echarts.min.js"></script>"#
+                .to_string(),
+        },
+        body = body,
+        downloads = downloads_template(&options.downloads, &options.strings),
+    );
+    let report = if options.connect_graphs && options.sections.show_graphs {
+        connect_graphs(&report)
+    } else {
+        report
+    };
+
+    match options.script_nonce.as_ref() {
+        Some(nonce) => add_script_nonce(&report, nonce),
+        None => report,
+    }
+}
+
+/// Build the logo displayed at the top of the report, or an empty string if there's no
+/// logo or it isn't a `data:` URI.
+pub fn logo_template(logo_data_uri: Option<&str>) -> String {
+    match logo_data_uri {
+        Some(uri) if uri.trim_start().to_ascii_lowercase().starts_with("data:") => format!(
+            r#"<img class="logo" src="{}" alt="">"#,
+            escape_html(uri.trim())
+        ),
+        Some(_) => {
+            warn!("ignoring report logo, it must be a data: URI");
+            "".to_string()
+        }
+        None => "".to_string(),
+    }
+}
+
+/// Add every graph of the html report with a `dataZoom` to the [`GRAPH_GROUP`] echarts
+/// group, and connect the group, linking their zoom.
+fn connect_graphs(html: &str) -> String {
+    let init = "var myChart = echarts.init(chartDom);";
+    let mut parts = html.split(init);
+    let mut connected = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        connected.push_str(init);
+        let script = part.split("</script>").next().unwrap_or_default();
+        if script.contains("dataZoom") {
+            connected.push_str(&format!(
+                "\n                    myChart.group = '{}';",
+                GRAPH_GROUP
+            ));
+        }
+        connected.push_str(part);
+    }
+    connected.replace(
+        "</body>",
+        &format!(
+            r#"    <script type="text/javascript">echarts.connect('{}');</script>
+</body>"#,
+            GRAPH_GROUP
+        ),
+    )
+}
+
+/// Add a `nonce` attribute to every `<script>` tag in the html report, with or without
+/// other attributes.
+fn add_script_nonce(html: &str, nonce: &str) -> String {
+    let nonce = format!(r#"nonce="{}""#, nonce.replace('"', "&quot;"));
+    html.replace("<script ", &format!("<script {} ", nonce))
+        .replace("<script>", &format!("<script {}>", nonce))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::goose::GooseMethod;
+
+    /// Compare html with the golden file `tests/goldens/{name}.html`.
+    ///
+    /// After intentionally changing the html report, run the tests with the `GOOSE_BLESS`
+    /// environment variable set to regenerate the golden files, then review the diff.
+    fn assert_golden(name: &str, html: &str) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("goldens")
+            .join(format!("{}.html", name));
+        if std::env::var_os("GOOSE_BLESS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, html).unwrap();
+        } else {
+            let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+                panic!(
+                    "failed to read golden file {} (set GOOSE_BLESS to create it): {}",
+                    path.display(),
+                    e
+                )
+            });
+            assert!(
+                html == expected,
+                "html differs from golden file {} (set GOOSE_BLESS to update it):\n{}",
+                path.display(),
+                html
+            );
+        }
+    }
+
+    /// A request metric with no requests, for tests to override the fields they need.
+    fn request_metric(method: &str, name: &str) -> RequestMetric {
+        RequestMetric {
+            method: method.to_string(),
+            name: name.to_string(),
+            number_of_requests: 0,
+            number_of_failures: 0,
+            response_time_average: "0.00".to_string(),
+            response_time_minimum: 0,
+            response_time_maximum: 0,
+            requests_per_second: "0.00".to_string(),
+            failures_per_second: "0.00".to_string(),
+            requests_per_second_value: 0.0,
+            failures_per_second_value: 0.0,
+            apdex: "".to_string(),
+            requests_per_second_series: None,
+            status_code_counts: HashMap::new(),
+            traffic_share: 0.0,
+            first_seen: None,
+            last_seen: None,
+            connection_reuse: None,
+        }
+    }
+
+    /// Data shared by the graph tests.
+    fn graph_data() -> Vec<(String, u32)> {
+        vec![
+            ("2021-11-21 21:20:32".to_string(), 123),
+            ("2021-11-21 21:20:33".to_string(), 111),
+            ("2021-11-21 21:20:34".to_string(), 99),
+            ("2021-11-21 21:20:35".to_string(), 134),
+        ]
+    }
+
+    /// Starting, started, stopping and stopped markers each graph is tested with, and the
+    /// suffix of the corresponding golden file.
+    #[allow(clippy::type_complexity)]
+    fn graph_markers() -> Vec<(&'static str, [Option<DateTime<Local>>; 4])> {
+        let time = |second| {
+            Some(
+                Local
+                    .with_ymd_and_hms(2021, 11, 21, 21, 20, second)
+                    .unwrap(),
+            )
+        };
+        vec![
+            ("no_markers", [None, None, None, None]),
+            ("starting", [time(32), time(34), None, None]),
+            ("stopping", [None, None, time(32), time(34)]),
+            (
+                "starting_stopping",
+                [time(32), time(34), time(36), time(38)],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_graph_rps_template() {
+        for (suffix, [starting, started, stopping, stopped]) in graph_markers() {
+            let html = graph_rps_template(
+                &graph_data(),
+                GraphPhases {
+                    starting,
+                    started,
+                    stopping,
+                    stopped,
+                    ..Default::default()
+                },
+                &GooseReportOptions::default(),
+            );
+            assert_golden(&format!("graph_rps_{}", suffix), &html);
+        }
+    }
+
+    #[test]
+    fn test_graph_eps_template() {
+        for (suffix, [starting, started, stopping, stopped]) in graph_markers() {
+            let html = graph_eps_template(
+                &graph_data(),
+                GraphPhases {
+                    starting,
+                    started,
+                    stopping,
+                    stopped,
+                    ..Default::default()
+                },
+                &GooseReportOptions::default(),
+            );
+            assert_golden(&format!("graph_eps_{}", suffix), &html);
+        }
+    }
+
+    #[test]
+    fn test_graph_average_response_time_template() {
+        for (suffix, [starting, started, stopping, stopped]) in graph_markers() {
+            let html = graph_average_response_time_template(
+                &graph_data(),
+                GraphPhases {
+                    starting,
+                    started,
+                    stopping,
+                    stopped,
+                    ..Default::default()
+                },
+                None,
+                None,
+                &GooseReportOptions::default(),
+            );
+            assert_golden(&format!("graph_average_response_time_{}", suffix), &html);
+        }
+    }
+
+    #[test]
+    fn test_graph_users_per_second_template() {
+        let data = graph_data()
+            .into_iter()
+            .map(|(time, users)| (time, users as usize))
+            .collect::<Vec<_>>();
+        for (suffix, [starting, started, stopping, stopped]) in graph_markers() {
+            let html = graph_users_per_second_template(
+                &data,
+                GraphPhases {
+                    starting,
+                    started,
+                    stopping,
+                    stopped,
+                    ..Default::default()
+                },
+                &GooseReportOptions::default(),
+            );
+            assert_golden(&format!("graph_users_per_second_{}", suffix), &html);
+        }
+    }
+
+    #[test]
+    fn test_graph_tasks_per_second_template() {
+        for (suffix, [starting, started, stopping, stopped]) in graph_markers() {
+            let html = graph_tasks_per_second_template(
+                &graph_data(),
+                GraphPhases {
+                    starting,
+                    started,
+                    stopping,
+                    stopped,
+                    ..Default::default()
+                },
+                &GooseReportOptions::default(),
+            );
+            assert_golden(&format!("graph_tasks_per_second_{}", suffix), &html);
+        }
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let errors = metrics::GooseErrorMetrics::new();
+        assert!(is_empty_report(&[], &[], &errors));
+
+        let report = build_empty_report("0", "", "http://localhost/", &Default::default());
+        assert!(report.contains("No metrics were collected"));
+        assert!(!report.contains("<th># Requests</th>"));
+
+        let request_metric = RequestMetric {
+            number_of_requests: 1,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.00,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", "/")
+        };
+        assert!(!is_empty_report(&[request_metric], &[], &errors));
+    }
+
+    #[test]
+    fn test_response_metrics_footer() {
+        let response_metric = |name: &str, percentile_95: &str| ResponseMetric {
+            method: "GET".to_string(),
+            name: name.to_string(),
+            response_time_average: "10.00".to_string(),
+            percentile_50: "10".to_string(),
+            percentile_60: "10".to_string(),
+            percentile_70: "10".to_string(),
+            percentile_80: "10".to_string(),
+            percentile_90: "10".to_string(),
+            percentile_95: percentile_95.to_string(),
+            percentile_99: "2,000".to_string(),
+            percentile_100: "2,000".to_string(),
+            over_threshold: "".to_string(),
+        };
+        let metrics = vec![
+            response_metric("/fast", "900"),
+            response_metric("/slow", "1,200"),
+        ];
+
+        let footer = response_metrics_footer(&metrics, &GooseReportOptions::default());
+        assert!(footer.contains("<td>1,200<br /><small>GET /slow</small></td>"));
+        assert!(!footer.contains("<td>900<br />"));
+        // Ties are attributed to the first endpoint.
+        assert!(footer.contains("<td>2,000<br /><small>GET /fast</small></td>"));
+
+        assert_eq!(
+            response_metrics_footer(&[], &GooseReportOptions::default()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_script_nonce() {
+        let data = vec![("2021-11-21 21:20:32".to_string(), 123)];
+        let graph = graph_rps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "<script>var tasks = 1;</script>",
+            status_codes_template: "",
+            errors_template: &graph,
+            graph_rps_template: &graph,
+            graph_average_response_time_template: &graph,
+            graph_users_per_second: &graph,
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        // By default no nonce is added.
+        let report = build_report("1", "", "", templates(), &GooseReportOptions::default());
+        assert!(!report.contains("nonce="));
+
+        let options = GooseReportOptions {
+            script_nonce: Some("r4nd0m".to_string()),
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        // The echarts library, four graphs and a script tag without attributes.
+        assert_eq!(report.matches("<script").count(), 6);
+        assert_eq!(report.matches(r#"<script nonce="r4nd0m" "#).count(), 5);
+        assert!(report.contains(r#"<script nonce="r4nd0m">var tasks = 1;</script>"#));
+    }
+
+    #[test]
+    fn test_graph_ramp_shading() {
+        let time = |second| {
+            Some(
+                Local
+                    .with_ymd_and_hms(2021, 11, 21, 21, 20, second)
+                    .unwrap(),
+            )
+        };
+        let graph = |ramp_shading| {
+            graph_users_per_second_template(
+                &[("2021-11-21 21:20:32".to_string(), 3)],
+                GraphPhases {
+                    starting: time(32),
+                    started: time(34),
+                    stopping: time(36),
+                    stopped: time(38),
+                    ..Default::default()
+                },
+                &GooseReportOptions {
+                    hide_ramp_shading: if ramp_shading {
+                        HashSet::new()
+                    } else {
+                        vec![ReportGraph::ActiveUsers].into_iter().collect()
+                    },
+                    ..Default::default()
+                },
+            )
+        };
+
+        let shaded = graph(true);
+        assert!(shaded.contains(r#""name":"Starting""#));
+        assert!(shaded.contains(r#""name":"Stopping""#));
+
+        // Suppressed shading leaves the mark area empty despite the timestamps.
+        let unshaded = graph(false);
+        assert!(!unshaded.contains(r#""name":"Starting""#));
+        assert!(!unshaded.contains(r#""name":"Stopping""#));
+        assert!(!unshaded.contains(r#""xAxis":"2021-11-21 21:20:3"#));
+
+        let options = GooseReportOptions {
+            hide_ramp_shading: vec![ReportGraph::RequestsPerSecond].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(!options.shows_ramp_shading(ReportGraph::RequestsPerSecond));
+        assert!(options.shows_ramp_shading(ReportGraph::ActiveUsers));
+    }
+
+    #[test]
+    fn test_graph_serialization_failure() {
+        struct Unserializable;
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("<unsupported> value"))
+            }
+        }
+
+        let data = vec![("2021-11-21 21:20:32".to_string(), Unserializable)];
+        let graph = graph_tasks_per_second_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert_eq!(
+            graph,
+            r#"<div class="graph graph-error" id="graph-tps">Unable to render graph: &lt;unsupported&gt; value</div>"#
+        );
+        assert!(!graph.contains("<script"));
+    }
+
+    #[test]
+    fn test_graph_response_scatter_template() {
+        let samples = vec![
+            ("2021-11-21 21:20:32".to_string(), 12),
+            ("2021-11-21 21:20:32".to_string(), 850),
+            ("2021-11-21 21:20:33".to_string(), 15),
+        ];
+        let graph = graph_response_scatter_template(
+            &samples,
+            None,
+            ColorRole::default(),
+            Palette::default(),
+            &ReportStrings::default(),
+        );
+        assert!(graph.contains("type: 'scatter',"));
+        assert!(graph.contains(
+            r#"data: [["2021-11-21 21:20:32",12],["2021-11-21 21:20:32",850],["2021-11-21 21:20:33",15]]"#
+        ));
+
+        // Downsampling keeps the slowest sample of each run.
+        let graph = graph_response_scatter_template(
+            &samples,
+            Some(2),
+            ColorRole::default(),
+            Palette::default(),
+            &ReportStrings::default(),
+        );
+        assert!(graph.contains(r#"data: [["2021-11-21 21:20:32",850],["2021-11-21 21:20:33",15]]"#));
+        let samples = (0..25_000)
+            .map(|index| ("2021-11-21 21:20:32".to_string(), index % 100))
+            .collect::<Vec<_>>();
+        let downsampled = downsample_scatter(&samples, DEFAULT_SCATTER_POINTS);
+        assert!(downsampled.len() <= DEFAULT_SCATTER_POINTS);
+        assert!(downsampled
+            .iter()
+            .all(|(_, response_time)| *response_time >= 2));
+    }
+
+    #[cfg(feature = "chart-png")]
+    #[test]
+    fn test_export_graph_png() {
+        let data = graph_data();
+        let starting = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 30).unwrap();
+        let started = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap();
+        let png = export_graph_png(
+            ReportGraph::AverageResponseTime,
+            &data,
+            GraphPhases {
+                starting: Some(starting),
+                started: Some(started),
+                ..Default::default()
+            },
+            &GooseReportOptions {
+                slow_request_threshold: Some(250),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        // Graphs without data have nothing to draw.
+        let empty: Vec<(String, u32)> = Vec::new();
+        let options = GooseReportOptions::default();
+        assert_eq!(
+            export_graph_png(
+                ReportGraph::RequestsPerSecond,
+                &empty,
+                GraphPhases::default(),
+                &options
+            ),
+            None
+        );
+    }
+
+    #[cfg(not(feature = "chart-png"))]
+    #[test]
+    fn test_export_graph_png_disabled() {
+        let options = GooseReportOptions::default();
+        assert_eq!(
+            export_graph_png(
+                ReportGraph::RequestsPerSecond,
+                &graph_data(),
+                GraphPhases::default(),
+                &options
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_export_graph_option_json() {
+        let data = graph_data();
+        let starting = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 30).unwrap();
+        let started = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap();
+        let options = GooseReportOptions {
+            slow_request_threshold: Some(250),
+            ..Default::default()
+        };
+        let export = export_graph_option_json(
+            ReportGraph::AverageResponseTime,
+            &data,
+            GraphPhases {
+                starting: Some(starting),
+                started: Some(started),
+                ..Default::default()
+            },
+            &options,
+        )
+        .unwrap();
+        let option: serde_json::Value = serde_json::from_str(&export).unwrap();
+        assert_eq!(option["xAxis"]["type"], "time");
+        assert_eq!(option["yAxis"]["name"], "Response time [ms]");
+        let series = option["series"].as_array().unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0]["type"], "line");
+        assert_eq!(series[0]["data"], json!(data));
+        assert_eq!(series[0]["markLine"]["data"], json!([{ "yAxis": 250 }]));
+        assert_eq!(
+            series[0]["markArea"]["data"],
+            json!([[
+                { "name": "Starting", "xAxis": "2021-11-21 21:20:30" },
+                { "xAxis": "2021-11-21 21:20:32" },
+            ]])
+        );
+
+        // The exported option matches the one inlined in the html report.
+        let markup = graph_average_response_time_template(
+            &data,
+            GraphPhases {
+                starting: Some(starting),
+                started: Some(started),
+                ..Default::default()
+            },
+            None,
+            None,
+            &GooseReportOptions {
+                slow_request_threshold: Some(250),
+                graph_axis_decimals: Some(1),
+                ..Default::default()
+            },
+        );
+        assert!(markup.contains(&format!("myChart.setOption({});", export)));
+
+        // Errors are drawn in the danger color.
+        let export = export_graph_option_json(
+            ReportGraph::ErrorsPerSecond,
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        )
+        .unwrap();
+        let option: serde_json::Value = serde_json::from_str(&export).unwrap();
+        assert_eq!(
+            option["color"],
+            json!([ColorRole::Danger.colors(Palette::default()).0])
+        );
+        assert_eq!(option["series"][0]["markArea"]["data"], json!([]));
+        assert!(option["series"][0].get("markLine").is_none());
+    }
+
+    #[test]
+    fn test_graph_symbol() {
+        // Each point of a short series is marked.
+        let data = vec![("2021-11-21 21:20:32".to_string(), 3)];
+        let graph = graph_rps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(graph.contains(r#""symbol":"circle","type":"line""#));
+
+        // A long series is drawn as a plain line.
+        let data = (0..SPARSE_GRAPH_POINTS)
+            .map(|second| (format!("2021-11-21 21:20:{:02}", second), 3))
+            .collect::<Vec<_>>();
+        let graph = graph_rps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(graph.contains(r#""symbol":"none","type":"line""#));
+        assert!(!graph.contains(r#""circle""#));
+    }
+
+    #[test]
+    fn test_first_error() {
+        let starting = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 0).unwrap();
+        let users = [10, 20, 30, 40, 50];
+        let rps = [100, 200, 300, 400, 500];
+
+        // No errors.
+        assert_eq!(first_error(&[0, 0, 0, 0, 0], &users, &rps, &starting), None);
+        assert_eq!(first_error_template(None, &ReportStrings::default()), "");
+
+        // The first error occurred with 40 users.
+        let first = first_error(&[0, 0, 0, 2, 5], &users, &rps, &starting).unwrap();
+        assert_eq!(
+            first,
+            FirstError {
+                time: Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 3).unwrap(),
+                users: 40,
+                requests_per_second: 400,
+            }
+        );
+        assert_eq!(
+            first_error_template(Some(&first), &ReportStrings::default()),
+            "<p>First error at: 21:20:03 (&asymp; 40 users, 400 RPS)</p>"
+        );
+        assert!(
+            errors_template("", "", Some(&first), &ReportStrings::default())
+                .contains("<h2>Errors</h2>\n        <p>First error at: 21:20:03")
+        );
+
+        // Series that end before the first error count as no load.
+        let first = first_error(&[0, 0, 0, 2, 5], &users[..2], &rps, &starting).unwrap();
+        assert_eq!(first.users, 0);
+        assert_eq!(first.requests_per_second, 400);
+    }
+
+    #[test]
+    fn test_palette() {
+        let verdict = verdict_template(
+            &ReportVerdict {
+                passed: false,
+                results: Vec::new(),
+            },
+            &ReportStrings::default(),
+        );
+        let graph = |palette| {
+            graph_rps_template(
+                &[("2021-11-21 21:20:32".to_string(), 3)],
+                GraphPhases::default(),
+                &GooseReportOptions {
+                    palette,
+                    ..Default::default()
+                },
+            )
+        };
+        let mut counts = BTreeMap::new();
+        counts.insert("2xx".to_string(), 10);
+        counts.insert("5xx".to_string(), 1);
+        let status_codes = |palette| {
+            status_code_metrics_template(
+                "",
+                &graph_status_class_donut_template(&counts, palette, &ReportStrings::default()),
+                &ReportStrings::default(),
+            )
+        };
+        let report = |options: &GooseReportOptions| {
+            let graph = graph(options.palette);
+            let status_codes = status_codes(options.palette);
+            let templates = GooseReportTemplates {
+                raw_requests_template: "",
+                raw_responses_template: "",
+                raw_responses_footer_template: "",
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: "",
+                status_codes_template: &status_codes,
+                errors_template: "",
+                graph_rps_template: &graph,
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: &verdict,
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: "",
+            };
+            build_report("", "", "", templates, options)
+        };
+
+        // The default palette is green and red.
+        let html = report(&Default::default());
+        assert!(html.contains("#2c664f"));
+        assert!(html.contains("#a32a2a"));
+        assert!(!html.contains("#0072b2"));
+
+        // The color-blind-safe palette replaces the green and red, in the graphs, the
+        // status classes and the pass/fail styles.
+        let options = GooseReportOptions {
+            palette: Palette::ColorBlindSafe,
+            ..Default::default()
+        };
+        let html = report(&options);
+        for (default, _) in COLOR_BLIND_SAFE_COLORS {
+            assert!(!html.contains(default), "{} wasn't replaced", default);
+        }
+        assert!(html.contains(r##""color":["#0072b2"]"##));
+        assert!(html.contains(r##""itemStyle":{"color":"#d55e00"},"name":"5xx""##));
+        assert!(html.contains(".verdict.fail h2 {\n            background-color: #d55e00;"));
+        assert_eq!(Palette::ColorBlindSafe.color("#2c664f"), "#0072b2");
+        assert_eq!(Palette::ColorBlindSafe.color("#123456"), "#123456");
+
+        // Colors of the default palette in user-supplied content are left alone.
+        let options = GooseReportOptions {
+            palette: Palette::ColorBlindSafe,
+            method_colors: vec![("POST".to_string(), "#2c664f".to_string())]
+                .into_iter()
+                .collect(),
+            description: Some("Brand color #a32a2a".to_string()),
+            ..Default::default()
+        };
+        let html = report(&options);
+        assert!(html.contains("Brand color #a32a2a"));
+        assert!(method_cell("POST", &options).contains("background-color: #2c664f;"));
+        assert!(method_cell("GET", &options).contains("background-color: #cc79a7;"));
+    }
+
+    #[test]
+    fn test_graph_color_role() {
+        let data = vec![("2021-11-21 21:20:32".to_string(), 3)];
+
+        // Errors are drawn in the danger color by default.
+        let (danger_line, danger_area) = ColorRole::Danger.colors(Palette::default());
+        let graph = graph_eps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(graph.contains(&format!(r#""color":["{}"]"#, danger_line)));
+        assert!(graph.contains(&format!(r#""areaStyle":{{"color":"{}"}}"#, danger_area)));
+        assert!(!graph.contains(ColorRole::Success.colors(Palette::default()).0));
+
+        // Requests are drawn in the success color by default.
+        let graph = graph_rps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(graph.contains(&format!(
+            r#""color":["{}"]"#,
+            ColorRole::Success.colors(Palette::default()).0
+        )));
+        assert!(!graph.contains(danger_line));
+    }
+
+    #[test]
+    fn test_tasks_only_report() {
+        let request = request_metric("", "Aggregated");
+        let task = TaskMetric {
+            is_task_set: false,
+            task: "0.0".to_string(),
+            name: "checkout".to_string(),
+            number_of_requests: 10,
+            number_of_failures: 0,
+            response_time_average: "250.00".to_string(),
+            response_time_minimum: 200,
+            response_time_maximum: 300,
+            requests_per_second: "1.00".to_string(),
+            failures_per_second: "0.00".to_string(),
+        };
+        assert!(is_tasks_only_report(
+            std::slice::from_ref(&request),
+            std::slice::from_ref(&task)
+        ));
+        assert!(!is_tasks_only_report(&[request], &[]));
+
+        let strings = ReportStrings::default();
+        let tasks_template = task_metrics_template(&task_metrics_row(task), "", &strings);
+        let report = build_tasks_only_report(
+            "1",
+            "",
+            "",
+            GooseReportTemplates {
+                raw_requests_template: "",
+                raw_responses_template: "",
+                raw_responses_footer_template: "",
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: &tasks_template,
+                status_codes_template: "",
+                errors_template: "",
+                graph_rps_template: "",
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: "",
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: "",
+            },
+            &GooseReportOptions::default(),
+        );
+        assert!(report.contains("<h2>Task Metrics</h2>"));
+        assert!(report.contains("0.0 checkout"));
+        assert!(report.contains("<h2>User Metrics</h2>"));
+        assert!(!report.contains("<h2>Request Metrics</h2>"));
+        assert!(!report.contains("<h2>Response Time Metrics</h2>"));
+    }
+
+    #[test]
+    fn test_report_data_uri() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+
+        // Decode standard, padded base64.
+        let decode = |encoded: &str| {
+            const ALPHABET: &str =
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+            let mut bits = 0u32;
+            let mut count = 0;
+            let mut decoded = Vec::new();
+            for c in encoded.chars().filter(|c| *c != '=') {
+                bits = bits << 6 | ALPHABET.find(c).unwrap() as u32;
+                count += 6;
+                if count >= 8 {
+                    count -= 8;
+                    decoded.push((bits >> count) as u8);
+                }
+            }
+            String::from_utf8(decoded).unwrap()
+        };
+
+        let graph = r#"<div class="graph"><script>echarts.init()</script></div>"#;
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "<tr><td>GET</td><td>/</td></tr>",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: graph,
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+        let html = |options: &GooseReportOptions| {
+            let uri = build_report_data_uri(
+                "1",
+                "",
+                "http://caf\u{e9}.example.com",
+                templates(),
+                options,
+            );
+            decode(uri.strip_prefix("data:text/html;base64,").unwrap())
+        };
+
+        // Without an inlined ECharts the graphs are omitted.
+        let report = html(&GooseReportOptions::default());
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.ends_with("</html>"));
+        assert!(report.contains("<h2>Request Metrics</h2>"));
+        assert!(report.contains("http://caf\u{e9}.example.com"));
+        assert!(!report.contains("<script src="));
+        assert!(!report.contains("echarts"));
+
+        // With an inlined ECharts the graphs are drawn offline.
+        let options = GooseReportOptions {
+            echarts_script: Some("var echarts = {}; // </script>".to_string()),
+            ..Default::default()
+        };
+        let report = html(&options);
+        assert!(!report.contains("<script src="));
+        assert!(report.contains("<script>var echarts = {}; // <\\/script></script>"));
+        assert!(report.contains(graph));
+    }
+
+    #[test]
+    fn test_build_info() {
+        // Nothing is displayed by default.
+        assert_eq!(
+            build_info_template(&BuildInfo::default(), &ReportStrings::default()),
+            ""
+        );
+
+        let options = GooseReportOptions {
+            build_info: BuildInfo {
+                commit: Some("4a7d741".to_string()),
+                branch: Some("feature/<login>".to_string()),
+                build_label: None,
+            },
+            ..Default::default()
+        };
+        let html = build_empty_report("", "", "", &options);
+        let info = html.split(r#"<div class="info">"#).nth(1).unwrap();
+        let info = info.split("</div>").next().unwrap();
+        assert!(info.contains(
+            "<p>Commit: <span>4a7d741</span></p>\n            <p>Branch: <span>feature/&lt;login&gt;</span></p>"
+        ));
+        assert!(!info.contains("Build:"));
+    }
+
+    #[test]
+    fn test_generator_template() {
+        let goose = format!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+
+        // Only Goose is credited by default.
+        let html = build_empty_report("", "", "", &GooseReportOptions::default());
+        assert!(html.contains(&format!(
+            "<p><span><small><em>{}</em></small></span></p>",
+            goose
+        )));
+
+        // A wrapper tool is credited alongside the Goose version.
+        let options = GooseReportOptions {
+            generator_name: Some("LoadRunner & Co".to_string()),
+            ..Default::default()
+        };
+        let html = build_empty_report("", "", "", &options);
+        assert!(html.contains(&format!(
+            "<em>Generated by LoadRunner &amp; Co (powered by {})</em>",
+            goose
+        )));
+
+        // And optionally linked.
+        let options = GooseReportOptions {
+            generator_url: Some("https://example.com/tool".to_string()),
+            ..options
+        };
+        assert!(generator_template(&options).contains(&format!(
+            r#"Generated by <a href="https://example.com/tool">LoadRunner &amp; Co</a> (powered by {})"#,
+            goose
+        )));
+
+        // A link without a name is ignored.
+        let options = GooseReportOptions {
+            generator_url: Some("https://example.com/tool".to_string()),
+            ..Default::default()
+        };
+        assert!(!generator_template(&options).contains("example.com"));
+    }
+
+    #[test]
+    fn test_logo() {
+        let logo = "data:image/png;base64,iVBORw0KGgo=";
+        assert_eq!(
+            logo_template(Some(logo)),
+            r#"<img class="logo" src="data:image/png;base64,iVBORw0KGgo=" alt="">"#
+        );
+        assert_eq!(logo_template(Some("https://example.com/logo.png")), "");
+        assert_eq!(logo_template(None), "");
+
+        let options = GooseReportOptions {
+            logo_data_uri: Some(logo.to_string()),
+            ..Default::default()
+        };
+        let report = build_empty_report("1", "", "", &options);
+        assert!(report.contains(&format!(r#"<img class="logo" src="{}" alt="">"#, logo)));
+
+        let options = GooseReportOptions {
+            logo_data_uri: Some("https://example.com/logo.png".to_string()),
+            ..Default::default()
+        };
+        let report = build_empty_report("1", "", "", &options);
+        assert!(!report.contains("<img"));
+        assert!(!report.contains("example.com"));
+    }
+
+    #[test]
+    fn test_external_stylesheet() {
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        // By default the styles are inlined.
+        let options = GooseReportOptions::default();
+        let report = build_report("1", "", "", templates(), &options);
+        assert!(report.contains(&format!(
+            "<style>\n{}    </style>",
+            report_stylesheet(&options)
+        )));
+        assert!(!report.contains("<link"));
+
+        let options = GooseReportOptions {
+            stylesheet_href: Some("goose-report.css".to_string()),
+            script_nonce: Some("r4nd0m".to_string()),
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        assert!(!report.contains("<style"));
+        assert!(report.contains(r#"<link rel="stylesheet" href="goose-report.css">"#));
+        // Scripts still get the nonce.
+        assert!(report.contains(r#"<script nonce="r4nd0m" "#));
+
+        let stylesheet = report_stylesheet(&options);
+        assert!(stylesheet.contains(".container {"));
+        assert!(stylesheet.contains("height: 30px;"));
+        assert!(!stylesheet.contains("<style"));
+    }
+
+    #[test]
+    fn test_freshness_badge() {
+        let updated = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap();
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        // Without a stopped timestamp the load test is still running.
+        let report = build_report(
+            "1",
+            &freshness_badge(None, updated, &ReportStrings::default()),
+            "",
+            templates(),
+            &GooseReportOptions::default(),
+        );
+        assert!(report.contains(r#"<span class="badge live">Live &mdash; updated 21:20:32</span>"#));
+        assert!(!report.contains(r#"<span class="badge final">"#));
+
+        let report = build_report(
+            "1",
+            &freshness_badge(Some(updated), updated, &ReportStrings::default()),
+            "",
+            templates(),
+            &GooseReportOptions::default(),
+        );
+        assert!(report.contains(r#"<span class="badge final">Final</span>"#));
+        assert!(!report.contains(r#"<span class="badge live">"#));
+    }
+
+    #[test]
+    fn test_achieved_users() {
+        let locale = LocaleConfig::default();
+        assert_eq!(
+            achieved_users_row(100, &[20, 60, 80, 75], &locale, &ReportStrings::default()),
+            r#"<p>Achieved users: <span class="shortfall">80/100 users (shortfall)</span></p>"#
+        );
+        assert_eq!(
+            achieved_users_row(100, &[50, 100, 100], &locale, &ReportStrings::default()),
+            "<p>Achieved users: <span>100/100 users</span></p>"
+        );
+        // No users were started.
+        assert!(
+            achieved_users_row(10, &[], &locale, &ReportStrings::default())
+                .contains("0/10 users (shortfall)")
+        );
+    }
+
+    #[test]
+    fn test_strip_ansi_escapes() {
+        let error = metrics::GooseErrorMetricAggregate {
+            method: GooseMethod::Get,
+            name: "/".to_string(),
+            error: "\x1b[31mboom\x1b[0m".to_string(),
+            occurrences: 1,
+        };
+        let row = error_row(&error, &GooseReportOptions::default());
+        assert!(row.contains(r#"<td colspan="4">boom</strong></td>"#));
+
+        // Stripping runs before escaping and truncating multi-line errors.
+        let error = metrics::GooseErrorMetricAggregate {
+            error: "\x1b[1;31merror:\x1b[0m <timeout>\n\x1b]8;;https://example.com\x07docs\x1b]8;;\x1b\\"
+                .to_string(),
+            ..error
+        };
+        let row = error_row(&error, &GooseReportOptions::default());
+        assert!(row.contains("<summary>error: &lt;timeout&gt;</summary>"));
+        assert!(row.contains("<pre>error: &lt;timeout&gt;\ndocs</pre>"));
+
+        let options = GooseReportOptions {
+            preserve_ansi_escapes: true,
+            ..Default::default()
+        };
+        assert!(error_row(&error, &options).contains("\x1b[1;31m"));
+        assert_eq!(strip_ansi_escapes("plain\ttext"), "plain\ttext");
+    }
+
+    #[test]
+    fn test_description() {
+        assert_eq!(description_template(None, DescriptionFormat::PlainText), "");
+        assert_eq!(
+            description_template(Some("  "), DescriptionFormat::Markdown),
+            ""
+        );
+
+        // Plain text is escaped.
+        assert_eq!(
+            description_template(
+                Some("Spike test, <b>3x</b> normal load\n& more\n\nSecond paragraph"),
+                DescriptionFormat::PlainText
+            ),
+            r#"<div class="description"><p>Spike test, &lt;b&gt;3x&lt;/b&gt; normal load<br>&amp; more</p>
+<p>Second paragraph</p></div>"#
+        );
+
+        // Markdown converts a safe subset, escaping any html.
+        let markdown = "Spike test simulating **Black Friday**, see [the plan](https://example.com/?a=1&b=2).\n\n- 3x <i>normal</i> load\n- [unsafe](javascript:void)\n1. First\n2. Second";
+        assert_eq!(
+            description_template(Some(markdown), DescriptionFormat::Markdown),
+            r#"<div class="description"><p>Spike test simulating <strong>Black Friday</strong>, see <a href="https://example.com/?a=1&amp;b=2">the plan</a>.</p>
+<ul><li>3x &lt;i&gt;normal&lt;/i&gt; load</li><li>unsafe</li></ul>
+<ol><li>First</li><li>Second</li></ol></div>"#
+        );
+
+        // The callout is displayed under the header.
+        let options = GooseReportOptions {
+            description: Some("Spike test".to_string()),
+            ..Default::default()
+        };
+        let report = report_page("1", "", "", "", &options);
+        assert!(
+            report.contains("</div>\n        <div class=\"description\"><p>Spike test</p></div>")
+        );
+    }
+
+    #[test]
+    fn test_connect_graphs() {
+        let graph = graph_rps_template(&[], GraphPhases::default(), &GooseReportOptions::default());
+        // A chart without a zoom, such as a pie chart, isn't linked.
+        let body = format!(
+            r#"{}
+<script type="text/javascript">
+    var myChart = echarts.init(chartDom);
+    myChart.setOption({{ series: [{{ type: 'pie' }}] }});
+</script>"#,
+            graph
+        );
+
+        let report = report_page("1", "", "", &body, &GooseReportOptions::default());
+        assert!(!report.contains("echarts.connect"));
+        assert!(!report.contains("myChart.group"));
+
+        let options = GooseReportOptions {
+            connect_graphs: true,
+            ..Default::default()
+        };
+        let report = report_page("1", "", "", &body, &options);
+        assert_eq!(report.matches("myChart.group = 'goose-graphs';").count(), 1);
+        assert!(report.contains(
+            "var myChart = echarts.init(chartDom);\n                    myChart.group = 'goose-graphs';\n"
+        ));
+        assert!(report.contains(
+            "<script type=\"text/javascript\">echarts.connect('goose-graphs');</script>\n</body>"
+        ));
+    }
+
+    #[test]
+    fn test_empty_graphs() {
+        let eps: Vec<(String, u32)> = Vec::new();
+
+        // Rendered as an empty chart by default.
+        assert_eq!(
+            empty_graph_template(
+                ReportGraph::ErrorsPerSecond,
+                &eps,
+                &GooseReportOptions::default()
+            ),
+            None
+        );
+
+        let options = GooseReportOptions {
+            empty_graphs: EmptyGraphs::Skip,
+            ..Default::default()
+        };
+        let markup = empty_graph_template(ReportGraph::ErrorsPerSecond, &eps, &options).unwrap();
+        assert!(!markup.contains("graph-eps"));
+        assert!(!markup.contains("echarts.init"));
+        let errors = errors_template("", &markup, None, &options.strings);
+        assert!(!errors.contains("graph-eps"));
+        assert!(!errors.contains("echarts.init"));
+
+        let options = GooseReportOptions {
+            empty_graphs: EmptyGraphs::Placeholder,
+            ..Default::default()
+        };
+        assert_eq!(
+            empty_graph_template(ReportGraph::ErrorsPerSecond, &eps, &options).unwrap(),
+            r#"<div class="graph graph-empty" id="graph-eps">No data</div>"#
+        );
+
+        // Graphs with data are always rendered.
+        let eps = vec![("2021-11-21 21:20:32".to_string(), 0)];
+        assert_eq!(
+            empty_graph_template(ReportGraph::ErrorsPerSecond, &eps, &options),
+            None
+        );
+    }
+
+    #[test]
+    fn test_method_colors() {
+        let metric = RequestMetric {
+            number_of_requests: 1,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.00,
+            apdex: "1.00".to_string(),
+            ..request_metric("PATCH", "/user")
+        };
+
+        // By default PATCH is orange.
+        let row = raw_request_metrics_row(metric.clone(), &GooseReportOptions::default());
+        assert!(row.contains(
+            r#"<td><span class="method" style="background-color: #d98c1f;">PATCH</span></td>"#
+        ));
+
+        let mut method_colors = HashMap::new();
+        method_colors.insert("PATCH".to_string(), "#7b3fa0".to_string());
+        let options = GooseReportOptions {
+            method_colors,
+            ..Default::default()
+        };
+        let row = raw_request_metrics_row(metric.clone(), &options);
+        assert!(row.contains(
+            r#"<td><span class="method" style="background-color: #7b3fa0;">PATCH</span></td>"#
+        ));
+
+        // Unknown methods are neutral, and the aggregated row has no badge.
+        let (neutral, _) = ColorRole::Neutral.colors(Palette::default());
+        assert!(method_cell("OPTIONS", &options).contains(neutral));
+        let row = raw_request_metrics_row(
+            RequestMetric {
+                method: "".to_string(),
+                ..metric
+            },
+            &options,
+        );
+        assert!(row.contains("<tr data-name=\"/user\">\n        <td></td>"));
+    }
+
+    #[test]
+    fn test_response_boxplot() {
+        let mut response_times = BTreeMap::new();
+        for response_time in 1..=100 {
+            response_times.insert(response_time, 1);
+        }
+        assert_eq!(
+            five_number_summary(&response_times, 100, 1, 100),
+            [1, 25, 50, 75, 100]
+        );
+
+        let data = vec![
+            ("GET /".to_string(), [1, 25, 50, 75, 100]),
+            ("GET /about".to_string(), [5, 10, 12, 20, 300]),
+            ("POST /login".to_string(), [40, 45, 50, 60, 1_200]),
+        ];
+        let graph =
+            graph_response_boxplot_template(&data, Palette::default(), &ReportStrings::default());
+        assert!(graph.contains("type: 'boxplot',"));
+        assert!(graph.contains(r#"data: ["GET /","GET /about","POST /login"]"#));
+        assert!(graph.contains("data: [[1,25,50,75,100],[5,10,12,20,300],[40,45,50,60,1200]]"));
+    }
+
+    #[test]
+    fn test_debug_annotations() {
+        let rows = "<tr><td>GET</td><td>/</td></tr>\n<tr><td>GET</td><td>/about</td></tr>";
+        let templates = || GooseReportTemplates {
+            raw_requests_template: rows,
+            raw_responses_template: rows,
+            raw_responses_footer_template: "<tr><td>Slowest</td></tr>",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: r#"<div class="errors"><table><thead><tr><th>#</th></tr></thead><tbody><tr><td>1</td></tr></tbody></table></div>"#,
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        let report = build_report("1", "", "", templates(), &GooseReportOptions::default());
+        assert!(!report.contains("<!--"));
+
+        let options = GooseReportOptions {
+            debug_annotations: true,
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        assert!(report.contains("<!-- raw_request_metrics_row x2 -->"));
+        assert!(report.contains("<!-- /raw_request_metrics_row -->"));
+        // Footer rows aren't counted.
+        assert!(report.contains("<!-- response_metrics_row x2 -->"));
+        assert!(report.contains("<!-- error_row x1 -->"));
+        assert!(report.contains("<!-- graph_users_per_second_template -->"));
+        // Empty sections aren't annotated.
+        assert!(!report.contains("task_metrics_row"));
+    }
+
+    #[test]
+    fn test_downloads() {
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        // Without downloads there's no footer.
+        let report = build_report("1", "", "", templates(), &GooseReportOptions::default());
+        assert!(!report.contains("Downloads"));
+
+        let options = GooseReportOptions {
+            downloads: vec![
+                "report.json".to_string(),
+                "requests & responses.csv".to_string(),
+            ],
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        let footer = report.split(r#"<div class="footer">"#).nth(1).unwrap();
+        assert!(footer.contains(r#"<div class="download">"#));
+        assert!(footer.contains("<h3>Downloads</h3>"));
+        assert!(footer.contains(r#"<li><a href="report.json">report.json</a></li>"#));
+        assert!(footer.contains(
+            r#"<li><a href="requests &amp; responses.csv">requests &amp; responses.csv</a></li>"#
+        ));
+    }
+
+    #[test]
+    fn test_baseline_delta() {
+        let options = GooseReportOptions {
+            regression_threshold_pct: Some(10.0),
+            ..Default::default()
+        };
+        // Response times 5% slower than the baseline are within the threshold.
+        assert_eq!(
+            baseline_delta_template(105.0, 100.0, true, &options),
+            r#"<span class="delta">+5.0%</span>"#
+        );
+        // 25% slower is a regression, 25% faster is an improvement.
+        assert_eq!(
+            baseline_delta_template(125.0, 100.0, true, &options),
+            r#"<span class="delta regression">+25.0%</span>"#
+        );
+        assert_eq!(
+            baseline_delta_template(75.0, 100.0, true, &options),
+            r#"<span class="delta improvement">-25.0%</span>"#
+        );
+        // Fewer requests per second is a regression.
+        assert_eq!(
+            baseline_delta_template(75.0, 100.0, false, &options),
+            r#"<span class="delta regression">-25.0%</span>"#
+        );
+
+        // A larger threshold tolerates the same change.
+        let options = GooseReportOptions {
+            regression_threshold_pct: Some(30.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            baseline_delta_template(125.0, 100.0, true, &options),
+            r#"<span class="delta">+25.0%</span>"#
+        );
+        assert_eq!(
+            baseline_delta_template(5.0, 0.0, true, &options),
+            r#"<span class="delta">&ndash;</span>"#
+        );
+    }
+
+    #[test]
+    fn test_history_trend() {
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        // Without history there's no trend chart.
+        let report = build_report("1", "", "", templates(), &GooseReportOptions::default());
+        assert!(!report.contains(r#"<div id="history-trend""#));
+
+        let options = GooseReportOptions {
+            history: vec![
+                ("run 1".to_string(), 210.0),
+                ("run 2".to_string(), 225.5),
+                ("run 3".to_string(), 198.0),
+            ],
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        assert!(report.contains(r#"<div id="history-trend""#));
+        assert!(report.contains(r#"data: ["run 1","run 2","run 3"]"#));
+        assert!(report.contains("data: [210.0,225.5,198.0]"));
+    }
+
+    #[test]
+    fn test_get_response_metric_raw() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 90);
+        response_times.insert(1234, 10);
+        let raw = get_response_metric_raw(&response_times, 100, 10, 1234);
+        assert_eq!(raw.len(), PERCENTILES.len());
+        let (_, p95) = raw
+            .iter()
+            .find(|(percentile, _)| *percentile == 0.95_f32 as f64)
+            .unwrap();
+        assert_eq!(*p95, 1234.0);
+
+        // The formatted percentile parses back to the raw number.
+        let metric = get_response_metric("GET", "/", &response_times, 100, 133.4, 10, 1234);
+        assert_eq!(metric.percentile_95, "1,234");
+        let parsed: f64 = metric.percentile_95.replace(',', "").parse().unwrap();
+        assert_eq!(parsed, *p95);
+    }
+
+    #[test]
+    fn test_key_percentiles() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 90);
+        response_times.insert(500, 10);
+        let metric = get_response_metric("GET", "/", &response_times, 100, 59.0, 10, 500);
+
+        // By default no percentiles are emphasized and there's no average column.
+        let row = response_metrics_row(metric.clone(), &GooseReportOptions::default());
+        assert!(!row.contains("<td class="));
+        assert!(!row.contains("59.00"));
+
+        let options = GooseReportOptions {
+            key_percentiles: vec![0.95, 0.99],
+            ..Default::default()
+        };
+        let row = response_metrics_row(metric, &options);
+        assert!(row.contains("<td>59.00</td>"));
+        assert!(row.contains(r#"<td class="key">500</td>"#));
+        assert_eq!(row.matches(r#"class="key""#).count(), 2);
+        assert_eq!(row.matches(r#"class="minor""#).count(), 6);
+
+        let header = response_metrics_header(&options);
+        assert!(header.contains("<th>Average (ms)</th>"));
+        assert!(header.contains(r#"<th class="key">95%ile (ms)</th>"#));
+        assert!(header.contains(r#"<th class="minor">60%ile (ms)</th>"#));
+    }
+
+    #[test]
+    fn test_influx_line_protocol() {
+        let mut metrics = metrics::GooseMetrics::default();
+        let mut request =
+            metrics::GooseRequestMetricAggregate::new("/about us", GooseMethod::Get, 0);
+        request.record_time(10, false);
+        request.record_time(30, false);
+        request.success_count = 2;
+        request.fail_count = 1;
+        metrics
+            .requests
+            .insert("GET /about us".to_string(), request);
+        let request = metrics::GooseRequestMetricAggregate::new("/", GooseMethod::Post, 0);
+        metrics.requests.insert("POST /".to_string(), request);
+
+        let output = build_influx_line_protocol(&metrics, 1639512745000000000);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        // Measurement and tags, fields, and timestamp are separated by unescaped spaces.
+        let (line, timestamp) = lines[0].rsplit_once(' ').unwrap();
+        assert_eq!(timestamp, "1639512745000000000");
+        let fields = line
+            .strip_prefix(r"goose_request,method=GET,name=/about\ us ")
+            .unwrap();
+        let fields: Vec<&str> = fields.split(',').collect();
+        assert!(fields.contains(&"requests=3i"));
+        assert!(fields.contains(&"failures=1i"));
+        assert!(fields.contains(&"response_time_average=20"));
+        assert!(fields.contains(&"p50=10i"));
+        assert!(fields.contains(&"p100=30i"));
+
+        // No average is emitted for endpoints without any response times.
+        assert!(
+            lines[1].starts_with("goose_request,method=POST,name=/ requests=0i,failures=0i,p50=0i")
+        );
+    }
+
+    #[test]
+    fn test_slack_blocks() {
+        let mut metrics = metrics::GooseMetrics {
+            users: 10,
+            ..Default::default()
+        };
+        for (index, name) in ["/a", "/b", "/c", "/d", "/e", "/f", "/<g>"]
+            .iter()
+            .enumerate()
+        {
+            let mut request = metrics::GooseRequestMetricAggregate::new(name, GooseMethod::Get, 0);
+            request.record_time(10 * (index as u64 + 1), false);
+            request.success_count = 1;
+            metrics.requests.insert(format!("GET {}", name), request);
+        }
+        metrics.requests.get_mut("GET /a").unwrap().fail_count = 1;
+
+        let output = build_slack_blocks(&metrics);
+        let payload: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let blocks = payload["blocks"].as_array().unwrap();
+        let types = blocks
+            .iter()
+            .map(|block| block["type"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(types, vec!["header", "section", "divider", "section"]);
+        assert_eq!(blocks[0]["text"]["type"], "plain_text");
+
+        let fields = blocks[1]["fields"].as_array().unwrap();
+        assert_eq!(fields[0]["text"], "*Requests*\n8");
+        assert_eq!(fields[1]["text"], "*Failures*\n1 (12.50%)");
+        assert_eq!(fields[2]["text"], "*95%ile*\n70 ms");
+        assert_eq!(fields[3]["text"], "*Users*\n10");
+
+        // The slowest endpoints are listed first, capped, and escaped.
+        let slowest = blocks[3]["text"]["text"].as_str().unwrap();
+        let lines = slowest.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1 + SLACK_MAX_ENDPOINTS + 1);
+        assert_eq!(lines[1], "• `GET /&lt;g&gt;`: 70 ms (1 requests)");
+        assert_eq!(lines[5], "• `GET /c`: 30 ms (1 requests)");
+        assert_eq!(lines[6], "_2 more endpoints not shown_");
+
+        // Without requests, only the header and summary are sent.
+        let output = build_slack_blocks(&metrics::GooseMetrics::default());
+        let payload: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(payload["blocks"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(0.5), "0.50");
+        assert_eq!(format_rate(f32::NAN), "0.00");
+        assert_eq!(format_rate(f32::INFINITY), "0.00");
+        assert_eq!(format_rate(f32::NEG_INFINITY), "0.00");
+
+        // A zero-duration load test renders rates of zero.
+        let (requests_per_second, failures_per_second) = metrics::per_second_calculations(0, 10, 1);
+        assert_eq!(format_rate(requests_per_second), "0.00");
+        assert_eq!(format_rate(failures_per_second), "0.00");
+    }
+
+    #[test]
+    fn test_report_sections() {
+        let data = vec![("2021-11-21 21:20:32".to_string(), 123)];
+        let graph = graph_rps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        let templates = || GooseReportTemplates {
+            raw_requests_template: "<tr><td>GET</td><td>/</td></tr>",
+            raw_responses_template: "<tr><td>GET</td><td>/</td></tr>",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: r#"<div class="tasks"></div>"#,
+            status_codes_template: r#"<div class="status_codes"></div>"#,
+            errors_template: r#"<div class="errors"></div>"#,
+            graph_rps_template: &graph,
+            graph_average_response_time_template: &graph,
+            graph_users_per_second: &graph,
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        let report = build_report("1", "", "", templates(), &GooseReportOptions::default());
+        assert_eq!(report.matches("echarts.init").count(), 3);
+        assert!(report.contains("echarts.min.js"));
+
+        // Disabling graphs removes every chart, but keeps the tables.
+        let options = GooseReportOptions {
+            sections: ReportSections {
+                show_graphs: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        assert!(!report.contains("echarts.init"));
+        assert!(!report.contains("echarts.min.js"));
+        assert!(report.contains("<h2>Request Metrics</h2>"));
+        assert!(report.contains("<h2>Response Time Metrics</h2>"));
+        assert!(report.contains("<h2>User Metrics</h2>"));
+        assert!(report.contains(r#"<div class="tasks">"#));
+
+        // Sections can be suppressed regardless of data.
+        let options = GooseReportOptions {
+            sections: ReportSections {
+                show_requests: false,
+                show_status_codes: false,
+                show_errors: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let report = build_report("1", "", "", templates(), &options);
+        assert!(!report.contains("<h2>Request Metrics</h2>"));
+        assert!(!report.contains(r#"<div class="status_codes">"#));
+        assert!(!report.contains(r#"<div class="errors">"#));
+        assert!(report.contains("<h2>Response Time Metrics</h2>"));
+        assert!(report.contains(r#"<div class="tasks">"#));
+    }
+
+    #[test]
+    fn test_rps_sparkline() {
+        let metric = RequestMetric {
+            number_of_requests: 10,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: "2.50".to_string(),
+            requests_per_second_value: 2.50,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", "/")
+        };
+        assert!(
+            !raw_request_metrics_row(metric.clone(), &GooseReportOptions::default())
+                .contains("<svg")
+        );
+
+        let row = raw_request_metrics_row(
+            RequestMetric {
+                requests_per_second_series: Some(vec![1, 4, 2, 3]),
+                ..metric
+            },
+            &GooseReportOptions::default(),
+        );
+        assert!(row.contains(r#"<td>2.50 <svg class="sparkline""#));
+        let points = row.split(r#"points=""#).nth(1).unwrap();
+        let points = points.split('"').next().unwrap();
+        assert_eq!(points, "0.0,12.0 20.0,0.0 40.0,8.0 60.0,4.0");
+        assert_eq!(points.split(' ').count(), 4);
+    }
+
+    #[test]
+    fn test_aggregate_graph_data() {
+        let counts: Vec<u32> = (1..=60).collect();
+        let aggregated = aggregate_graph_data(&counts, 5, GraphAggregation::Sum);
+        assert_eq!(aggregated.len(), 12);
+        assert_eq!(aggregated[0], 1 + 2 + 3 + 4 + 5);
+        assert_eq!(aggregated[11], 56 + 57 + 58 + 59 + 60);
+        assert_eq!(aggregated.iter().sum::<u32>(), counts.iter().sum::<u32>());
+
+        let averages = aggregate_graph_data(&counts, 5, GraphAggregation::Average);
+        assert_eq!(averages.len(), 12);
+        assert_eq!(averages[0], 3);
+        assert_eq!(averages[11], 58);
+
+        // A trailing partial interval is aggregated on its own.
+        let users: Vec<usize> = vec![2, 4, 6, 8, 10, 12, 20];
+        assert_eq!(
+            aggregate_graph_data(&users, 3, GraphAggregation::Average),
+            vec![4, 10, 20]
+        );
+
+        // Intervals of 0 and 1 leave the data unchanged.
+        assert_eq!(
+            aggregate_graph_data(&users, 0, GraphAggregation::Sum),
+            users
+        );
+        assert_eq!(
+            aggregate_graph_data(&users, 1, GraphAggregation::Sum),
+            users
+        );
+    }
+
+    #[test]
+    fn test_csv_bom() {
+        let metrics = vec![RequestMetric {
+            number_of_requests: 3,
+            number_of_failures: 1,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: "1.50".to_string(),
+            failures_per_second: "0.50".to_string(),
+            requests_per_second_value: 1.50,
+            failures_per_second_value: 0.50,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", "/caf\u{e9}")
+        }];
+
+        let csv = request_metrics_csv(&metrics, false);
+        assert!(csv.starts_with("method,name,"));
+        assert!(csv.contains("\"GET\",\"/caf\u{e9}\",3,1,"));
+
+        let csv = request_metrics_csv(&metrics, true);
+        assert_eq!(&csv.as_bytes()[..3], &[0xEF, 0xBB, 0xBF]);
+        assert!(csv[3..].starts_with("method,name,"));
+
+        let csv = response_metrics_csv(&[], true);
+        assert_eq!(&csv.as_bytes()[..3], &[0xEF, 0xBB, 0xBF]);
+        assert!(csv[3..].starts_with("method,name,average,50%ile,"));
+    }
+
+    #[test]
+    fn test_request_metrics_csv_rates() {
+        let metric = RequestMetric {
+            number_of_requests: 2469,
+            number_of_failures: 1,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: format_rate(1234.5),
+            failures_per_second: format_rate(0.5),
+            requests_per_second_value: 1234.5,
+            failures_per_second_value: 0.5,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", "/")
+        };
+
+        // Rates are exported as raw numbers, and formatted for display in the html report.
+        let csv = request_metrics_csv(std::slice::from_ref(&metric), false);
+        assert!(csv.ends_with(",1234.5,0.5\n"));
+        let row = raw_request_metrics_row(metric, &GooseReportOptions::default());
+        assert!(row.contains("<td>1234.50</td>"));
+    }
+
+    #[test]
+    fn test_response_time_heatmap() {
+        let mut first = BTreeMap::new();
+        first.insert(5, 2);
+        first.insert(8, 1);
+        first.insert(40, 4);
+        let mut second = BTreeMap::new();
+        second.insert(200, 3);
+        let data = vec![
+            ("2021-11-21 21:20:32".to_string(), first),
+            ("2021-11-21 21:20:33".to_string(), second),
+        ];
+
+        let markup = graph_response_time_heatmap_template(
+            &data,
+            &[10, 50],
+            Palette::default(),
+            &ReportStrings::default(),
+        );
+        assert!(markup.contains("type: 'heatmap'"));
+        assert!(markup.contains(r#"data: ["0-10 ms","11-50 ms","51+ ms"]"#));
+        assert!(markup.contains("data: [[0,0,3],[0,1,4],[1,2,3]]"));
+        assert!(markup.contains("max: 4,"));
+    }
+
+    #[test]
+    fn test_fine_latency_buckets() {
+        assert_eq!(fine_latency_buckets(&[10, 50]), vec![0, 2, 10, 50]);
+        assert_eq!(fine_latency_buckets(&[1, 2, 5]), vec![0, 2, 5]);
+
+        // Sub-millisecond responses are recorded as 0 ms.
+        let mut histogram = BTreeMap::new();
+        histogram.insert(0, 5);
+        histogram.insert(1, 2);
+        histogram.insert(8, 1);
+        let data = vec![("2021-11-21 21:20:32".to_string(), histogram)];
+
+        let markup = graph_response_time_heatmap_template(
+            &data,
+            &fine_latency_buckets(&[10]),
+            Palette::default(),
+            &ReportStrings::default(),
+        );
+        assert!(markup.contains(r#"data: ["<1 ms","1-2 ms","3-10 ms","11+ ms"]"#));
+        assert!(markup.contains("data: [[0,0,5],[0,1,2],[0,2,1]]"));
+
+        // Without the fine buckets they all crowd into the first bucket.
+        let markup = graph_response_time_heatmap_template(
+            &data,
+            &[10],
+            Palette::default(),
+            &ReportStrings::default(),
+        );
+        assert!(markup.contains(r#"data: ["0-10 ms","11+ ms"]"#));
+        assert!(markup.contains("data: [[0,0,8]]"));
+    }
+
+    #[test]
+    fn test_response_time_threshold() {
+        let data = vec![
+            ("2021-11-21 21:20:32".to_string(), 123),
+            ("2021-11-21 21:20:33".to_string(), 611),
+        ];
+
+        let graph = graph_average_response_time_template(
+            &data,
+            GraphPhases::default(),
+            None,
+            None,
+            &GooseReportOptions {
+                slow_request_threshold: Some(500),
+                ..Default::default()
+            },
+        );
+        assert!(graph.contains(r#""markLine":{"data":[{"yAxis":500}],"label":{"formatter":"SLO"}"#));
+
+        let graph = graph_average_response_time_template(
+            &data,
+            GraphPhases::default(),
+            None,
+            None,
+            &GooseReportOptions::default(),
+        );
+        assert!(!graph.contains("markLine"));
+    }
+
+    #[test]
+    fn test_graph_axis_decimals() {
+        let data = vec![
+            ("2021-11-21 21:20:32".to_string(), 12),
+            ("2021-11-21 21:20:33".to_string(), 13),
+        ];
+        let graph = |axis_decimals| {
+            graph_average_response_time_template(
+                &data,
+                GraphPhases::default(),
+                None,
+                None,
+                &GooseReportOptions {
+                    graph_axis_decimals: axis_decimals,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert!(!graph(None).contains("axisLabel"));
+        assert!(graph(Some(1)).contains(
+            "});\n                    myChart.setOption({ yAxis: { axisLabel: { formatter: function (value) { return parseFloat(value.toFixed(1)); } } } });"
+        ));
+        assert!(graph(Some(0)).contains("value.toFixed(0)"));
+    }
+
+    #[test]
+    fn test_confidence_band() {
+        let data = vec![
+            ("2021-11-21 21:20:32".to_string(), 120),
+            ("2021-11-21 21:20:33".to_string(), 150),
+        ];
+        let lower = vec![
+            ("2021-11-21 21:20:32".to_string(), 100),
+            ("2021-11-21 21:20:33".to_string(), 110),
+        ];
+        let upper = vec![
+            ("2021-11-21 21:20:32".to_string(), 140),
+            ("2021-11-21 21:20:33".to_string(), 190),
+        ];
+        let graph = |confidence_band| {
+            graph_average_response_time_template(
+                &data,
+                GraphPhases::default(),
+                confidence_band,
+                None,
+                &GooseReportOptions::default(),
+            )
+        };
+
+        let with_band = graph(Some((&lower, &upper)));
+        assert_eq!(with_band.matches(r#""type":"line""#).count(), 3);
+        assert_eq!(with_band.matches(r#""stack":"confidence-band""#).count(), 2);
+        assert!(with_band
+            .contains(r#""data":[["2021-11-21 21:20:32",100],["2021-11-21 21:20:33",110]]"#));
+        assert!(
+            with_band.contains(r#""data":[["2021-11-21 21:20:32",40],["2021-11-21 21:20:33",80]]"#)
+        );
+
+        let without_band = graph(None);
+        assert_eq!(without_band.matches(r#""type":"line""#).count(), 1);
+        assert!(!without_band.contains("confidence-band"));
+    }
+
+    #[test]
+    fn test_response_time_envelope() {
+        let data = vec![
+            ("2021-11-21 21:20:32".to_string(), 120),
+            ("2021-11-21 21:20:33".to_string(), 150),
+        ];
+        let minimum = vec![
+            ("2021-11-21 21:20:32".to_string(), 20),
+            ("2021-11-21 21:20:33".to_string(), 35),
+        ];
+        let maximum = vec![
+            ("2021-11-21 21:20:32".to_string(), 900),
+            ("2021-11-21 21:20:33".to_string(), 400),
+        ];
+        let graph = |envelope| {
+            graph_average_response_time_template(
+                &data,
+                GraphPhases::default(),
+                None,
+                envelope,
+                &GooseReportOptions::default(),
+            )
+        };
+
+        let with_envelope = graph(Some((&minimum, &maximum)));
+        assert_eq!(with_envelope.matches(r#""type":"line""#).count(), 3);
+        assert_eq!(with_envelope.matches(r#""stack":"envelope""#).count(), 2);
+        assert!(with_envelope
+            .contains(r#""data":[["2021-11-21 21:20:32",20],["2021-11-21 21:20:33",35]]"#));
+        assert!(with_envelope
+            .contains(r#""data":[["2021-11-21 21:20:32",880],["2021-11-21 21:20:33",365]]"#));
+        // The envelope is drawn behind the average line.
+        assert!(
+            with_envelope.find(r#""name":"Maximum""#) < with_envelope.find(r#""symbol":"circle""#)
+        );
+        assert!(!with_envelope.contains("confidence-band"));
+
+        let without_envelope = graph(None);
+        assert_eq!(without_envelope.matches(r#""type":"line""#).count(), 1);
+        assert!(!without_envelope.contains("envelope"));
+    }
+
+    #[test]
+    fn test_rate_unit() {
+        assert_eq!(format_rate(RateUnit::PerSecond.scale(0.5)), "0.50");
+        assert_eq!(format_rate(RateUnit::PerMinute.scale(0.5)), "30.00");
+
+        let options = GooseReportOptions {
+            rate_unit: RateUnit::PerMinute,
+            ..Default::default()
+        };
+        let templates = GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+        let report = build_report("", "", "", templates, &options);
+        assert!(report.contains("<th>RPM</th>"));
+        assert!(report.contains("<th>Failures/min</th>"));
+        assert!(!report.contains("<th>RPS</th>"));
+
+        let data = vec![("2021-11-21 21:20:32".to_string(), 2)];
+        let graph = graph_rps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions {
+                rate_unit: RateUnit::PerMinute,
+                ..Default::default()
+            },
+        );
+        assert!(graph.contains(r#""name":"Requests per minute""#));
+        assert!(graph.contains(r#""data":[["2021-11-21 21:20:32",120]]"#));
+    }
+
+    #[test]
+    fn test_verdict() {
+        let mut metrics = metrics::GooseMetrics {
+            duration: 10,
+            ..Default::default()
+        };
+        let mut request = metrics::GooseRequestMetricAggregate::new("/", GooseMethod::Get, 0);
+        for time in 1..=100 {
+            request.record_time(time, false);
+        }
+        request.success_count = 99;
+        request.fail_count = 1;
+        metrics.requests.insert("GET /".to_string(), request);
+
+        let verdict = evaluate_assertions(
+            &metrics,
+            &[
+                ReportAssertion::MaxResponseTimePercentile(0.95, 300),
+                ReportAssertion::MaxFailurePercentage(2.0),
+                ReportAssertion::MinRequestsPerSecond(5.0),
+            ],
+        );
+        assert!(verdict.passed);
+        assert_eq!(verdict.results.len(), 3);
+        assert!(verdict.results.iter().all(|result| result.passed));
+        let markup = verdict_template(&verdict, &ReportStrings::default());
+        assert!(markup.contains(r#"<div class="verdict pass">"#));
+        assert!(markup.contains("<h2>PASS</h2>"));
+        assert!(!markup.contains("not met"));
+
+        let verdict = evaluate_assertions(
+            &metrics,
+            &[
+                ReportAssertion::MaxResponseTimePercentile(0.95, 50),
+                ReportAssertion::MaxFailurePercentage(1.0),
+                ReportAssertion::MinRequestsPerSecond(5.0),
+            ],
+        );
+        assert!(!verdict.passed);
+        assert_eq!(verdict.results[0].description, "p95 < 50 ms");
+        assert_eq!(verdict.results[0].actual, "95 ms");
+        assert!(!verdict.results[0].passed);
+        assert_eq!(verdict.results[1].description, "failures < 1%");
+        assert_eq!(verdict.results[1].actual, "1.00%");
+        assert!(!verdict.results[1].passed);
+        assert!(verdict.results[2].passed);
+        let markup = verdict_template(&verdict, &ReportStrings::default());
+        assert!(markup.contains(r#"<div class="verdict fail">"#));
+        assert!(markup.contains("<h2>FAIL</h2>"));
+        assert!(markup.contains("<td>p95 < 50 ms</td>"));
+        assert_eq!(markup.matches("<td>not met</td>").count(), 2);
+
+        let export = export_timeseries_json(
+            &[],
+            &[],
+            &[],
+            &[],
+            &[],
+            ReportMetadata::new("", "", ""),
+            Some(verdict),
+            &BTreeMap::new(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&export).unwrap();
+        assert_eq!(value["verdict"]["passed"], false);
+        let assertions = value["verdict"]["assertions"].as_array().unwrap();
+        assert_eq!(assertions.len(), 3);
+        assert_eq!(assertions[0]["description"], "p95 < 50 ms");
+        assert_eq!(assertions[0]["threshold"], 50.0);
+        assert_eq!(assertions[0]["value"], 95.0);
+        assert_eq!(assertions[0]["passed"], false);
+        assert_eq!(assertions[2]["passed"], true);
+    }
+
+    #[test]
+    fn test_table_density() {
+        let report = build_empty_report("", "", "", &GooseReportOptions::default());
+        assert!(report.contains("height: 30px;"));
+        assert!(report.contains("tr:nth-child(even)"));
+
+        let options = GooseReportOptions {
+            table_density: TableDensity::Compact,
+            no_table_striping: true,
+            ..Default::default()
+        };
+        let report = build_empty_report("", "", "", &options);
+        assert!(report.contains("height: 20px;"));
+        assert!(!report.contains("height: 30px;"));
+        assert!(!report.contains("nth-child"));
+
+        let options = GooseReportOptions {
+            table_density: TableDensity::Comfortable,
+            ..Default::default()
+        };
+        let report = build_empty_report("", "", "", &options);
+        assert!(report.contains("height: 40px;"));
+        assert!(report.contains("padding: 6px;"));
+    }
+
+    #[test]
+    fn test_scenario_reports() {
+        let request = |name: &str, requests, failures| RequestMetric {
+            number_of_requests: requests,
+            number_of_failures: failures,
+            response_time_average: "10.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 20,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.0,
+            apdex: "1.00".to_string(),
+            traffic_share: 25.0,
+            ..request_metric("GET", name)
+        };
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 10);
+        let response =
+            |name: &str| get_response_metric("GET", name, &response_times, 10, 10.0, 10, 10);
+        let scenarios = vec![
+            ScenarioMetrics {
+                name: "Checkout".to_string(),
+                requests: vec![request("/cart", 10, 1), request("/checkout", 10, 0)],
+                responses: vec![response("/cart"), response("/checkout")],
+            },
+            ScenarioMetrics {
+                name: "Browse & search".to_string(),
+                requests: vec![request("/search", 20, 2)],
+                responses: vec![response("/search")],
+            },
+        ];
+
+        let reports = build_scenario_reports("10", "", "", &scenarios, &Default::default());
+        let file_names = reports
+            .iter()
+            .map(|(file_name, _)| file_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            file_names,
+            vec![
+                "index.html",
+                "scenario-checkout.html",
+                "scenario-browse-search.html"
+            ]
+        );
+
+        let index = &reports[0].1;
+        assert!(index.contains(
+            r#"<li><a href="scenario-checkout.html">Checkout</a>: 20 requests, 1 failed</li>"#
+        ));
+        assert!(index.contains(
+            r#"<li><a href="scenario-browse-search.html">Browse &amp; search</a>: 20 requests, 2 failed</li>"#
+        ));
+
+        // Each report only has its own scenario's requests, and an aggregated row.
+        let checkout = &reports[1].1;
+        assert!(checkout.contains("<title>Goose Attack Report: Checkout</title>"));
+        assert!(checkout.contains("<td>/checkout</td>"));
+        assert!(checkout.contains("<td>Aggregated</td>"));
+        assert!(!checkout.contains("<td>/search</td>"));
+        let browse = &reports[2].1;
+        assert!(browse.contains("<td>/search</td>"));
+        assert!(!browse.contains("<td>/checkout</td>"));
+
+        // Scenarios with the same file name are numbered.
+        let mut file_names = HashSet::new();
+        assert_eq!(
+            scenario_file_name("Checkout", &mut file_names),
+            "scenario-checkout.html"
+        );
+        assert_eq!(
+            scenario_file_name("checkout!", &mut file_names),
+            "scenario-checkout-2.html"
+        );
+        assert_eq!(scenario_file_name("???", &mut file_names), "scenario.html");
+    }
+
+    #[test]
+    fn test_minify_html() {
+        let scenarios = vec![ScenarioMetrics {
+            name: "Checkout".to_string(),
+            requests: Vec::new(),
+            responses: Vec::new(),
+        }];
+        let reports = build_scenario_reports("10", "", "", &scenarios, &Default::default());
+        let report = &reports[1].1;
+        let minified = minify_html(report);
+        assert!(minified.len() < report.len());
+        assert!(minified.contains("<title>Goose Attack Report: Checkout</title>"));
+        assert!(minified.contains("<h2>Request Metrics</h2>"));
+        assert!(!minified.contains("\n    "));
+
+        let error = metrics::GooseErrorMetricAggregate {
+            method: GooseMethod::Get,
+            name: "/".to_string(),
+            error: "error sending request\n    caused by: connection refused".to_string(),
+            occurrences: 2,
+        };
+        let html = format!(
+            r#"<table>
+            <tbody>
+                {}
+            </tbody>
+        </table>
+        <script>
+            // Don't touch literals.
+            var label = 'Requests:   {{c}}';
+            var template = `first
+                second`;
+        </script>"#,
+            error_row(&error, &GooseReportOptions::default())
+        );
+        let minified = minify_html(&html);
+        assert!(minified.starts_with("<table><tbody><tr>"));
+        assert!(minified
+            .contains("<pre>error sending request\n    caused by: connection refused</pre>"));
+        assert!(minified.ends_with(
+            "<script>// Don't touch literals.\nvar label = 'Requests:   {c}';\nvar template = `first\n                second`;\n</script>"
+        ));
+    }
+
+    #[test]
+    fn test_phased_report() {
+        let graph = graph_rps_template(&[], GraphPhases::default(), &GooseReportOptions::default());
+        let templates = |requests| GooseReportTemplates {
+            raw_requests_template: requests,
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template: "",
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+        let phases = vec![
+            ReportPhase {
+                label: "Warm-up",
+                templates: GooseReportTemplates {
+                    graph_rps_template: &graph,
+                    ..templates("<tr><td>warm-up row</td></tr>")
+                },
+            },
+            ReportPhase {
+                label: "Spike",
+                templates: GooseReportTemplates {
+                    graph_rps_template: &graph,
+                    ..templates("<tr><td>spike row</td></tr>")
+                },
+            },
+        ];
+
+        let report = build_phased_report(
+            "",
+            "",
+            "",
+            phases,
+            templates("<tr><td>overall row</td></tr>"),
+            &GooseReportOptions::default(),
+        );
+        let warm_up = report
+            .find(r#"<h2 class="phase-title">Warm-up</h2>"#)
+            .unwrap();
+        let spike = report
+            .find(r#"<h2 class="phase-title">Spike</h2>"#)
+            .unwrap();
+        let overall = report
+            .find(r#"<h2 class="phase-title">Overall</h2>"#)
+            .unwrap();
+        assert!(warm_up < spike && spike < overall);
+        assert_eq!(report.matches("<h2>Request Metrics</h2>").count(), 3);
+        let warm_up_row = report.find("warm-up row").unwrap();
+        let spike_row = report.find("spike row").unwrap();
+        assert!(warm_up < warm_up_row && warm_up_row < spike);
+        assert!(spike < spike_row && spike_row < overall);
+
+        // Each phase's graphs have unique ids.
+        assert!(report.contains(r#"id="phase-0-graph-rps""#));
+        assert!(report.contains("getElementById('phase-1-graph-rps')"));
+    }
+
+    #[test]
+    fn test_error_details() {
+        let short = metrics::GooseErrorMetricAggregate {
+            method: GooseMethod::Get,
+            name: "/".to_string(),
+            error: "404 Not Found".to_string(),
+            occurrences: 2,
+        };
+        let row = error_row(&short, &GooseReportOptions::default());
+        assert!(row.contains(r#"<td colspan="4">404 Not Found</strong></td>"#));
+        assert!(!row.contains("<details>"));
+
+        // Short errors are escaped too.
+        let markup = metrics::GooseErrorMetricAggregate {
+            error: "400: <script>alert(1)</script>".to_string(),
+            ..short.clone()
+        };
+        let row = error_row(&markup, &GooseReportOptions::default());
+        assert!(row.contains("400: &lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!row.contains("<script>"));
+
+        let long = metrics::GooseErrorMetricAggregate {
+            error: "error sending request\ncaused by: <connection refused>".to_string(),
+            ..short
+        };
+        let row = error_row(&long, &GooseReportOptions::default());
+        assert!(row.contains("<details><summary>error sending request</summary>"));
+        assert!(row.contains(
+            "<pre>error sending request\ncaused by: &lt;connection refused&gt;</pre></details>"
+        ));
+
+        // A 2KB server error body is truncated, with the full escaped body in the expander.
+        let body = format!("500 Internal Server Error: <p>{}</p>", "x".repeat(2048));
+        let large = metrics::GooseErrorMetricAggregate {
+            error: body.clone(),
+            ..long
+        };
+        let options = GooseReportOptions {
+            max_error_length: Some(40),
+            ..Default::default()
+        };
+        let row = error_row(&large, &options);
+        assert!(row.contains(&format!(
+            "<summary>{}&hellip;</summary>",
+            escape_html(&body[..40])
+        )));
+        assert!(row.contains(&format!("<pre>{}</pre>", escape_html(&body))));
+    }
+
+    #[test]
+    fn test_graph_sampling_threshold() {
+        let data = (0..10)
+            .map(|second| (format!("2021-11-21 21:20:{:02}", second), second))
+            .collect::<Vec<_>>();
+        let graph = graph_eps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(!graph.contains("sampling"));
+
+        let data = (0..1000)
+            .map(|second| (format!("2021-11-21 21:20:{:04}", second), second))
+            .collect::<Vec<_>>();
+        let graph = graph_eps_template(
+            &data,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(graph.contains(r#""sampling":"lttb""#));
+    }
+
+    #[test]
+    fn test_export_errors_json() {
+        // Errors as recorded by Goose for unexpected status codes.
+        let service_unavailable = format!("{}: GET /", http::StatusCode::SERVICE_UNAVAILABLE);
+        let not_found = format!("{}: GET /missing", http::StatusCode::NOT_FOUND);
+        let mut errors = BTreeMap::new();
+        for (error, occurrences) in [
+            (service_unavailable.as_str(), 3),
+            ("error sending request GET /about: connection refused", 12),
+            (not_found.as_str(), 7),
+            ("operation timed out", 1),
+        ] {
+            let mut metric = metrics::GooseErrorMetricAggregate::new(
+                GooseMethod::Get,
+                "/".to_string(),
+                error.to_string(),
+            );
+            metric.occurrences = occurrences;
+            errors.insert(error.to_string(), metric);
+        }
+
+        let export = export_timeseries_json(
+            &[],
+            &[],
+            &[],
+            &[],
+            &[],
+            ReportMetadata::new("", "", ""),
+            None,
+            &errors,
+        );
+        let value: serde_json::Value = serde_json::from_str(&export).unwrap();
+        assert_eq!(
+            value["errors"],
+            json!([
+                {
+                    "error": "error sending request GET /about: connection refused",
+                    "occurrences": 12,
+                    "category": "connection",
+                },
+                {
+                    "error": "404 Not Found: GET /missing",
+                    "occurrences": 7,
+                    "category": "client_error",
+                },
+                {
+                    "error": "503 Service Unavailable: GET /",
+                    "occurrences": 3,
+                    "category": "server_error",
+                },
+                { "error": "operation timed out", "occurrences": 1, "category": "timeout" },
+            ])
+        );
+
+        assert_eq!(
+            error_category(&format!("{}: GET /", http::StatusCode::FOUND)),
+            "http_status"
+        );
+        assert_eq!(error_category("custom failure"), "other");
+    }
+
+    #[test]
+    fn test_export_timeseries_json() {
+        let rps = vec![
+            ("2021-11-21 21:20:32".to_string(), 12),
+            ("2021-11-21 21:20:33".to_string(), 15),
+        ];
+        let users = vec![("2021-11-21 21:20:32".to_string(), 3)];
+
+        let export = export_timeseries_json(
+            &rps,
+            &[],
+            &[],
+            &users,
+            &[],
+            ReportMetadata::new(
+                "http://localhost/",
+                "3",
+                "2021-11-21 21:20:30 - 2021-11-21 21:20:33",
+            )
+            .with_phases(
+                Some(Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 30).unwrap()),
+                Some(Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap()),
+                None,
+                None,
+            ),
+            None,
+            &BTreeMap::new(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&export).unwrap();
+        assert!(value.get("verdict").is_none());
+        assert_eq!(value["errors"], json!([]));
+        assert_eq!(value["metadata"]["title"], "Goose Attack Report");
+        assert_eq!(value["metadata"]["hosts"], "http://localhost/");
+        assert_eq!(value["metadata"]["users"], "3");
+        assert_eq!(
+            value["metadata"]["report_range"],
+            "2021-11-21 21:20:30 - 2021-11-21 21:20:33"
+        );
+        assert_eq!(value["metadata"]["version"], env!("CARGO_PKG_VERSION"));
+        let generated = value["metadata"]["generated"].as_str().unwrap();
+        assert!(DateTime::parse_from_rfc3339(generated).is_ok());
+        assert_eq!(value["metadata"]["starting"], "2021-11-21 21:20:30");
+        assert_eq!(value["metadata"]["started"], "2021-11-21 21:20:32");
+        assert!(value["metadata"]["stopping"].is_null());
+
+        let series: Vec<(String, u32)> =
+            serde_json::from_value(value["series"]["requests_per_second"].clone()).unwrap();
+        assert_eq!(series, rps);
+        let series: Vec<(String, usize)> =
+            serde_json::from_value(value["series"]["active_users"].clone()).unwrap();
+        assert_eq!(series, users);
+        assert_eq!(value["series"]["errors_per_second"], json!([]));
+    }
+
+    #[test]
+    fn test_truncate_rows() {
+        let errors = (0..10_000)
+            .map(|index| metrics::GooseErrorMetricAggregate {
+                method: GooseMethod::Get,
+                name: format!("/{}", index),
+                error: format!("error {}", index),
+                occurrences: index % 1_000,
+            })
+            .collect::<Vec<_>>();
+        let total = errors.len();
+
+        let errors = truncate_rows(errors, Some(25), |error| error.occurrences);
+        assert_eq!(errors.len(), 25);
+        assert!(errors.iter().all(|error| error.occurrences >= 997));
+        // The original order is preserved.
+        assert_eq!(errors[0].name, "/997");
+        assert_eq!(errors[1].name, "/998");
+
+        let mut rows = errors
+            .iter()
+            .map(|error| error_row(error, &GooseReportOptions::default()))
+            .collect::<Vec<_>>();
+        rows.push(truncated_rows_notice(
+            errors.len(),
+            total,
+            5,
+            &ReportStrings::default(),
+        ));
+        let table = errors_template(&rows.join("\n"), "", None, &ReportStrings::default());
+        // One header row and 25 error rows.
+        assert_eq!(table.matches("<tr>").count(), 1 + 25);
+        assert!(table.contains("Showing top 25 of 10,000."));
+
+        // Nothing is truncated without a limit, or below it.
+        assert_eq!(
+            truncate_rows(vec![1, 2, 3], None, |row| *row),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            truncate_rows(vec![1, 2, 3], Some(3), |row| *row),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            truncated_rows_notice(3, 3, 5, &ReportStrings::default()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_graph_overview_template() {
+        let rps = vec![("2021-11-21 21:20:32".to_string(), 12)];
+        let eps = vec![("2021-11-21 21:20:32".to_string(), 1)];
+        let response_times = vec![("2021-11-21 21:20:32".to_string(), 105)];
+        let users = vec![("2021-11-21 21:20:32".to_string(), 5)];
+
+        let markup = graph_overview_template(
+            &rps,
+            &eps,
+            &response_times,
+            &users,
+            GraphPhases::default(),
+            &GooseReportOptions::default(),
+        );
+        assert!(markup.contains(r#"<div id="graph-overview""#));
+        assert!(markup.contains("axisPointer: { link: [{ xAxisIndex: 'all' }] },"));
+        assert_eq!(markup.matches("{ left: 70, right: 30, top: ").count(), 4);
+        assert_eq!(markup.matches("type: 'time', gridIndex: ").count(), 4);
+        assert!(markup.contains("xAxisIndex: [0, 1, 2, 3]"));
+        assert!(markup.contains("yAxisIndex: 3, symbol: 'none'"));
+        assert!(markup.contains(r#"data: [["2021-11-21 21:20:32",105]]"#));
+        // Only the bottom subplot labels the shared time axis.
+        assert_eq!(markup.matches("axisLabel: { show: true }").count(), 1);
+
+        // Each subplot is colored by its own role.
+        let (success, _) = ColorRole::Success.colors(Palette::default());
+        let (danger, _) = ColorRole::Danger.colors(Palette::default());
+        assert!(markup.contains(&format!(
+            "color: ['{success}', '{danger}', '{success}', '{success}'],",
+            success = success,
+            danger = danger,
+        )));
+        let options = GooseReportOptions {
+            style: ReportStyle {
+                users: ColorRole::Warning,
+                ..Default::default()
+            },
+            rate_unit: RateUnit::PerMinute,
+            hide_ramp_shading: vec![ReportGraph::ErrorsPerSecond].into_iter().collect(),
+            ..Default::default()
+        };
+        let time = |second| {
+            Local
+                .with_ymd_and_hms(2021, 11, 21, 21, 20, second)
+                .unwrap()
+        };
+        let phases = GraphPhases {
+            starting: Some(time(30)),
+            started: Some(time(32)),
+            warmup: Some((time(32), time(34))),
+            ..Default::default()
+        };
+        let markup = graph_overview_template(&rps, &eps, &response_times, &users, phases, &options);
+        let (warning, _) = ColorRole::Warning.colors(Palette::default());
+        assert!(markup.contains(&format!("lineStyle: {{ color: '{}' }}", warning)));
+        assert!(markup.contains(r#"name: "Requests per minute""#));
+        assert!(markup.contains(r#"data: [["2021-11-21 21:20:32",720]]"#));
+        // The errors subplot hides the ramp shading, but not the warmup.
+        assert_eq!(markup.matches(r#""name":"Starting""#).count(), 3);
+        assert_eq!(markup.matches(r#""name":"Warmup""#).count(), 4);
+    }
+
+    #[test]
+    fn test_report_range_datetime_format() {
+        let start = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap();
+        let end = Local.with_ymd_and_hms(2021, 11, 21, 22, 21, 35).unwrap();
+
+        assert_eq!(
+            report_range_row("Running", &start, &end, (1, 1, 3), &LocaleConfig::default(), &ReportStrings::default()),
+            "<p>Running: <span>2021-11-21 21:20:32 - 2021-11-21 22:21:35 (Duration: 01:01:03)</span></p>"
+        );
+        assert_eq!(
+            report_range_row(
+                "Running",
+                &start,
+                &end,
+                (1, 1, 3),
+                &LocaleConfig {
+                    datetime_format: "%d/%m/%Y %H:%M".to_string(),
+                    ..Default::default()
+                },
+                &ReportStrings::default()
+            ),
+            "<p>Running: <span>21/11/2021 21:20 - 21/11/2021 22:21 (Duration: 01:01:03)</span></p>"
+        );
+    }
+
+    #[test]
+    fn test_locale_config() {
+        let default = LocaleConfig::default();
+        assert_eq!(default.localize("1,234.50 ms"), "1,234.50 ms");
+        assert_eq!(default.format_number(1_234_567), "1,234,567");
+
+        let german = LocaleConfig {
+            decimal_separator: ',',
+            thousands_separator: '.',
+            datetime_format: "%d.%m.%Y %H:%M:%S".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(german.localize("1,234.50"), "1.234,50");
+        assert_eq!(german.localize("3 (30.5%), 1.2.3"), "3 (30,5%), 1.2.3");
+        assert_eq!(german.format_number(1_234_567), "1.234.567");
 
-/// Build a users per second graph.
-pub fn graph_users_per_second_template(
-    active_users: &[(String, usize)],
-    starting: Option<DateTime<Local>>,
-    started: Option<DateTime<Local>>,
-    stopping: Option<DateTime<Local>>,
-    stopped: Option<DateTime<Local>>,
-) -> String {
-    Graph::new(
-        "graph-active-users",
-        "Active users #",
-        active_users,
-        starting,
-        started,
-        stopping,
-        stopped,
-    )
-    .generate_markup()
-}
+        let indian = LocaleConfig {
+            number_system: NumberSystem::Indian,
+            ..Default::default()
+        };
+        assert_eq!(indian.format_number(1_234_567), "12,34,567");
+        assert_eq!(indian.localize("1,234,567.89"), "12,34,567.89");
 
-/// Build a tasks per second graph.
-pub fn graph_tasks_per_second_template<T: Serialize>(
-    tps: &[(String, T)],
-    starting: Option<DateTime<Local>>,
-    started: Option<DateTime<Local>>,
-    stopping: Option<DateTime<Local>>,
-    stopped: Option<DateTime<Local>>,
-) -> String {
-    Graph::new(
-        "graph-tps",
-        "Tasks #",
-        tps,
-        starting,
-        started,
-        stopping,
-        stopped,
-    )
-    .generate_markup()
-}
+        // A single locale affects both the number cells and the timestamps.
+        let options = GooseReportOptions {
+            locale: german.clone(),
+            ..Default::default()
+        };
+        let row = raw_request_metrics_row(
+            RequestMetric {
+                number_of_requests: 10,
+                response_time_average: "1234.50".to_string(),
+                response_time_minimum: 1,
+                response_time_maximum: 2_500,
+                requests_per_second: "0.50".to_string(),
+                requests_per_second_value: 0.5,
+                apdex: "1.00".to_string(),
+                traffic_share: 100.0,
+                ..request_metric("GET", "/")
+            },
+            &options,
+        );
+        assert!(row.contains("<td>1234,50</td>"));
+        assert!(row.contains("<td>2500</td>"));
+        assert!(row.contains(r#"data-rps="0.50">10</td>"#));
 
-/// Build the html report.
-pub fn build_report(
-    users: &str,
-    report_range: &str,
-    hosts: &str,
-    templates: GooseReportTemplates,
-) -> String {
-    let pkg_name = env!("CARGO_PKG_NAME");
-    let pkg_version = env!("CARGO_PKG_VERSION");
+        let start = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap();
+        let end = Local.with_ymd_and_hms(2021, 11, 21, 22, 21, 35).unwrap();
+        assert_eq!(
+            report_range_row("Running", &start, &end, (1, 1, 3), &options.locale, &ReportStrings::default()),
+            "<p>Running: <span>21.11.2021 21:20:32 - 21.11.2021 22:21:35 (Duration: 01:01:03)</span></p>"
+        );
+    }
 
-    format!(
-        r#"<!DOCTYPE html>
-<html>
-<head>
-    <title>Goose Attack Report</title>
-    <style>
-        .container {{
-            width: 1000px;
-            margin: 0 auto;
-            padding: 10px;
-            background: #173529;
-            font-family: Arial, Helvetica, sans-serif;
-            font-size: 14px;
-            color: #fff;
-        }}
+    #[test]
+    fn test_apdex() {
+        // 60 satisfied, 30 tolerating and 10 frustrated responses.
+        let mut response_times = BTreeMap::new();
+        response_times.insert(100, 40);
+        response_times.insert(500, 20);
+        response_times.insert(1_000, 20);
+        response_times.insert(2_000, 10);
+        response_times.insert(3_000, 10);
+        assert_eq!(apdex(&response_times, 500), Some(0.75));
+        assert_eq!(format_apdex(apdex(&response_times, 500)), "0.75");
 
-        .info span{{
-            color: #b3c3bc;
-        }}
+        // With a larger target every response is satisfying.
+        assert_eq!(apdex(&response_times, 3_000), Some(1.0));
+        assert_eq!(apdex(&BTreeMap::new(), 500), None);
+        assert_eq!(format_apdex(None), "");
+    }
 
-        table {{
-            border-collapse: collapse;
-            text-align: center;
-            width: 100%;
-        }}
+    #[test]
+    fn test_sla_budget_used() {
+        // 1 failure in 2,000 requests is 0.05%, half of a 0.1% budget.
+        assert_eq!(sla_budget_used(2_000, 1, 0.1).unwrap().round(), 50.0);
+        assert_eq!(sla_budget_used(0, 0, 0.1), None);
 
-        td, th {{
-            border: 1px solid #cad9ea;
-            color: #666;
-            height: 30px;
-        }}
+        let metric = RequestMetric {
+            number_of_requests: 2_000,
+            number_of_failures: 1,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: "2.00".to_string(),
+            requests_per_second_value: 2.00,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", "/")
+        };
 
-        thead th {{
-            background-color: #cce8eb;
-            width: 100px;
-        }}
+        // Without an SLA there's no budget column.
+        let row = raw_request_metrics_row(metric.clone(), &GooseReportOptions::default());
+        assert!(!row.contains("sla-budget"));
 
-        tr:nth-child(odd) {{
-            background: #fff;
-        }}
+        let options = GooseReportOptions {
+            sla_failure_percentage: Some(0.1),
+            ..Default::default()
+        };
+        let row = raw_request_metrics_row(metric.clone(), &options);
+        assert!(row.contains(
+            r#"<td class="sla-budget" style="background-color: hsl(60, 70%, 80%);">50%</td>"#
+        ));
 
-        tr:nth-child(even) {{
-            background: #f5fafa;
-        }}
+        // An exhausted budget is red.
+        let row = raw_request_metrics_row(
+            RequestMetric {
+                number_of_failures: 4,
+                ..metric.clone()
+            },
+            &options,
+        );
+        assert!(row.contains("hsl(0, 70%, 80%);\">200%</td>"));
 
-        .charts-container .chart {{
-            width: 100%;
-            height: 350px;
-            margin-bottom: 30px;
-        }}
+        // An SLA allowing no failures has no budget, rather than an infinite one.
+        assert_eq!(sla_budget_used(2_000, 1, 0.0), None);
+        assert_eq!(sla_budget_used(2_000, 0, 0.0), None);
+        assert_eq!(sla_budget_used(2_000, 1, -1.0), None);
+        let options = GooseReportOptions {
+            sla_failure_percentage: Some(0.0),
+            ..Default::default()
+        };
+        let row = raw_request_metrics_row(metric, &options);
+        assert!(!row.contains("inf%"));
+        assert!(!row.contains("NaN%"));
+        assert!(row.contains("<td></td>"));
+    }
 
-        .download {{
-            float: right;
-        }}
+    #[test]
+    fn test_status_class_donut() {
+        let mut status_code_counts = HashMap::new();
+        status_code_counts.insert(200, 90);
+        status_code_counts.insert(204, 5);
+        status_code_counts.insert(301, 3);
+        status_code_counts.insert(404, 2);
+        status_code_counts.insert(503, 1);
+        let classes = status_classes(&status_code_counts);
+        assert_eq!(classes.len(), 4);
+        assert_eq!(classes.get("2xx"), Some(&95));
 
-        .download a {{
-            color: #00ca5a;
-        }}
+        let graph = graph_status_class_donut_template(
+            &classes,
+            Palette::default(),
+            &ReportStrings::default(),
+        );
+        assert!(graph.contains("type: 'pie',"));
+        let data = graph
+            .split("data: ")
+            .nth(1)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap();
+        let data: serde_json::Value = serde_json::from_str(data).unwrap();
+        let data = data.as_array().unwrap();
+        assert_eq!(data.len(), 4);
+        assert_eq!(data[0]["name"], "2xx");
+        assert_eq!(data[0]["value"], 95);
+        assert_eq!(data[1]["value"], 3);
+        assert_eq!(data[2]["value"], 2);
+        assert_eq!(data[3]["name"], "5xx");
+        assert_eq!(data[3]["value"], 1);
+        assert_eq!(
+            data[3]["itemStyle"]["color"],
+            ColorRole::Danger.colors(Palette::default()).0
+        );
+    }
 
-        .graph {{
-            margin-bottom: 1em;
-        }}
-    </style>
-    <script src="https://cdn.jsdelivr.net/npm/echarts@5.2.2/dist/echarts.min.js"></script>
-</head>
-<body>
-    <div class="container">
-        <h1>Goose Attack Report</h1>
+    #[test]
+    fn test_traffic_share() {
+        let metric = |name: &str, number_of_requests| RequestMetric {
+            number_of_requests,
+            response_time_average: "10.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 20,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.0,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", name)
+        };
+        let mut metrics = vec![metric("/", 250), metric("/about", 750)];
+        set_traffic_shares(&mut metrics);
+        assert_eq!(metrics[0].traffic_share, 25.0);
+        assert_eq!(metrics[1].traffic_share, 75.0);
 
-        <div class="info">
-            <p>Users: <span>{users}</span> </p>
-            <p>Target Host: <span>{hosts}</span></p>
-            {report_range}
-            <p><span><small><em>{pkg_name} v{pkg_version}</em></small></span></pr>
-        </div>
+        let options = GooseReportOptions::default();
+        assert!(request_metrics_header(&options).contains("<th>% of Requests</th>"));
+        let row = raw_request_metrics_row(metrics[0].clone(), &options);
+        assert!(row.contains(
+            r#"<td class="traffic-share">25.0%<div class="bar" style="width: 25.0%;"></div></td>"#
+        ));
 
-        <div class="requests">
-            <h2>Request Metrics</h2>
+        // Without requests there's no share.
+        let mut metrics = vec![metric("/", 0)];
+        set_traffic_shares(&mut metrics);
+        assert_eq!(metrics[0].traffic_share, 0.0);
+    }
 
-            {graph_rps_template}
+    #[test]
+    fn test_max_percentile() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 99);
+        response_times.insert(500, 1);
+        let metric = get_response_metric("GET", "/", &response_times, 100, 14.9, 10, 500);
 
-            <table>
-                <thead>
-                    <tr>
-                        <th>Method</th>
-                        <th>Name</th>
-                        <th># Requests</th>
-                        <th># Fails</th>
-                        <th>Average (ms)</th>
-                        <th>Min (ms)</th>
-                        <th>Max (ms)</th>
-                        <th>RPS</th>
-                        <th>Failures/s</th>
-                    </tr>
-                </thead>
-                <tbody>
-                    {raw_requests_template}
-                </tbody>
-            </table>
-        </div>
+        // Labeled as a percentile by default.
+        let options = GooseReportOptions::default();
+        let header = response_metrics_header(&options);
+        assert!(header.contains("<th>100%ile (ms)</th>"));
+        assert!(!header.contains("<th>Max (ms)</th>"));
 
-        {co_requests_template}
+        let options = GooseReportOptions {
+            max_percentile: MaxPercentile::Max,
+            ..Default::default()
+        };
+        let header = response_metrics_header(&options);
+        assert!(header.contains("<th>99%ile (ms)</th>\n                        <th>Max (ms)</th>"));
+        assert!(!header.contains("100%ile"));
+        assert!(response_metrics_row(metric.clone(), &options).contains("<td>500</td>"));
 
-        <div class="responses">
-            <h2>Response Time Metrics</h2>
+        // Hidden if the request metrics table displays the maximum.
+        let options = GooseReportOptions {
+            max_percentile: MaxPercentile::HideIfRequestMax,
+            ..Default::default()
+        };
+        assert!(!response_metrics_header(&options).contains("100%ile"));
+        assert!(!response_metrics_row(metric.clone(), &options).contains("<td>500</td>"));
+        assert_eq!(
+            response_metrics_columns(&options),
+            response_metrics_columns(&GooseReportOptions::default()) - 1
+        );
 
-            {graph_average_response_time_template}
+        // But displayed if the request metrics table doesn't.
+        let options = GooseReportOptions {
+            request_columns: Some(vec![RequestColumn::Requests].into_iter().collect()),
+            ..options
+        };
+        assert!(response_metrics_header(&options).contains("<th>100%ile (ms)</th>"));
+        assert!(response_metrics_row(metric, &options).contains("<td>500</td>"));
+    }
 
-            <table>
-                <thead>
-                    <tr>
-                        <th>Method</th>
-                        <th>Name</th>
-                        <th>50%ile (ms)</th>
-                        <th>60%ile (ms)</th>
-                        <th>70%ile (ms)</th>
-                        <th>80%ile (ms)</th>
-                        <th>90%ile (ms)</th>
-                        <th>95%ile (ms)</th>
-                        <th>99%ile (ms)</th>
-                        <th>100%ile (ms)</th>
-                    </tr>
-                </thead>
-                <tbody>
-                    {raw_responses_template}
-                </tbody>
-            </table>
-        </div>
+    #[test]
+    fn test_percentiles_relative_to_median() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(100, 98);
+        response_times.insert(400, 1);
+        response_times.insert(1_250, 1);
+        let metric = get_response_metric("GET", "/", &response_times, 100, 110.0, 100, 1_250);
+        assert_eq!(metric.percentile_50, "100");
+        assert_eq!(metric.percentile_99, "400");
 
-        {co_responses_template}
+        // Milliseconds by default.
+        let options = GooseReportOptions::default();
+        assert!(response_metrics_header(&options).contains("<th>99%ile (ms)</th>"));
+        assert!(response_metrics_row(metric.clone(), &options).contains("<td>400</td>"));
 
-        {status_codes_template}
+        let options = GooseReportOptions {
+            percentiles_relative_to_median: true,
+            ..Default::default()
+        };
+        assert!(response_metrics_header(&options).contains("<th>99%ile (&times; p50)</th>"));
+        let row = response_metrics_row(metric.clone(), &options);
+        assert!(row.contains("<td>1.0&times;</td>"));
+        assert!(row.contains("<td>4.0&times;</td>"));
+        assert!(row.contains("<td>12.5&times;</td>"));
+        assert!(!row.contains("<td>400</td>"));
+        assert!(response_metrics_footer(&[metric], &options)
+            .contains("<td>4.0&times;<br /><small>GET /</small></td>"));
 
-        {tasks_template}
+        // Without a median there's no ratio.
+        let metric = get_response_metric("GET", "/", &BTreeMap::new(), 0, 0.0, 0, 0);
+        assert!(response_metrics_row(metric, &options).contains("<td>&ndash;</td>"));
+    }
 
-        <div class="users">
-        <h2>User Metrics</h2>
-            {graph_users_per_second}
-        </div>
+    #[test]
+    fn test_over_threshold() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(200, 5);
+        response_times.insert(1_000, 2);
+        response_times.insert(1_100, 2);
+        response_times.insert(3_000, 1);
+        let metric = get_response_metric("GET", "/", &response_times, 10, 800.0, 200, 3_000);
 
-        {errors_template}
+        // No column by default.
+        let options = GooseReportOptions::default();
+        let row = response_metrics_row(metric.clone(), &options);
+        assert!(!response_metrics_header(&options).contains("&gt;"));
+        assert!(!row.contains("(30%)"));
 
-    </div>
-</body>
-</html>"#,
-        users = users,
-        report_range = report_range,
-        hosts = hosts,
-        pkg_name = pkg_name,
-        pkg_version = pkg_version,
-        raw_requests_template = templates.raw_requests_template,
-        raw_responses_template = templates.raw_responses_template,
-        co_requests_template = templates.co_requests_template,
-        co_responses_template = templates.co_responses_template,
-        tasks_template = templates.tasks_template,
-        status_codes_template = templates.status_codes_template,
-        errors_template = templates.errors_template,
-        graph_rps_template = templates.graph_rps_template,
-        graph_average_response_time_template = templates.graph_average_response_time_template,
-        graph_users_per_second = templates.graph_users_per_second,
-    )
-}
+        // Responses of exactly the threshold aren't over it.
+        let options = GooseReportOptions {
+            over_threshold_ms: Some(1_000),
+            ..Default::default()
+        };
+        let metric = metric.with_over_threshold(&response_times, options.over_threshold_ms);
+        assert_eq!(metric.over_threshold, "3 (30%)");
+        assert!(response_metrics_header(&options).contains("<th>&gt; 1,000 ms</th>"));
+        assert!(response_metrics_row(metric, &options).contains("<td>3 (30%)</td>"));
+        assert_eq!(
+            response_metrics_columns(&options),
+            response_metrics_columns(&GooseReportOptions::default()) + 1
+        );
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        assert_eq!(format_over_threshold(&response_times, 100), "10 (100%)");
+        assert_eq!(format_over_threshold(&response_times, 2_000), "1 (10%)");
+        response_times.insert(10, 2);
+        assert_eq!(format_over_threshold(&response_times, 2_000), "1 (8.3%)");
+        assert_eq!(format_over_threshold(&BTreeMap::new(), 2_000), "0 (0%)");
+    }
 
-    fn expected_graph_html_prefix(html_id: &str, y_axis_label: &str) -> String {
-        format!(
-            r#"<div class="graph">
-                <div id="{html_id}" style="width: 1000px; height:500px; background: white;"></div>
+    #[test]
+    fn test_scenario_request_metrics_rows() {
+        let metric = |method: &str, name: &str, number_of_requests, average: &str| RequestMetric {
+            number_of_requests,
+            number_of_failures: 1,
+            response_time_average: average.to_string(),
+            response_time_minimum: 5,
+            response_time_maximum: 50,
+            requests_per_second: format_rate(number_of_requests as f32 / 10.0),
+            failures_per_second: "0.10".to_string(),
+            requests_per_second_value: number_of_requests as f32 / 10.0,
+            failures_per_second_value: 0.1,
+            ..request_metric(method, name)
+        };
+        let options = GooseReportOptions::default();
+        let rows = scenario_request_metrics_rows(
+            vec![
+                (
+                    "Anonymous".to_string(),
+                    vec![
+                        metric("GET", "/", 30, "10.00"),
+                        metric("GET", "/about", 10, "30.00"),
+                    ],
+                ),
+                (
+                    "Authenticated".to_string(),
+                    vec![metric("POST", "/login", 20, "40.00")],
+                ),
+            ],
+            &options,
+        );
+        assert_eq!(rows.len(), 7);
 
-                <script type="text/javascript">
-                    var chartDom = document.getElementById('{html_id}');
-                    var myChart = echarts.init(chartDom);
+        let columns = request_metrics_columns(&options);
+        assert!(rows[0].contains(&format!(
+            r#"<td colspan="{}" align="left"><strong>Anonymous</strong></td>"#,
+            columns
+        )));
+        assert!(rows[1].contains("<td>/</td>"));
+        assert!(rows[2].contains("<td>/about</td>"));
+        // The subtotal sums counts and rates and weights the average.
+        assert!(rows[3].contains("<td>Subtotal</td>"));
+        assert!(rows[3].contains(r#"<td data-count="40" data-rps="4.00">40</td>"#));
+        assert!(rows[3].contains(r#"<td data-count="2" data-rps="0.20">2</td>"#));
+        assert!(rows[3].contains("<td>15.00</td>"));
 
-                    myChart.setOption({{
-                        color: ['#2c664f'],
-                        tooltip: {{ trigger: 'axis' }},
-                        toolbox: {{
-                            feature: {{
-                                dataZoom: {{ yAxisIndex: 'none' }},
-                                restore: {{}},
-                                saveAsImage: {{}}
-                            }}
-                        }},
-                        dataZoom: [
-                            {{
-                                type: 'inside',
-                                start: 0,
-                                end: 100,
-                                fillerColor: 'rgba(34, 80, 61, 0.25)',
-                                selectedDataBackground: {{
-                                    lineStyle: {{ color: '#2c664f' }},
-                                    areaStyle: {{ color: '#378063' }}
-                                }}
-                            }},
-                            {{
-                                start: 0,
-                                end: 100,
-                                fillerColor: 'rgba(34, 80, 61, 0.25)',
-                                selectedDataBackground: {{
-                                    lineStyle: {{ color: '#2c664f' }},
-                                    areaStyle: {{ color: '#378063' }}
-                                }}
-                            }},
-                        ],
-                        xAxis: {{ type: 'time' }},
-                        yAxis: {{
-                            name: '{y_axis_label}',
-                            nameLocation: 'center',
-                            nameRotate: 90,
-                            nameGap: 45,
-                            type: 'value'
-                        }},
-                        series: [
-                            {{
-                                type: 'line',
-                                symbol: 'none',
-                                sampling: 'lttb',
-                                lineStyle: {{ color: '#2c664f' }},
-                                areaStyle: {{ color: '#378063' }},
-                                markArea: {{
-                                    itemStyle: {{ color: 'rgba(6, 6, 6, 0.10)' }},
-"#,
-            html_id = html_id,
-            y_axis_label = y_axis_label
-        )
+        assert!(rows[4].contains("<strong>Authenticated</strong>"));
+        assert!(rows[5].contains("<td>/login</td>"));
+        assert!(rows[6].contains("<td>Subtotal</td>"));
+        assert!(rows[6].contains(r#"<td data-count="20" data-rps="2.00">20</td>"#));
+        assert!(rows[6].contains("<td>40.00</td>"));
     }
 
     #[test]
-    fn test_graph_rps_template() {
-        let expected_prefix = expected_graph_html_prefix("graph-rps", "Requests #");
+    fn test_diagnostics_template() {
+        let strings = ReportStrings::default();
+        assert_eq!(diagnostics_template(None, &strings), "");
 
-        let data = vec![
-            ("2021-11-21 21:20:32".to_string(), 123),
-            ("2021-11-21 21:20:33".to_string(), 111),
-            ("2021-11-21 21:20:34".to_string(), 99),
-            ("2021-11-21 21:20:35".to_string(), 134),
-        ];
+        let overhead = MetricsOverhead {
+            recording_time: std::time::Duration::from_millis(1_500),
+            duration: std::time::Duration::from_secs(300),
+        };
+        let template = diagnostics_template(Some(&overhead), &strings);
+        assert!(template.starts_with(r#"<details class="diagnostics">"#));
+        assert!(template.contains("<summary>Diagnostics</summary>"));
+        assert!(template.contains(
+            "Metrics collection overhead: <span>1.50 seconds (0.50% of the load test)</span>"
+        ));
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+        let html = build_report(
+            "",
+            "",
+            "",
+            GooseReportTemplates {
+                raw_requests_template: "",
+                raw_responses_template: "",
+                raw_responses_footer_template: "",
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: "",
+                status_codes_template: "",
+                errors_template: "",
+                graph_rps_template: "",
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: "",
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: &template,
+            },
+            &Default::default(),
         );
-        assert_eq!(graph_rps_template(&data, None, None, None, None), expected);
+        assert!(html.contains("Metrics collection overhead"));
+    }
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+    #[test]
+    fn test_throttle_template() {
+        let strings = ReportStrings::default();
+        let templates = |throttle_template| GooseReportTemplates {
+            raw_requests_template: "",
+            raw_responses_template: "",
+            raw_responses_footer_template: "",
+            co_requests_template: "",
+            co_responses_template: "",
+            tasks_template: "",
+            status_codes_template: "",
+            errors_template: "",
+            graph_rps_template: "",
+            graph_average_response_time_template: "",
+            graph_users_per_second: "",
+            verdict_template: "",
+            graph_overview_template: "",
+            throttle_template,
+            fingerprint_template: "",
+            diagnostics_template: "",
+        };
+
+        // Without throttle metadata the section is omitted.
+        let template = throttle_template(None, &strings);
+        assert_eq!(template, "");
+        let html = build_report("", "", "", templates(&template), &Default::default());
+        assert!(!html.contains(r#"<div class="throttle">"#));
+
+        // The throttle capped the requests per second.
+        let throttle = ThrottleSummary {
+            throttle_requests: 100,
+            requests_per_second: 99.5,
+            throttled_time: Some(std::time::Duration::from_millis(12_340)),
+        };
+        assert!(throttle.is_binding());
+        let template = throttle_template(Some(&throttle), &strings);
+        let html = build_report("", "", "", templates(&template), &Default::default());
+        assert!(html.contains(r#"<div class="throttle">"#));
+        assert!(html.contains("<h2>Throttle</h2>"));
+        assert!(html.contains("at most 100 per second, and 99.50 per second were achieved"));
+        assert!(html.contains("The throttle was the binding constraint"));
+        assert!(html.contains("for a total of 12.3 seconds"));
+
+        // The server couldn't keep up with the throttle.
+        let throttle = ThrottleSummary {
+            requests_per_second: 40.0,
+            throttled_time: None,
+            ..throttle
+        };
+        assert!(!throttle.is_binding());
+        let template = throttle_template(Some(&throttle), &strings);
+        assert!(template.contains("The throttle was not the binding constraint"));
+        assert!(!template.contains("waited on the throttle"));
+    }
+
+    #[test]
+    fn test_metrics_fingerprint() {
+        let request = |path: &str, requests| RequestMetric {
+            number_of_requests: requests,
+            response_time_average: "10.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 20,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.0,
+            apdex: "1.00".to_string(),
+            status_code_counts: (200..210).map(|status_code| (status_code, 1)).collect(),
+            traffic_share: 50.0,
+            ..request_metric("GET", path)
+        };
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 10);
+        let responses = vec![get_response_metric(
+            "GET",
+            "/",
+            &response_times,
+            10,
+            10.0,
+            10,
+            10,
+        )];
+        let errors = vec![ReportError {
+            error: "503 Service Unavailable: /".to_string(),
+            occurrences: 1,
+            category: "server_error",
+        }];
+
+        let fingerprint = metrics_fingerprint(
+            &[request("/", 10), request("/about", 10)],
+            &responses,
+            &errors,
         );
+        assert_eq!(fingerprint.len(), 16);
+        // The order of the rows doesn't matter.
         assert_eq!(
-            graph_rps_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                None,
-                None
+            metrics_fingerprint(
+                &[request("/about", 10), request("/", 10)],
+                &responses,
+                &errors
             ),
-            expected
+            fingerprint
         );
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_rps_template(
-                &data,
-                None,
-                None,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34))
+        // Reports of runs at different times differ, but their fingerprints don't.
+        let report = |report_range: &str| {
+            build_report(
+                "1",
+                report_range,
+                "",
+                GooseReportTemplates {
+                    raw_requests_template: "",
+                    raw_responses_template: "",
+                    raw_responses_footer_template: "",
+                    co_requests_template: "",
+                    co_responses_template: "",
+                    tasks_template: "",
+                    status_codes_template: "",
+                    errors_template: "",
+                    graph_rps_template: "",
+                    graph_average_response_time_template: "",
+                    graph_users_per_second: "",
+                    verdict_template: "",
+                    graph_overview_template: "",
+                    throttle_template: "",
+                    fingerprint_template: &fingerprint_template(
+                        &metrics_fingerprint(
+                            &[request("/", 10), request("/about", 10)],
+                            &responses,
+                            &errors,
+                        ),
+                        &ReportStrings::default(),
+                    ),
+                    diagnostics_template: "",
+                },
+                &GooseReportOptions::default(),
+            )
+        };
+        let first = report("<p>Running: <span>2021-11-21 21:20:32</span></p>");
+        let second = report("<p>Running: <span>2021-11-22 09:00:00</span></p>");
+        assert_ne!(first, second);
+        let footer = format!("Metrics fingerprint: <code>{}</code>", fingerprint);
+        assert!(first.contains(&footer));
+        assert!(second.contains(&footer));
+
+        // Any change to the metrics changes the fingerprint.
+        assert_ne!(
+            metrics_fingerprint(
+                &[request("/", 10), request("/about", 11)],
+                &responses,
+                &errors
             ),
-            expected
+            fingerprint
+        );
+        assert_ne!(
+            metrics_fingerprint(&[request("/", 10), request("/about", 10)], &responses, &[]),
+            fingerprint
         );
+    }
 
-        let mut expected = expected_prefix;
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:36'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:38'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+    #[test]
+    fn test_request_name_links() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 1);
+        let metric =
+            |name: &str| get_response_metric("GET", name, &response_times, 1, 10.0, 10, 10);
+
+        // Names are plain text by default.
+        let options = GooseReportOptions::default();
+        assert!(response_metrics_row(metric("/checkout"), &options).contains("<td>/checkout</td>"));
+
+        let options = GooseReportOptions {
+            request_name_host: Some("https://example.com/".to_string()),
+            ..Default::default()
+        };
+        assert!(response_metrics_row(metric("/checkout"), &options).contains(
+            r#"<td><a href="https://example.com/checkout" target="_blank" rel="noopener noreferrer">/checkout</a></td>"#
+        ));
+        // Unsafe characters are encoded.
+        assert!(response_metrics_row(metric(r#"/search?q="<x>""#), &options)
+            .contains(r#"href="https://example.com/search?q=%22%3Cx%3E%22""#));
+        // Names that aren't paths, and hosts that aren't web sites, aren't linked.
+        assert!(
+            response_metrics_row(metric("Aggregated"), &options).contains("<td>Aggregated</td>")
         );
-        assert_eq!(
-            graph_rps_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 36)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 38))
-            ),
-            expected
+        let options = GooseReportOptions {
+            request_name_host: Some("javascript:alert(1)".to_string()),
+            ..Default::default()
+        };
+        assert!(response_metrics_row(metric("/checkout"), &options).contains("<td>/checkout</td>"));
+    }
+
+    #[test]
+    fn test_highlight_error_status() {
+        let mut status_code_counts = HashMap::new();
+        status_code_counts.insert(200, 90);
+        status_code_counts.insert(503, 1);
+        let metric = RequestMetric {
+            number_of_requests: 91,
+            response_time_average: "10.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 20,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.0,
+            apdex: "1.00".to_string(),
+            status_code_counts,
+            ..request_metric("GET", "/")
+        };
+        assert!(metric.has_error_status());
+
+        // Not highlighted by default.
+        let options = GooseReportOptions::default();
+        assert!(
+            raw_request_metrics_row(metric.clone(), &options).starts_with(r#"<tr data-name="/">"#)
         );
+
+        // Highlighted even though Goose didn't count a failure.
+        let options = GooseReportOptions {
+            highlight_error_status: true,
+            ..Default::default()
+        };
+        assert!(raw_request_metrics_row(metric.clone(), &options)
+            .starts_with(r#"<tr data-name="/" class="error-status">"#));
+
+        // Redirects and successes aren't highlighted.
+        let mut healthy = metric;
+        healthy.status_code_counts.remove(&503);
+        healthy.status_code_counts.insert(302, 4);
+        assert!(!healthy.has_error_status());
+        assert!(raw_request_metrics_row(healthy, &options).starts_with(r#"<tr data-name="/">"#));
     }
 
     #[test]
-    fn test_graph_eps_template() {
-        let expected_prefix = expected_graph_html_prefix("graph-eps", "Errors #");
+    fn test_search_box() {
+        let report = report_page("1", "", "", "", &GooseReportOptions::default());
+        assert!(!report.contains("table-search"));
 
-        let data = vec![
-            ("2021-11-21 21:20:32".to_string(), 123),
-            ("2021-11-21 21:20:33".to_string(), 111),
-            ("2021-11-21 21:20:34".to_string(), 99),
-            ("2021-11-21 21:20:35".to_string(), 134),
-        ];
+        let options = GooseReportOptions {
+            search_box: true,
+            ..Default::default()
+        };
+        let report = report_page("1", "", "", "", &options);
+        assert!(report.contains(r#"<input id="table-search" type="search""#));
+        assert!(report.contains("document.querySelectorAll('tr[data-name]')"));
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+        // The rows of the request, response time and status code tables are named.
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 1);
+        let response = get_response_metric("GET", "/a&b", &response_times, 1, 10.0, 10, 10);
+        assert!(
+            response_metrics_row(response, &options).starts_with(r#"<tr data-name="/a&amp;b">"#)
         );
-        assert_eq!(graph_eps_template(&data, None, None, None, None), expected);
+        let status = StatusCodeMetric {
+            method: "GET".to_string(),
+            name: "/a&b".to_string(),
+            status_codes: "200 [1]".to_string(),
+        };
+        assert!(status_code_metrics_row(status).starts_with(r#"<tr data-name="/a&amp;b">"#));
+    }
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_eps_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                None,
-                None
-            ),
-            expected
-        );
+    #[test]
+    fn test_sort_metrics() {
+        let request = |name: &str, number_of_requests| RequestMetric {
+            number_of_requests,
+            response_time_average: "10.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 2_000,
+            requests_per_second: "1.00".to_string(),
+            requests_per_second_value: 1.0,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", name)
+        };
+        let response = |name: &str, maximum| {
+            let mut times = BTreeMap::new();
+            times.insert(10, 90);
+            times.insert(maximum, 10);
+            get_response_metric("GET", name, &times, 100, 10.0, 10, maximum)
+        };
+        let mut requests = vec![request("/fast", 100), request("/slow", 10)];
+        let mut responses = vec![response("/fast", 20), response("/slow", 1_500)];
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_eps_template(
-                &data,
-                None,
-                None,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34))
-            ),
-            expected
+        sort_metrics(
+            &mut requests,
+            &mut responses,
+            SortBy::P95,
+            SortDirection::Descending,
         );
+        assert_eq!(requests[0].name, "/slow");
+        assert_eq!(requests[1].name, "/fast");
+        assert_eq!(responses[0].name, "/slow");
+        assert_eq!(responses[0].percentile_95, "1,500");
+        assert_eq!(responses[1].name, "/fast");
 
-        let mut expected = expected_prefix;
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:36'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:38'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+        sort_metrics(
+            &mut requests,
+            &mut responses,
+            SortBy::Requests,
+            SortDirection::Descending,
         );
-        assert_eq!(
-            graph_eps_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 36)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 38))
-            ),
-            expected
+        assert_eq!(requests[0].name, "/fast");
+        assert_eq!(responses[0].name, "/fast");
+
+        sort_metrics(
+            &mut requests,
+            &mut responses,
+            SortBy::Name,
+            SortDirection::Descending,
         );
+        assert_eq!(requests[0].name, "/slow");
+        assert_eq!(responses[0].name, "/slow");
     }
 
     #[test]
-    fn test_graph_average_response_time_template() {
-        let expected_prefix =
-            expected_graph_html_prefix("graph-avg-response-time", "Response time [ms]");
+    fn test_graph_endpoint_rps_template() {
+        let series = vec![
+            (
+                SeriesSummary {
+                    name: "GET /".to_string(),
+                    requests: 1200,
+                    failures: 6,
+                },
+                vec![("2021-11-21 21:20:32".to_string(), 10)],
+            ),
+            (
+                SeriesSummary {
+                    name: "POST /login".to_string(),
+                    requests: 0,
+                    failures: 0,
+                },
+                vec![("2021-11-21 21:20:32".to_string(), 0)],
+            ),
+        ];
+        let graph = graph_endpoint_rps_template(&series, &ReportStrings::default());
+        assert!(graph.contains(
+            r#"var totals = {"GET /":"1,200 requests, 0.50% failed","POST /login":"0 requests, 0.00% failed"};"#
+        ));
+        assert!(graph.contains("' (' + totals[param.seriesName] + ')'"));
+        assert!(graph.contains(r#"{"data":[["2021-11-21 21:20:32",10]],"name":"GET /""#));
+    }
 
+    #[test]
+    fn test_requests_treemap() {
         let data = vec![
-            ("2021-11-21 21:20:32".to_string(), 123),
-            ("2021-11-21 21:20:33".to_string(), 111),
-            ("2021-11-21 21:20:34".to_string(), 99),
-            ("2021-11-21 21:20:35".to_string(), 134),
+            ("GET /".to_string(), 600),
+            ("GET /about".to_string(), 395),
+            ("GET /favicon.ico".to_string(), 3),
+            ("GET /robots.txt".to_string(), 2),
         ];
-
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
         assert_eq!(
-            graph_average_response_time_template(&data, None, None, None, None),
-            expected
+            treemap_data(&data, 1.0, "Other"),
+            vec![
+                ("GET /".to_string(), 600),
+                ("GET /about".to_string(), 395),
+                ("Other".to_string(), 5),
+            ]
         );
+        // Nothing is combined without a threshold.
+        assert_eq!(treemap_data(&data, 0.0, "Other"), data);
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_average_response_time_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                None,
-                None
-            ),
-            expected
+        let graph = graph_requests_treemap_template(
+            &data,
+            None,
+            Palette::default(),
+            &ReportStrings::default(),
         );
+        assert!(graph.contains(r#"{"name":"GET /","value":600}"#));
+        assert!(graph.contains(r#"{"name":"GET /about","value":395}"#));
+        assert!(graph.contains(r#"{"name":"Other","value":5}"#));
+        assert!(!graph.contains("favicon"));
+    }
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_average_response_time_template(
-                &data,
-                None,
-                None,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34))
-            ),
-            expected
-        );
+    #[test]
+    fn test_response_time_cdf() {
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 2);
+        response_times.insert(20, 5);
+        response_times.insert(50, 0);
+        response_times.insert(100, 3);
 
-        let mut expected = expected_prefix;
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:36'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:38'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_average_response_time_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 36)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 38))
-            ),
-            expected
+        let cdf = response_time_cdf(&response_times);
+        assert_eq!(cdf, vec![(10, 20.0), (20, 70.0), (50, 70.0), (100, 100.0)]);
+        assert!(cdf.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+        let graph = graph_response_time_cdf_template(
+            &response_times,
+            ColorRole::Success,
+            Palette::default(),
+            &ReportStrings::default(),
         );
+        assert!(graph.contains(r#"[[10,"20.00"],[20,"70.00"],[50,"70.00"],[100,"100.00"]]"#));
     }
 
     #[test]
-    fn test_graph_users_per_second_template() {
-        let expected_prefix = expected_graph_html_prefix("graph-active-users", "Active users #");
+    fn test_task_percentiles() {
+        let mut run_times = BTreeMap::new();
+        run_times.insert(100, 90);
+        run_times.insert(250, 8);
+        run_times.insert(900, 2);
+        let metric = get_response_metric("", "login", &run_times, 100, 133.0, 100, 900);
+        assert_eq!(metric.percentile_95, "250");
 
-        let data = vec![
-            ("2021-11-21 21:20:32".to_string(), 123),
-            ("2021-11-21 21:20:33".to_string(), 111),
-            ("2021-11-21 21:20:34".to_string(), 99),
-            ("2021-11-21 21:20:35".to_string(), 134),
-        ];
+        let options = GooseReportOptions::default();
+        let row = task_response_metrics_row("0.1", metric, &options);
+        assert!(row.contains(r#"<td colspan="2">0.1 login</td>"#));
+        assert!(row.contains("<td>250</td>\n            <td>900</td>\n            <td>900</td>"));
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_users_per_second_template(&data, None, None, None, None),
-            expected
+        let table = task_response_metrics_template(&row, &options);
+        assert!(table.contains("<h2>Task Run Time Metrics</h2>"));
+        assert!(table.contains("<th>95%ile (ms)</th>"));
+    }
+
+    #[test]
+    fn test_report_strings() {
+        let strings = ReportStrings {
+            title: "Goose Lasttest-Bericht".to_string(),
+            users: "Benutzer".to_string(),
+            request_metrics: "Anfragemetriken".to_string(),
+            response_time_metrics: "Antwortzeitmetriken".to_string(),
+            status_code_metrics: "Statuscode-Metriken".to_string(),
+            errors: "Fehler".to_string(),
+            method: "Methode".to_string(),
+            average: "Durchschnitt (ms)".to_string(),
+            ..Default::default()
+        };
+        let options = GooseReportOptions {
+            strings: strings.clone(),
+            ..Default::default()
+        };
+        let report = build_report(
+            "1",
+            "",
+            "",
+            GooseReportTemplates {
+                raw_requests_template: "",
+                raw_responses_template: "",
+                raw_responses_footer_template: "",
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: "",
+                status_codes_template: &status_code_metrics_template("", "", &strings),
+                errors_template: &errors_template("", "", None, &strings),
+                graph_rps_template: "",
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: "",
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: "",
+            },
+            &options,
         );
+        assert!(report.contains("<h1>Goose Lasttest-Bericht</h1>"));
+        assert!(report.contains("<p>Benutzer: <span>1</span> </p>"));
+        assert!(report.contains("<h2>Anfragemetriken</h2>"));
+        assert!(report.contains("<h2>Antwortzeitmetriken</h2>"));
+        assert!(report.contains("<h2>Statuscode-Metriken</h2>"));
+        assert!(report.contains("<h2>Fehler</h2>"));
+        assert!(report.contains("<th>Methode</th>"));
+        assert!(report.contains("<th>Durchschnitt (ms)</th>"));
+        assert!(!report.contains("<h2>Request Metrics</h2>"));
+        assert!(!report.contains("<th>Method</th>"));
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+        // Labels outside the section headings and column headers are translated too.
+        let strings = ReportStrings {
+            assertion: "Annahme".to_string(),
+            fail: "DURCHGEFALLEN".to_string(),
+            not_met: "nicht erfüllt".to_string(),
+            show_rates: "Raten anzeigen".to_string(),
+            filter_endpoints: "Endpunkte filtern".to_string(),
+            throttle_summary: "Höchstens {limit} pro Sekunde, erreicht {achieved}.".to_string(),
+            ..strings
+        };
+        let verdict = verdict_template(
+            &ReportVerdict {
+                passed: false,
+                results: vec![AssertionResult {
+                    description: "p95 < 100 ms".to_string(),
+                    threshold: 100.0,
+                    value: 120.0,
+                    actual: "120 ms".to_string(),
+                    passed: false,
+                }],
+            },
+            &strings,
         );
-        assert_eq!(
-            graph_users_per_second_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                None,
-                None
-            ),
-            expected
+        assert!(verdict.contains("<h2>DURCHGEFALLEN</h2>"));
+        assert!(verdict.contains("<th>Annahme</th>"));
+        assert!(verdict.contains("<td>nicht erfüllt</td>"));
+        let throttle = throttle_template(
+            Some(&ThrottleSummary {
+                throttle_requests: 1_000,
+                requests_per_second: 998.5,
+                throttled_time: None,
+            }),
+            &strings,
         );
+        assert!(throttle.contains("<p>Höchstens 1,000 pro Sekunde, erreicht 998.50.</p>"));
+        let options = GooseReportOptions {
+            strings,
+            rate_toggle: true,
+            search_box: true,
+            ..Default::default()
+        };
+        let report = build_empty_report("", "", "", &options);
+        assert!(report.contains(">Raten anzeigen</button>"));
+        assert!(report.contains(r#"placeholder="Endpunkte filtern""#));
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:32'
+        // No English label remains in the rest of the report either.
+        let strings = ReportStrings {
+            requests_per_second: "Anfr./s".to_string(),
+            failures_per_second: "Fehlschläge/s".to_string(),
+            requests: "# Anfragen".to_string(),
+            traffic_share: "% der Anfragen".to_string(),
+            minimum: "Kleinste (ms)".to_string(),
+            maximum: "Größte (ms)".to_string(),
+            max: "Höchstwert".to_string(),
+            aggregated: "Gesamt".to_string(),
+            other: "Sonstige".to_string(),
+            overall: "Insgesamt".to_string(),
+            starting: "Hochfahren".to_string(),
+            running: "Laufend".to_string(),
+            stopping: "Herunterfahren".to_string(),
+            warmup: "Aufwärmen".to_string(),
+            duration: "Dauer".to_string(),
+            commit: "Revision".to_string(),
+            branch: "Zweig".to_string(),
+            build: "Bau".to_string(),
+            generated_by: "Erstellt mit {generator} ({goose})".to_string(),
+            final_report: "Endgültig".to_string(),
+            live_report: "Vorläufig &mdash; aktualisiert {time}".to_string(),
+            truncated_rows: "Die ersten {shown} von {total}.".to_string(),
+            hidden_endpoints: "{hidden} fehlerfreie Endpunkte ausgeblendet".to_string(),
+            first_error: "Erster Fehler: {time} ({users} Benutzer, {rps} Anfr./s)".to_string(),
+            metrics_overhead: "Messaufwand".to_string(),
+            metrics_overhead_detail: "{seconds} Sekunden ({percentage}%)".to_string(),
+            graph_error: "Diagramm nicht darstellbar: {error}".to_string(),
+            requests_axis: "Anfragen #".to_string(),
+            errors_axis: "Fehler #".to_string(),
+            response_time_axis: "Antwortzeit [ms]".to_string(),
+            active_users_axis: "Aktive Benutzer #".to_string(),
+            tasks_axis: "Aufgaben #".to_string(),
+            requests_percentage_axis: "Anfragen [%]".to_string(),
+            response_time: "Antwortzeit".to_string(),
+            request_count: "Anfragen".to_string(),
+            requests_within: "Anfragen innerhalb".to_string(),
+            status_class: "Statusklasse".to_string(),
+            observed_minimum: "Kleinstwert".to_string(),
+            observed_maximum: "Größtwert".to_string(),
+            lower_bound: "Untergrenze".to_string(),
+            upper_bound: "Obergrenze".to_string(),
+            endpoint_totals: "{requests} Anfragen, {failure_rate}% fehlgeschlagen".to_string(),
+            p95_trend: "p95-Verlauf [ms]".to_string(),
+            ..options.strings
+        };
+        let options = GooseReportOptions {
+            strings: strings.clone(),
+            generator_name: Some("lasttest".to_string()),
+            max_percentile: MaxPercentile::Max,
+            ..Default::default()
+        };
+        let time = |second| {
+            Local
+                .with_ymd_and_hms(2021, 11, 21, 21, 20, second)
+                .unwrap()
+        };
+        let phases = GraphPhases {
+            starting: Some(time(30)),
+            started: Some(time(32)),
+            stopping: Some(time(36)),
+            stopped: Some(time(38)),
+            warmup: Some((time(32), time(34))),
+        };
+        let data = vec![("2021-11-21 21:20:32".to_string(), 3)];
+        let users = vec![("2021-11-21 21:20:32".to_string(), 3)];
+        struct Unserializable;
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("value"))
+            }
+        }
+        let response_times = vec![(10, 3), (20, 1)]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let rendered = [
+            task_metrics_template("", "", &strings),
+            request_metrics_header(&options),
+            response_metrics_header(&options),
+            graph_rps_template(&data, phases, &options),
+            graph_eps_template(&data, phases, &options),
+            graph_average_response_time_template(
+                &data,
+                phases,
+                Some((&data, &data)),
+                Some((&data, &data)),
+                &options,
+            ),
+            graph_users_per_second_template(&users, phases, &options),
+            graph_tasks_per_second_template(&data, phases, &options),
+            graph_tasks_per_second_template(
+                &[("2021-11-21 21:20:32".to_string(), Unserializable)],
+                phases,
+                &options,
+            ),
+            graph_overview_template(&data, &data, &data, &users, phases, &options),
+            graph_status_class_donut_template(
+                &vec![("Other".to_string(), 1)].into_iter().collect(),
+                Palette::default(),
+                &strings,
+            ),
+            graph_endpoint_rps_template(
+                &[(
+                    SeriesSummary {
+                        name: "GET /".to_string(),
+                        requests: 3,
+                        failures: 0,
                     },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_users_per_second_template(
+                    data.clone(),
+                )],
+                &strings,
+            ),
+            graph_response_boxplot_template(
+                &[("GET /".to_string(), [1, 2, 3, 4, 5])],
+                Palette::default(),
+                &strings,
+            ),
+            graph_response_scatter_template(
                 &data,
                 None,
+                ColorRole::Success,
+                Palette::default(),
+                &strings,
+            ),
+            graph_requests_treemap_template(
+                &[("GET /".to_string(), 999), ("GET /about".to_string(), 1)],
                 None,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34))
+                Palette::default(),
+                &strings,
             ),
-            expected
-        );
-
-        let mut expected = expected_prefix;
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:36'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:38'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_users_per_second_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 36)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 38))
+            graph_response_time_cdf_template(
+                &response_times,
+                ColorRole::Success,
+                Palette::default(),
+                &strings,
+            ),
+            graph_response_time_heatmap_template(
+                &[("GET /".to_string(), response_times.clone())],
+                &[10, 100],
+                Palette::default(),
+                &strings,
+            ),
+            history_trend_template(&[("1".to_string(), 12.0)], Palette::default(), &strings),
+            truncated_rows_notice(1, 2, 1, &strings),
+            hidden_successful_endpoints_notice(2, &strings),
+            first_error_template(
+                Some(&FirstError {
+                    time: time(33),
+                    users: 3,
+                    requests_per_second: 10,
+                }),
+                &strings,
+            ),
+            build_info_template(
+                &BuildInfo {
+                    commit: Some("4a7d741".to_string()),
+                    branch: Some("main".to_string()),
+                    build_label: Some("42".to_string()),
+                },
+                &strings,
+            ),
+            generator_template(&options),
+            freshness_badge(Some(time(38)), time(38), &strings),
+            freshness_badge(None, time(38), &strings),
+            diagnostics_template(
+                Some(&MetricsOverhead {
+                    recording_time: std::time::Duration::from_secs(1),
+                    duration: std::time::Duration::from_secs(10),
+                }),
+                &strings,
+            ),
+            report_range_row(
+                &strings.running,
+                &time(32),
+                &time(36),
+                (0, 0, 4),
+                &LocaleConfig::default(),
+                &strings,
             ),
-            expected
+        ]
+        .join("\n");
+        for label in [
+            "Anfr./s",
+            "Fehlschläge/s",
+            "Höchstwert",
+            "Sonstige",
+            "Hochfahren",
+            "Laufend",
+            "Herunterfahren",
+            "Aufwärmen",
+            "Dauer",
+            "Revision",
+            "Zweig",
+            "Erstellt mit",
+            "Endgültig",
+            "aktualisiert",
+            "Die ersten 1 von 2.",
+            "2 fehlerfreie Endpunkte ausgeblendet",
+            "Erster Fehler",
+            "Messaufwand",
+            "Diagramm nicht darstellbar",
+            "Anfragen #",
+            "Fehler #",
+            "Antwortzeit [ms]",
+            "Aktive Benutzer #",
+            "Aufgaben #",
+            "Anfragen [%]",
+            "Anfragen innerhalb",
+            "Statusklasse",
+            "Kleinstwert",
+            "Größtwert",
+            "Untergrenze",
+            "Obergrenze",
+            "3 Anfragen, 0.00% fehlgeschlagen",
+            "p95-Verlauf [ms]",
+        ] {
+            assert!(rendered.contains(label), "missing {:?}", label);
+        }
+        for label in [
+            "RPS",
+            "Failures/s",
+            "Max",
+            "Other",
+            "Starting",
+            "Running",
+            "Stopping",
+            "Warmup",
+            "Duration",
+            "Commit",
+            "Branch",
+            "Build",
+            "Generated by",
+            "Final",
+            "Live",
+            "Showing top",
+            "successful responses hidden",
+            "First error",
+            "Metrics collection overhead",
+            "Unable to render graph",
+            "Requests",
+            "Errors #",
+            "Response time",
+            "Active users",
+            "Tasks #",
+            "Status class",
+            "Minimum",
+            "Maximum",
+            "Lower bound",
+            "Upper bound",
+            "failed",
+            "p95 trend",
+        ] {
+            assert!(!rendered.contains(label), "untranslated {:?}", label);
+        }
+
+        let report = build_phased_report(
+            "",
+            "",
+            "",
+            Vec::new(),
+            GooseReportTemplates {
+                raw_requests_template: "",
+                raw_responses_template: "",
+                raw_responses_footer_template: "",
+                co_requests_template: "",
+                co_responses_template: "",
+                tasks_template: "",
+                status_codes_template: "",
+                errors_template: "",
+                graph_rps_template: "",
+                graph_average_response_time_template: "",
+                graph_users_per_second: "",
+                verdict_template: "",
+                graph_overview_template: "",
+                throttle_template: "",
+                fingerprint_template: "",
+                diagnostics_template: "",
+            },
+            &options,
         );
+        assert!(report.contains(r#"<h2 class="phase-title">Insgesamt</h2>"#));
+        assert!(!report.contains("Overall"));
     }
 
     #[test]
-    fn test_graph_tasks_per_second_template() {
-        let expected_prefix = expected_graph_html_prefix("graph-tps", "Tasks #");
+    fn test_column_visibility() {
+        let metric = RequestMetric {
+            number_of_requests: 10,
+            number_of_failures: 1,
+            response_time_average: "5.00".to_string(),
+            response_time_minimum: 3,
+            response_time_maximum: 7,
+            requests_per_second: "2.00".to_string(),
+            failures_per_second: "0.20".to_string(),
+            requests_per_second_value: 2.00,
+            failures_per_second_value: 0.20,
+            apdex: "1.00".to_string(),
+            ..request_metric("GET", "/")
+        };
 
-        let data = vec![
-            ("2021-11-21 21:20:32".to_string(), 123),
-            ("2021-11-21 21:20:33".to_string(), 111),
-            ("2021-11-21 21:20:34".to_string(), 99),
-            ("2021-11-21 21:20:35".to_string(), 134),
-        ];
+        let options = GooseReportOptions::default();
+        assert_eq!(request_metrics_columns(&options), 11);
+        let header = request_metrics_header(&options);
+        assert!(header.contains("<th>Min (ms)</th>"));
+        let row = raw_request_metrics_row(metric.clone(), &options);
+        assert!(row.contains("<td>3</td>"));
+        assert_eq!(row.matches("<td").count(), 11);
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_tasks_per_second_template(&data, None, None, None, None),
-            expected
-        );
+        // Disabling the minimum and maximum omits them from the header and rows, keeping
+        // the remaining columns in order.
+        let options = GooseReportOptions {
+            request_columns: Some(
+                REQUEST_COLUMNS
+                    .iter()
+                    .copied()
+                    .filter(|column| {
+                        *column != RequestColumn::Minimum && *column != RequestColumn::Maximum
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(request_metrics_columns(&options), 9);
+        let header = request_metrics_header(&options);
+        assert!(!header.contains("Min (ms)"));
+        assert!(!header.contains("Max (ms)"));
+        assert_eq!(header.matches("<th>").count(), 9);
+        assert!(header.contains("<th>Average (ms)</th>\n                        <th>RPS</th>"));
+        let row = raw_request_metrics_row(metric, &options);
+        assert!(!row.contains("<td>3</td>"));
+        assert!(!row.contains("<td>7</td>"));
+        assert_eq!(row.matches("<td").count(), 9);
+        assert!(row.contains("<td>5.00</td>\n        <td>2.00</td>"));
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
-        );
-        assert_eq!(
-            graph_tasks_per_second_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                None,
-                None
+        // Hiding percentiles of the response time table.
+        let mut response_times = BTreeMap::new();
+        response_times.insert(10, 100);
+        let metric = get_response_metric("GET", "/", &response_times, 100, 10.0, 10, 10);
+        let options = GooseReportOptions {
+            response_columns: Some(
+                vec![ResponseColumn::Percentile95, ResponseColumn::Percentile99]
+                    .into_iter()
+                    .collect(),
             ),
-            expected
-        );
+            ..Default::default()
+        };
+        assert_eq!(response_metrics_columns(&options), 4);
+        let header = response_metrics_header(&options);
+        assert_eq!(header.matches("<th").count(), 4);
+        assert!(header.contains("<th>95%ile (ms)</th>"));
+        assert!(!header.contains("60%ile"));
+        let row = response_metrics_row(metric.clone(), &options);
+        assert_eq!(row.matches("<td").count(), 4);
+        let footer = response_metrics_footer(&[metric], &options);
+        assert_eq!(footer.matches("<td").count(), 3);
+    }
 
-        let mut expected = expected_prefix.to_owned();
-        expected.push_str(r#"                                    data: [
-                                        
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+    #[test]
+    fn test_first_last_seen() {
+        let first_seen = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 30).unwrap();
+        let last_seen = Local.with_ymd_and_hms(2021, 11, 21, 21, 25, 45).unwrap();
+        let metric = |first_seen, last_seen| RequestMetric {
+            number_of_requests: 10,
+            response_time_average: "5.00".to_string(),
+            response_time_minimum: 3,
+            response_time_maximum: 7,
+            requests_per_second: "2.00".to_string(),
+            requests_per_second_value: 2.00,
+            apdex: "1.00".to_string(),
+            first_seen,
+            last_seen,
+            ..request_metric("GET", "/")
+        };
+
+        // The columns are optional.
+        let options = GooseReportOptions::default();
+        assert!(!request_metrics_header(&options).contains("First seen"));
+        let row = raw_request_metrics_row(metric(Some(first_seen), Some(last_seen)), &options);
+        assert!(!row.contains("2021-11-21"));
+
+        let options = GooseReportOptions {
+            show_first_last_seen: true,
+            ..Default::default()
+        };
+        assert_eq!(request_metrics_columns(&options), 13);
+        assert!(request_metrics_header(&options).contains(
+            "<th>% of Requests</th>\n                        <th>First seen</th>\n                        <th>Last seen</th>"
+        ));
+        let row = raw_request_metrics_row(metric(Some(first_seen), Some(last_seen)), &options);
+        assert_eq!(row.matches("<td").count(), 13);
+        assert!(row.ends_with(
+            "<td class=\"seen\">2021-11-21 21:20:30</td>\n        <td class=\"seen\">2021-11-21 21:25:45</td>\n    </tr>"
+        ));
+
+        // Unknown timestamps leave the cells empty.
+        let row = raw_request_metrics_row(metric(None, None), &options);
+        assert_eq!(row.matches(r#"<td class="seen"></td>"#).count(), 2);
+
+        // Aggregating keeps the earliest first seen and the latest last seen.
+        let mut aggregate = metric(None, Some(first_seen));
+        add_request_metric(&mut aggregate, metric(Some(last_seen), Some(last_seen)));
+        add_request_metric(&mut aggregate, metric(Some(first_seen), None));
+        assert_eq!(aggregate.first_seen, Some(first_seen));
+        assert_eq!(aggregate.last_seen, Some(last_seen));
+    }
+
+    #[test]
+    fn test_connection_reuse() {
+        let metric = |connection_reuse| RequestMetric {
+            number_of_requests: 100,
+            response_time_average: "5.00".to_string(),
+            response_time_minimum: 3,
+            response_time_maximum: 7,
+            requests_per_second: "2.00".to_string(),
+            requests_per_second_value: 2.00,
+            apdex: "1.00".to_string(),
+            connection_reuse,
+            ..request_metric("GET", "/")
+        };
+        let connection_reuse = ConnectionReuse {
+            reused: 90,
+            new: 10,
+        };
+
+        // Without the option, the column is omitted.
+        let options = GooseReportOptions::default();
+        assert!(!request_metrics_header(&options).contains("Conn reuse %"));
+        let row = raw_request_metrics_row(metric(Some(connection_reuse)), &options);
+        assert!(!row.contains("90%"));
+
+        let options = GooseReportOptions {
+            show_connection_reuse: true,
+            ..Default::default()
+        };
+        assert_eq!(request_metrics_columns(&options), 12);
+        assert!(request_metrics_header(&options).contains("<th>Conn reuse %</th>"));
+        let row = raw_request_metrics_row(metric(Some(connection_reuse)), &options);
+        assert_eq!(row.matches("<td").count(), 12);
+        assert!(row.contains("<td>90%</td>\n    </tr>"));
+        let row = raw_request_metrics_row(metric(None), &options);
+        assert!(row.contains("<td></td>\n    </tr>"));
+
+        // Aggregating sums the counts.
+        let mut aggregate = metric(Some(connection_reuse));
+        add_request_metric(
+            &mut aggregate,
+            metric(Some(ConnectionReuse {
+                reused: 0,
+                new: 100,
+            })),
         );
-        assert_eq!(
-            graph_tasks_per_second_template(
-                &data,
-                None,
-                None,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34))
-            ),
-            expected
+        add_request_metric(&mut aggregate, metric(None));
+        assert_eq!(aggregate.connection_reuse.unwrap().percentage(), Some(45.0));
+    }
+
+    #[test]
+    fn test_percentile_labels() {
+        let options = GooseReportOptions {
+            percentile_labels: vec![(0.95, "SLA".to_string()), (1.0, "<timeout>".to_string())],
+            ..Default::default()
+        };
+        let header = response_metrics_header(&options);
+        assert!(header.contains("<th>p95 (SLA) (ms)</th>"));
+        assert!(header.contains("<th>p100 (&lt;timeout&gt;) (ms)</th>"));
+        assert!(header.contains("<th>99%ile (ms)</th>"));
+
+        // The suffix is added to the "Max" label too.
+        let options = GooseReportOptions {
+            max_percentile: MaxPercentile::Max,
+            ..options
+        };
+        assert!(response_metrics_header(&options).contains("<th>Max (&lt;timeout&gt;) (ms)</th>"));
+    }
+
+    #[test]
+    fn test_rate_toggle() {
+        let row = raw_request_metrics_row(
+            RequestMetric {
+                number_of_requests: 120,
+                number_of_failures: 6,
+                response_time_average: "1.00".to_string(),
+                response_time_minimum: 1,
+                response_time_maximum: 1,
+                requests_per_second: "2.00".to_string(),
+                failures_per_second: "0.10".to_string(),
+                requests_per_second_value: 2.00,
+                failures_per_second_value: 0.10,
+                apdex: "1.00".to_string(),
+                ..request_metric("GET", "/")
+            },
+            &GooseReportOptions::default(),
         );
+        assert!(row.contains(r#"<td data-count="120" data-rps="2.00">120</td>"#));
+        assert!(row.contains(r#"<td data-count="6" data-rps="0.10">6</td>"#));
 
-        let mut expected = expected_prefix;
-        expected.push_str(r#"                                    data: [
-                                        [
-                    {
-                        name: 'Starting',
-                        xAxis: '2021-11-21 21:20:32'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:34'
-                    }
-                ],
-                                        [
-                    {
-                        name: 'Stopping',
-                        xAxis: '2021-11-21 21:20:36'
-                    },
-                    {
-                        xAxis: '2021-11-21 21:20:38'
-                    }
-                ],
-                                    ]
-                                },
-                                data: [["2021-11-21 21:20:32",123],["2021-11-21 21:20:33",111],["2021-11-21 21:20:34",99],["2021-11-21 21:20:35",134]],
-                            }
-                        ]
-                    });
-                </script>
-            </div>"#
+        let row = task_metrics_row(TaskMetric {
+            is_task_set: false,
+            task: "0.0".to_string(),
+            name: "index".to_string(),
+            number_of_requests: 1_200,
+            number_of_failures: 0,
+            response_time_average: "1.00".to_string(),
+            response_time_minimum: 1,
+            response_time_maximum: 1,
+            requests_per_second: "20.00".to_string(),
+            failures_per_second: "0.00".to_string(),
+        });
+        assert!(row.contains(r#"<td data-count="1,200" data-rps="20.00">1,200</td>"#));
+
+        let report = build_empty_report("", "", "", &GooseReportOptions::default());
+        assert!(!report.contains("rate-toggle"));
+        let options = GooseReportOptions {
+            rate_toggle: true,
+            ..Default::default()
+        };
+        let report = build_empty_report("", "", "", &options);
+        assert!(report.contains(
+            r#"<button id="rate-toggle" type="button" data-show-rates="Show rates" data-show-counts="Show counts">Show rates</button>"#
+        ));
+        assert!(report.contains("document.querySelectorAll('td[data-count]')"));
+    }
+
+    #[test]
+    fn test_graph_warmup_area() {
+        let data = vec![("2021-11-21 21:20:32".to_string(), 12)];
+        let started = Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 32).unwrap();
+        let warmup = Some((
+            started,
+            Local.with_ymd_and_hms(2021, 11, 21, 21, 20, 42).unwrap(),
+        ));
+
+        // The warmup is shaded even without ramp shading.
+        let graph = graph_eps_template(
+            &data,
+            GraphPhases {
+                warmup,
+                ..Default::default()
+            },
+            &GooseReportOptions {
+                hide_ramp_shading: vec![ReportGraph::ErrorsPerSecond].into_iter().collect(),
+                ..Default::default()
+            },
         );
+        assert_eq!(graph.matches(r#""name":"Warmup""#).count(), 1);
+        assert!(graph.contains(r#""xAxis":"2021-11-21 21:20:42""#));
+
+        // The exported option shades the same warmup.
+        let export = export_graph_option_json(
+            ReportGraph::ErrorsPerSecond,
+            &data,
+            GraphPhases {
+                started: Some(started),
+                warmup,
+                ..Default::default()
+            },
+            &GooseReportOptions::default(),
+        )
+        .unwrap();
+        let option: serde_json::Value = serde_json::from_str(&export).unwrap();
         assert_eq!(
-            graph_tasks_per_second_template(
-                &data,
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 32)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 34)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 36)),
-                Some(Local.ymd(2021, 11, 21).and_hms(21, 20, 38))
-            ),
-            expected
+            option["series"][0]["markArea"]["data"],
+            json!([[
+                { "name": "Warmup", "xAxis": "2021-11-21 21:20:32" },
+                { "xAxis": "2021-11-21 21:20:42" },
+            ]])
         );
     }
 }
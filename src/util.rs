@@ -8,6 +8,7 @@ use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use url::Url;
 
 use crate::GooseError;
@@ -235,10 +236,25 @@ pub fn median(
     0
 }
 
+/// The number of terminal columns used to display a string, counting wide characters
+/// such as CJK ideographs as two columns.
+///
+/// # Example
+/// ```rust
+/// use goose::util;
+///
+/// assert_eq!(util::display_width("/about"), 6);
+/// assert_eq!(util::display_width("/关于"), 5);
+/// ```
+pub fn display_width(string: &str) -> usize {
+    UnicodeWidthStr::width(string)
+}
+
 /// Truncate strings when they're too long to display.
 ///
-/// If a string is longer than the specified max length, this function removes extra
-/// the characters and replaces the last two with a double-period ellipsis.
+/// If a string is wider than the specified max length, as measured by [`display_width`],
+/// this function removes the extra characters and replaces the last two columns with a
+/// double-period ellipsis.
 ///
 /// # Example
 /// ```rust
@@ -249,15 +265,44 @@ pub fn median(
 ///
 /// // All characters are returned as the string is less than 15 characters long.
 /// assert_eq!(util::truncate_string("shorter string", 15), "shorter string");
+///
+/// // Wide characters are two columns wide.
+/// assert_eq!(util::truncate_string("/关于我们的团队", 9), "/关于我..");
 /// ```
 pub fn truncate_string(str_to_truncate: &str, max_length: u64) -> String {
-    let mut string_to_truncate = str_to_truncate.to_string();
-    if string_to_truncate.len() as u64 > max_length {
-        let truncated_length = max_length - 2;
-        string_to_truncate.truncate(truncated_length as usize);
-        string_to_truncate += "..";
+    let max_length = max_length as usize;
+    if display_width(str_to_truncate) <= max_length {
+        return str_to_truncate.to_string();
     }
-    string_to_truncate
+
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in str_to_truncate.chars() {
+        // Control characters aren't displayed.
+        width += UnicodeWidthChar::width(c).unwrap_or(0);
+        if width + 2 > max_length {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated + ".."
+}
+
+/// Truncate a string with [`truncate_string`] if it's too wide, then pad it with spaces
+/// to exactly `width` columns, so columns of text tables align even when they contain
+/// wide characters.
+///
+/// # Example
+/// ```rust
+/// use goose::util;
+///
+/// assert_eq!(util::fit_to_width("/about", 8), "/about  ");
+/// assert_eq!(util::fit_to_width("/关于", 8), "/关于   ");
+/// ```
+pub fn fit_to_width(string: &str, width: usize) -> String {
+    let truncated = truncate_string(string, width as u64);
+    let padding = width.saturating_sub(display_width(&truncated));
+    truncated + &" ".repeat(padding)
 }
 
 /// Determine if a timer expired, with second granularity.
@@ -471,6 +516,17 @@ impl MovingAverage {
         self.count += 1;
         self.average += (item as f32 - self.average) / self.count as f32;
     }
+
+    /// Merges another moving average into this one, weighting each by its item count.
+    pub fn merge(&mut self, other: &MovingAverage) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        self.average =
+            (self.average * self.count as f32 + other.average * other.count as f32) / count as f32;
+        self.count = count;
+    }
 }
 
 impl Default for MovingAverage {
@@ -511,6 +567,26 @@ mod tests {
         assert_eq!(parse_timespan("100hourblah"), 360000);
     }
 
+    #[test]
+    fn unicode_width() {
+        assert_eq!(display_width("GET /about"), 10);
+        assert_eq!(display_width("GET /关于"), 9);
+        assert_eq!(display_width("GET /cafe\u{301}"), 9);
+        assert_eq!(display_width("GET /\u{1f680}"), 7);
+        assert_eq!(display_width("GET /\u{2615}"), 7);
+        assert_eq!(display_width("GET /x\u{20d7}"), 6);
+        assert_eq!(display_width("\u{1100}\u{1161}\u{11a8}"), 2);
+
+        // Wide characters are never split, and the result is never wider than requested.
+        assert_eq!(truncate_string("GET /关于我们", 10), "GET /关..");
+        assert_eq!(truncate_string("GET /关于我们", 11), "GET /关于..");
+        assert_eq!(display_width(&fit_to_width("GET /关于我们的团队", 24)), 24);
+        assert_eq!(
+            display_width(&fit_to_width("GET /关于我们的团队页面很长", 24)),
+            24
+        );
+    }
+
     #[test]
     fn greatest_common_divisor() {
         assert_eq!(gcd(2, 4), 2);